@@ -0,0 +1,191 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::cmd::lsof::ListeningPort;
+
+#[derive(Eq, PartialEq)]
+pub struct AliasError {
+    reason: String,
+}
+
+impl Error for AliasError {}
+
+impl fmt::Debug for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl fmt::Display for AliasError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Friendly names of ports (e.g., `8080` is `dev-api`).
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct AliasMap {
+    aliases: HashMap<u16, String>,
+}
+
+impl AliasMap {
+    /// Read aliases from a file.
+    ///
+    /// See [`AliasMap::parse()`] for the format.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the file cannot be read, or if it is malformed.
+    pub fn from_file(path: &Path) -> Result<Self, AliasError> {
+        let content = fs::read_to_string(path).map_err(|e| AliasError {
+            reason: format!("Cannot read '{}': {e}", path.display()),
+        })?;
+        Self::parse(&content)
+    }
+
+    /// Parse aliases, one per line.
+    ///
+    /// ```text
+    /// # Comment.
+    /// 8080 = "dev-api"
+    /// 5432 = postgres
+    /// ```
+    ///
+    /// This is compatible with the TOML equivalent (integer keys and
+    /// string values), but quotes are optional.
+    ///
+    /// # Errors
+    ///
+    /// Errors if a line is not of the form `PORT = alias`.
+    pub fn parse(content: &str) -> Result<Self, AliasError> {
+        let mut aliases = HashMap::new();
+
+        for (line_number, line) in (1..).zip(content.lines()) {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((port, alias)) = line.split_once('=') else {
+                return Err(AliasError {
+                    reason: format!("Line {line_number}: Expected 'PORT = alias'."),
+                });
+            };
+            let Ok(port) = port.trim().parse::<u16>() else {
+                return Err(AliasError {
+                    reason: format!("Line {line_number}: Invalid port '{}'.", port.trim()),
+                });
+            };
+            let alias = alias.trim();
+            let alias = alias
+                .strip_prefix('"')
+                .and_then(|alias| alias.strip_suffix('"'))
+                .unwrap_or(alias);
+
+            aliases.insert(port, String::from(alias));
+        }
+
+        Ok(Self { aliases })
+    }
+
+    /// Alias of the port number.
+    #[must_use]
+    pub fn get(&self, port: u16) -> Option<&String> {
+        self.aliases.get(&port)
+    }
+
+    /// Alias of the port's port number (`HOST:PORT`).
+    #[must_use]
+    pub fn alias_of(&self, port: &ListeningPort) -> Option<&String> {
+        self.get(port.port_number()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliaserror_display() {
+        let error = AliasError {
+            reason: String::from("an error has occurred"),
+        };
+
+        assert_eq!(error.to_string(), "an error has occurred");
+    }
+
+    #[test]
+    fn parse_regular() {
+        let aliases = AliasMap::parse(
+            r#"
+# Development.
+8080 = "dev-api"
+5432=postgres
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(aliases.get(8080), Some(&String::from("dev-api")));
+        assert_eq!(aliases.get(5432), Some(&String::from("postgres")));
+        assert_eq!(aliases.get(22), None);
+    }
+
+    #[test]
+    fn parse_error_missing_equal_sign() {
+        let res = AliasMap::parse("8080\n").unwrap_err();
+
+        assert_eq!(res.to_string(), "Line 1: Expected 'PORT = alias'.");
+    }
+
+    #[test]
+    fn parse_error_invalid_port() {
+        let res = AliasMap::parse("\n[aliases]\nhttp = \"web\"\n").unwrap_err();
+
+        assert_eq!(res.to_string(), "Line 2: Expected 'PORT = alias'.");
+
+        let res = AliasMap::parse("http = \"web\"\n").unwrap_err();
+
+        assert_eq!(res.to_string(), "Line 1: Invalid port 'http'.");
+    }
+
+    #[test]
+    fn from_file_not_found() {
+        let res = AliasMap::from_file(Path::new("/does/not/exist")).unwrap_err();
+
+        assert!(res
+            .to_string()
+            .starts_with("Cannot read '/does/not/exist': "));
+    }
+
+    #[test]
+    fn alias_of_regular() {
+        let aliases = AliasMap::parse("8080 = dev-api").unwrap();
+        let mut port = ListeningPort::new();
+        port.name = String::from("[::1]:8080");
+
+        assert_eq!(aliases.alias_of(&port), Some(&String::from("dev-api")));
+
+        port.name = String::from("*:http");
+
+        assert_eq!(aliases.alias_of(&port), None);
+    }
+}
@@ -16,5 +16,9 @@
 
 #![allow(clippy::module_name_repetitions)]
 
+pub mod backend;
+pub mod kill;
 pub mod lsof;
+pub mod netstat;
 pub mod ps;
+pub mod ss;
@@ -0,0 +1,213 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cmd::lsof::{ListeningPort, LsofError};
+
+/// A way of listing listening ports (e.g., `lsof`).
+pub type Backend = Box<dyn Fn() -> Result<Vec<ListeningPort>, LsofError>>;
+
+/// Try backends in order, until one of them succeeds.
+///
+/// By default, any error falls back to the next backend. Use
+/// [`BackendSelector::fall_back_if()`] to stop on real failures.
+///
+/// ```
+/// use ports::{BackendSelector, ListeningPortBuilder, LsofError};
+///
+/// let ports = BackendSelector::new()
+///     .backend(|| Err(LsofError::with_reason("Unable to locate lsof.")))
+///     .backend(|| Ok(vec![ListeningPortBuilder::new().name("*:80").build()]))
+///     .listening_ports()
+///     .unwrap();
+///
+/// assert_eq!(ports[0].name, "*:80");
+/// ```
+pub struct BackendSelector {
+    backends: Vec<Backend>,
+    fall_back_if: fn(&LsofError) -> bool,
+}
+
+impl Default for BackendSelector {
+    fn default() -> Self {
+        Self {
+            backends: Vec::new(),
+            fall_back_if: |_| true,
+        }
+    }
+}
+
+impl BackendSelector {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only try the next backend on errors `fall_back_if` is true for
+    /// (e.g., [`LsofError::is_missing_executable()`]). Other errors are
+    /// returned as-is.
+    #[must_use]
+    pub fn fall_back_if(mut self, fall_back_if: fn(&LsofError) -> bool) -> Self {
+        self.fall_back_if = fall_back_if;
+        self
+    }
+
+    #[must_use]
+    pub fn backend(
+        mut self,
+        backend: impl Fn() -> Result<Vec<ListeningPort>, LsofError> + 'static,
+    ) -> Self {
+        self.backends.push(Box::new(backend));
+        self
+    }
+
+    /// Ports of the first backend that succeeds.
+    ///
+    /// # Errors
+    ///
+    /// Errors if every backend fails, with the error of the first one.
+    ///  Being told `lsof` is missing is more helpful than `netstat`.
+    ///  Errors that don't fall back are returned right away.
+    pub fn listening_ports(&self) -> Result<Vec<ListeningPort>, LsofError> {
+        let mut first_error = None;
+        for backend in &self.backends {
+            match backend() {
+                Ok(ports) => return Ok(ports),
+                Err(err) if (self.fall_back_if)(&err) => {
+                    first_error.get_or_insert(err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Err(first_error.unwrap_or_else(|| LsofError::with_reason("No backend to list ports with.")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn available(name: &str) -> impl Fn() -> Result<Vec<ListeningPort>, LsofError> {
+        let port = ListeningPortBuilder::new().command(name).build();
+        move || Ok(vec![port.clone()])
+    }
+
+    fn missing(name: &str) -> impl Fn() -> Result<Vec<ListeningPort>, LsofError> {
+        let name = String::from(name);
+        move || Err(LsofError::missing_executable(&name))
+    }
+
+    #[test]
+    fn first_backend_available() {
+        let ports = BackendSelector::new()
+            .backend(available("lsof"))
+            .backend(available("ss"))
+            .backend(available("netstat"))
+            .listening_ports()
+            .unwrap();
+
+        assert_eq!(ports[0].command, "lsof");
+    }
+
+    #[test]
+    fn falls_back_to_second_backend() {
+        let ports = BackendSelector::new()
+            .backend(missing("lsof"))
+            .backend(available("ss"))
+            .backend(available("netstat"))
+            .listening_ports()
+            .unwrap();
+
+        assert_eq!(ports[0].command, "ss");
+    }
+
+    #[test]
+    fn falls_back_to_last_backend() {
+        let ports = BackendSelector::new()
+            .backend(missing("lsof"))
+            .backend(missing("ss"))
+            .backend(available("netstat"))
+            .listening_ports()
+            .unwrap();
+
+        assert_eq!(ports[0].command, "netstat");
+    }
+
+    #[test]
+    fn all_backends_missing_reports_first_error() {
+        let res = BackendSelector::new()
+            .backend(missing("lsof"))
+            .backend(missing("ss"))
+            .backend(missing("netstat"))
+            .listening_ports()
+            .unwrap_err();
+
+        assert_eq!(
+            res.to_string(),
+            "Unable to locate the lsof executable on the system."
+        );
+    }
+
+    #[test]
+    fn fall_back_if_missing() {
+        let ports = BackendSelector::new()
+            .backend(missing("lsof"))
+            .backend(available("ss"))
+            .fall_back_if(LsofError::is_missing_executable)
+            .listening_ports()
+            .unwrap();
+
+        assert_eq!(ports[0].command, "ss");
+    }
+
+    #[test]
+    fn fall_back_if_stops_on_other_errors() {
+        let res = BackendSelector::new()
+            .backend(|| Err(LsofError::with_reason("The lsof command has failed.")))
+            .backend(available("ss"))
+            .fall_back_if(LsofError::is_missing_executable)
+            .listening_ports()
+            .unwrap_err();
+
+        assert_eq!(res.to_string(), "The lsof command has failed.");
+    }
+
+    #[test]
+    fn no_backends() {
+        let res = BackendSelector::new().listening_ports().unwrap_err();
+
+        assert_eq!(res.to_string(), "No backend to list ports with.");
+    }
+
+    #[test]
+    fn stops_at_first_success() {
+        let calls = Rc::new(Cell::new(0));
+        let counter = Rc::clone(&calls);
+
+        BackendSelector::new()
+            .backend(available("lsof"))
+            .backend(move || {
+                counter.set(counter.get() + 1);
+                Ok(Vec::new())
+            })
+            .listening_ports()
+            .unwrap();
+
+        assert_eq!(calls.get(), 0);
+    }
+}
@@ -0,0 +1,193 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::process::{Command, Output};
+
+#[derive(Eq, PartialEq)]
+pub struct KillError {
+    reason: String,
+}
+
+impl KillError {
+    #[must_use]
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
+}
+
+impl Error for KillError {}
+
+impl fmt::Debug for KillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl fmt::Display for KillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Use `kill` to send `SIGTERM` to processes.
+///
+/// Processes are asked to terminate, not forced to. Does nothing if
+/// `pids` is empty.
+///
+/// # Errors
+///
+/// Errors if the `kill` executable is not found, or if any of the
+///  processes could not be signaled (e.g., it doesn't exist anymore,
+///  or belongs to another user).
+#[cfg(not(tarpaulin_include))]
+pub fn kill_processes(pids: &[u32]) -> Result<(), KillError> {
+    #![allow(unreachable_code)]
+    if pids.is_empty() {
+        return Ok(());
+    }
+
+    #[cfg(test)]
+    {
+        // Never signal anything for real in tests. What would be
+        // signaled is tested through `kill_command()`.
+        return Ok(());
+    }
+
+    match kill_command(pids).output() {
+        Ok(output) => handle_output_ok(&output),
+        Err(_) => handle_output_err(),
+    }
+}
+
+/// `kill -TERM <pid> ...`.
+fn kill_command(pids: &[u32]) -> Command {
+    let mut command = Command::new("kill");
+    command.arg("-TERM");
+    command.args(pids.iter().map(u32::to_string));
+    command
+}
+
+fn handle_output_ok(output: &Output) -> Result<(), KillError> {
+    if output.status.success() {
+        // Exit 0.
+        return Ok(());
+    }
+    // Non-zero exit code. `kill` still signals the PIDs it can, and
+    // says which ones it couldn't on stderr.
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        return Err(KillError {
+            reason: String::from("The kill command has failed in an unexpected way."),
+        });
+    }
+    Err(KillError {
+        reason: format!("Some processes could not be killed: {stderr}"),
+    })
+}
+
+fn handle_output_err() -> Result<(), KillError> {
+    Err(KillError {
+        reason: String::from("Unable to locate the kill executable on the system."),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn kill_error_with_reason() {
+        let error = KillError::with_reason("kill failed.");
+
+        assert_eq!(error.to_string(), "kill failed.");
+    }
+
+    #[test]
+    fn kill_command_targets_pids() {
+        let command = kill_command(&[723, 1174]);
+
+        assert_eq!(command.get_program(), "kill");
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-TERM", "723", "1174"]);
+    }
+
+    #[test]
+    fn kill_processes_empty() {
+        assert_eq!(kill_processes(&[]), Ok(()));
+    }
+
+    #[test]
+    fn kill_successful() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"".to_vec(),
+            stderr: b"".to_vec(),
+        };
+
+        assert_eq!(handle_output_ok(&output), Ok(()));
+    }
+
+    #[test]
+    fn kill_unsuccessful_with_reason() {
+        let output = Output {
+            status: ExitStatus::from_raw(1),
+            stdout: b"".to_vec(),
+            stderr: b"kill: (1174) - Operation not permitted\n".to_vec(),
+        };
+
+        assert_eq!(
+            handle_output_ok(&output),
+            Err(KillError {
+                reason: String::from(
+                    "Some processes could not be killed: kill: (1174) - Operation not permitted"
+                ),
+            })
+        );
+    }
+
+    #[test]
+    fn kill_unsuccessful_without_reason() {
+        let output = Output {
+            status: ExitStatus::from_raw(1),
+            stdout: b"".to_vec(),
+            stderr: b"".to_vec(),
+        };
+
+        assert_eq!(
+            handle_output_ok(&output),
+            Err(KillError {
+                reason: String::from("The kill command has failed in an unexpected way."),
+            })
+        );
+    }
+
+    #[test]
+    fn kill_executable_not_found() {
+        assert_eq!(
+            handle_output_err(),
+            Err(KillError {
+                reason: String::from("Unable to locate the kill executable on the system."),
+            })
+        );
+    }
+}
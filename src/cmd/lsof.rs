@@ -14,16 +14,75 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp::Ordering;
 use std::error::Error;
 use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::process::{Command, Output};
 use std::str::Lines;
 
+use crate::cmd::backend::BackendSelector;
+use crate::cmd::netstat::{Netstat, NetstatError};
 use crate::cmd::ps::ProcessInfo;
+use crate::cmd::ss::{Ss, SsError};
+
+/// What went wrong, independently of how the reason is worded.
+///
+/// Shared by [`LsofError`], [`SsError`] and [`NetstatError`], so it
+/// survives conversions between them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LsofErrorKind {
+    /// The executable is not installed.
+    MissingExecutable,
+    /// The executable ran, but failed.
+    CommandFailed,
+    /// The output is not as expected.
+    Parse,
+    Other,
+}
 
 #[derive(Eq, PartialEq)]
 pub struct LsofError {
-    reason: &'static str,
+    kind: LsofErrorKind,
+    reason: String,
+}
+
+impl LsofError {
+    #[must_use]
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self::with_kind(LsofErrorKind::Other, reason)
+    }
+
+    #[must_use]
+    pub fn with_kind(kind: LsofErrorKind, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            reason: reason.into(),
+        }
+    }
+
+    /// The `executable` (e.g., `lsof`, or `ss`) is not installed.
+    #[must_use]
+    pub fn missing_executable(executable: &str) -> Self {
+        Self::with_kind(
+            LsofErrorKind::MissingExecutable,
+            format!("Unable to locate the {executable} executable on the system."),
+        )
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> LsofErrorKind {
+        self.kind
+    }
+
+    /// Whether this is a [`LsofErrorKind::MissingExecutable`] error.
+    ///
+    /// `SsError`s and `NetstatError`s converted into an `LsofError`
+    /// keep their kind.
+    #[must_use]
+    pub fn is_missing_executable(&self) -> bool {
+        self.kind == LsofErrorKind::MissingExecutable
+    }
 }
 
 impl Error for LsofError {}
@@ -40,15 +99,49 @@ impl fmt::Display for LsofError {
     }
 }
 
+impl From<SsError> for LsofError {
+    fn from(error: SsError) -> Self {
+        Self::with_kind(error.kind(), error.to_string())
+    }
+}
+
+impl From<NetstatError> for LsofError {
+    fn from(error: NetstatError) -> Self {
+        Self::with_kind(error.kind(), error.to_string())
+    }
+}
+
+#[derive(Eq, PartialEq)]
+pub struct ParseError {
+    reason: String,
+}
+
+impl Error for ParseError {}
+
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ListeningPort {
     pub command: String,
     pub pid: String,
     pub user: String,
+    #[cfg_attr(feature = "serde", serde(rename = "type"))]
     pub type_: String,
     pub node: String,
     pub name: String,
     pub pinfo: Option<ProcessInfo>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _cannot_instantiate: std::marker::PhantomData<()>,
 }
 
@@ -71,6 +164,77 @@ impl ListeningPort {
         let pinfo = process_info.iter().find(|process| process.pid == self.pid);
         self.pinfo = pinfo.cloned();
     }
+
+    /// Full command line of the process (e.g., `python3 -m http.server`).
+    ///
+    /// `None` unless the port has been enriched with process info, which
+    /// only happens in verbose modes (`-vv`, `-vvv`).
+    #[must_use]
+    pub fn full_command(&self) -> Option<&str> {
+        self.pinfo.as_ref().map(|pinfo| pinfo.command.as_str())
+    }
+
+    /// Structured `HOST:PORT` name.
+    ///
+    /// See [`Address::parse()`] for the supported formats.
+    #[must_use]
+    pub fn address(&self) -> Option<Address> {
+        Address::parse(&self.name)
+    }
+
+    /// Port number, from the `HOST:PORT` name.
+    ///
+    /// Works with IPv6 (`[::1]:1337`), wildcard (`*:80`), and bare
+    /// (`1337`) names. `None` if the port is not numeric (e.g., `*:http`).
+    #[must_use]
+    pub fn port_number(&self) -> Option<u16> {
+        self.address().map(|address| address.port)
+    }
+
+    /// Whether the port is only reachable from the machine itself.
+    ///
+    /// True for `127.0.0.1`, `[::1]` and `::1` hosts (and the rest of
+    /// `127.0.0.0/8`), whatever the port. Wildcards (`*`) are not
+    /// loopback, they listen on every interface.
+    #[must_use]
+    pub fn is_loopback(&self) -> bool {
+        let Some((host, _port)) = self.name.rsplit_once(':') else {
+            return false;
+        };
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        let host = host.split_once('%').map_or(host, |(ip, _zone)| ip);
+        host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+    }
+
+    /// Whether the port listens on every interface.
+    ///
+    /// True for `*`, `0.0.0.0` and `[::]` hosts, whatever the port. As
+    /// with [`Address::parse()`], a bare port (`1337`) is a wildcard.
+    #[must_use]
+    pub fn is_wildcard(&self) -> bool {
+        let Some((host, _port)) = self.name.rsplit_once(':') else {
+            return self.port_number().is_some();
+        };
+        if host == "*" {
+            return true;
+        }
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        host.parse::<IpAddr>().is_ok_and(|ip| ip.is_unspecified())
+    }
+
+    /// Best available name for the process.
+    ///
+    /// `lsof` truncates command names (e.g., `docker-proxy` becomes
+    /// `docker-pr`). If the port has been enriched with process info,
+    /// the name of the executable is used instead.
+    #[must_use]
+    pub fn process_name(&self) -> &str {
+        self.pinfo
+            .as_ref()
+            .map(ProcessInfo::command_basename)
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.command)
+    }
 }
 
 impl Default for ListeningPort {
@@ -79,21 +243,466 @@ impl Default for ListeningPort {
     }
 }
 
+/// By port number, with non-numeric ports (e.g., `*:http`) last.
+///
+/// Ports with the same number are ordered by their other fields, so
+/// that ordering stays consistent with equality.
+impl Ord for ListeningPort {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let port = |port: &Self| {
+            let number = port.port_number();
+            (number.is_none(), number)
+        };
+        port(self)
+            .cmp(&port(other))
+            .then_with(|| self.name.cmp(&other.name))
+            .then_with(|| self.command.cmp(&other.command))
+            .then_with(|| self.pid.cmp(&other.pid))
+            .then_with(|| self.user.cmp(&other.user))
+            .then_with(|| self.type_.cmp(&other.type_))
+            .then_with(|| self.node.cmp(&other.node))
+            .then_with(|| self.pinfo.cmp(&other.pinfo))
+    }
+}
+
+impl PartialOrd for ListeningPort {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// `COMMAND(PID) USER NODE HOST:PORT`, on one line.
+///
+/// For example, `docker-pr(2673) root TCP *:333`. Empty fields are
+/// left out, along with their separating space.
+impl fmt::Display for ListeningPort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}({})", self.command, self.pid)?;
+        for field in [&self.user, &self.node, &self.name] {
+            if !field.is_empty() {
+                write!(f, " {field}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single line of `lsof -i -n -P` output.
+impl TryFrom<&str> for ListeningPort {
+    type Error = ParseError;
+
+    /// Parse a single line of `lsof -i -n -P` output.
+    ///
+    /// Columns are expected in the standard order (`COMMAND PID USER
+    /// FD TYPE DEVICE SIZE/OFF NODE NAME`). The socket state (e.g.,
+    /// `(LISTEN)`), if any, is ignored.
+    fn try_from(line: &str) -> Result<Self, Self::Error> {
+        const COLUMNS: usize = 9;
+
+        let columns: Vec<&str> = line.split_ascii_whitespace().collect();
+        let [command, pid, user, _fd, type_, _device, _size_off, node, name, ..] =
+            columns.as_slice()
+        else {
+            return Err(ParseError {
+                reason: format!(
+                    "The lsof line has {} columns, expected {COLUMNS}.",
+                    columns.len()
+                ),
+            });
+        };
+
+        Ok(ListeningPortBuilder::new()
+            .command(command)
+            .pid(pid)
+            .user(user)
+            .type_(type_)
+            .node(node)
+            .name(name)
+            .build())
+    }
+}
+
+/// Host part of an [`Address`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Host {
+    /// All interfaces (`*`).
+    Wildcard,
+    IPv4(Ipv4Addr),
+    IPv6(Ipv6Addr),
+}
+
+/// As `lsof` prints it: `*`, `127.0.0.1`, or `[::1]`.
+impl fmt::Display for Host {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wildcard => write!(f, "*"),
+            Self::IPv4(ip) => write!(f, "{ip}"),
+            Self::IPv6(ip) => write!(f, "[{ip}]"),
+        }
+    }
+}
+
+/// Parsed `HOST:PORT` name of a [`ListeningPort`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Address {
+    pub host: Host,
+    pub port: u16,
+}
+
+impl Address {
+    /// Parse a `lsof` name.
+    ///
+    /// - `*:80`: Wildcard.
+    /// - `127.0.0.1:8080`: IPv4.
+    /// - `[::1]:443`: IPv6 (a `%zone` suffix is ignored).
+    /// - `1337`: Bare port, considered a wildcard.
+    ///
+    /// Returns `None` if the port is not numeric (e.g., `*:http`), or if
+    /// the host is not an IP address (`lsof` is run with `-n`, so names
+    /// are not resolved).
+    #[must_use]
+    pub fn parse(name: &str) -> Option<Self> {
+        let Some((host, port)) = name.rsplit_once(':') else {
+            return Some(Self {
+                host: Host::Wildcard,
+                port: name.parse().ok()?,
+            });
+        };
+        let port = port.parse().ok()?;
+
+        let host = if host == "*" {
+            Host::Wildcard
+        } else if let Some(ip) = host.strip_prefix('[').and_then(|ip| ip.strip_suffix(']')) {
+            let ip = ip.split_once('%').map_or(ip, |(ip, _zone)| ip);
+            Host::IPv6(ip.parse().ok()?)
+        } else {
+            Host::IPv4(host.parse().ok()?)
+        };
+
+        Some(Self { host, port })
+    }
+}
+
+/// `HOST:PORT`, as `lsof` prints it.
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+/// Build a [`ListeningPort`] (e.g., for test fixtures).
+///
+/// ```
+/// use ports::ListeningPortBuilder;
+///
+/// let port = ListeningPortBuilder::new()
+///     .command("nginx")
+///     .pid("1234")
+///     .name("*:80")
+///     .build();
+///
+/// assert_eq!(port.name, "*:80");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ListeningPortBuilder {
+    port: ListeningPort,
+}
+
+impl ListeningPortBuilder {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn command(mut self, command: &str) -> Self {
+        self.port.command = String::from(command);
+        self
+    }
+
+    #[must_use]
+    pub fn pid(mut self, pid: &str) -> Self {
+        self.port.pid = String::from(pid);
+        self
+    }
+
+    #[must_use]
+    pub fn user(mut self, user: &str) -> Self {
+        self.port.user = String::from(user);
+        self
+    }
+
+    #[must_use]
+    pub fn type_(mut self, type_: &str) -> Self {
+        self.port.type_ = String::from(type_);
+        self
+    }
+
+    #[must_use]
+    pub fn node(mut self, node: &str) -> Self {
+        self.port.node = String::from(node);
+        self
+    }
+
+    /// `HOST:PORT` (e.g., `*:80`, `127.0.0.1:8080`).
+    #[must_use]
+    pub fn name(mut self, name: &str) -> Self {
+        self.port.name = String::from(name);
+        self
+    }
+
+    #[must_use]
+    pub fn pinfo(mut self, pinfo: ProcessInfo) -> Self {
+        self.port.pinfo = Some(pinfo);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> ListeningPort {
+        self.port
+    }
+}
+
+/// Whether to list UDP sockets alongside listening TCP sockets.
+///
+/// UDP is connectionless, so UDP sockets never are in the `LISTEN`
+/// state. Instead, all unconnected UDP sockets are considered to be
+/// listening.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IncludeUdp {
+    Yes,
+    No,
+}
+
+/// Whether `lsof` resolves IP addresses to hostnames.
+///
+/// It doesn't by default (`-n`), because every address is a DNS lookup,
+/// which can make `lsof` very slow.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResolveHostnames {
+    Yes,
+    No,
+}
+
+/// Whether `lsof` resolves port numbers to service names.
+///
+/// It doesn't by default (`-P`), names are looked up in `/etc/services`
+/// and are not parsed back into numbers (e.g., `*:http` for `*:80`).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ResolveServiceNames {
+    Yes,
+    No,
+}
+
 pub struct Lsof;
 
 impl Lsof {
     /// Use `lsof` to list listening ports.
     ///
+    /// If `lsof` is not installed, falls back to [`Ss`], then to
+    /// [`Netstat`]. Both list TCP ports only, so there is no fallback
+    /// with [`IncludeUdp::Yes`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `lsof` executable is not found (and neither are the
+    ///  fallbacks), or if the command exits with a non-zero exit code.
+    pub fn listening_ports(include_udp: IncludeUdp) -> Result<Vec<ListeningPort>, LsofError> {
+        Self::listening_ports_with(
+            || Self::lsof(ResolveHostnames::No, ResolveServiceNames::No),
+            include_udp,
+        )
+    }
+
+    fn listening_ports_with(
+        lsof: impl Fn() -> Result<String, LsofError> + 'static,
+        include_udp: IncludeUdp,
+    ) -> Result<Vec<ListeningPort>, LsofError> {
+        let lsof = move || Self::parse(&lsof()?, include_udp);
+        if include_udp == IncludeUdp::Yes {
+            return lsof();
+        }
+        BackendSelector::new()
+            .backend(lsof)
+            .backend(|| Ss::listening_ports().map_err(LsofError::from))
+            .backend(|| Netstat::listening_ports().map_err(LsofError::from))
+            .fall_back_if(LsofError::is_missing_executable)
+            .listening_ports()
+    }
+
+    /// Use `lsof` to list UDP sockets only (both IPv4 and IPv6).
+    ///
+    /// `lsof -i` already lists UDP sockets, so there is no need for a
+    /// separate `lsof -i udp`. As with [`IncludeUdp::Yes`], sockets
+    /// connected to a peer are left out.
+    ///
     /// # Errors
     ///
     /// Errors if the `lsof` executable is not found, or if the command
     ///  exits with a non-zero exit code.
-    pub fn listening_ports() -> Result<Vec<ListeningPort>, LsofError> {
-        let output = Self::lsof()?;
-        let mut output = output.lines();
+    pub fn udp_ports() -> Result<Vec<ListeningPort>, LsofError> {
+        let mut ports = Self::listening_ports(IncludeUdp::Yes)?;
+        ports.retain(|port| port.node.eq_ignore_ascii_case("UDP"));
+        Ok(ports)
+    }
+
+    /// Use `lsof -U` to list listening Unix domain sockets.
+    ///
+    /// The `NODE` of a Unix socket is an inode number, and its `NAME` a
+    /// path (e.g., `/run/dbus/system_bus_socket`).
+    ///
+    /// Linux only. Elsewhere (e.g., macOS), `lsof -U` doesn't print the
+    /// socket state, so listening sockets can't be told apart.
+    ///
+    /// # Errors
+    ///
+    /// Errors if not on Linux, if the `lsof` executable is not found,
+    /// or if the command exits with a non-zero exit code.
+    pub fn unix_sockets() -> Result<Vec<ListeningPort>, LsofError> {
+        if !cfg!(target_os = "linux") {
+            return Err(LsofError::with_reason(
+                "Listing Unix domain sockets is only supported on Linux.",
+            ));
+        }
+        let output = Self::lsof_unix()?;
+        Self::parse_unix(&output)
+    }
+
+    /// Raw `lsof -i -n -P` output, unparsed.
+    ///
+    /// For logging it, or for parsing it differently. It can be turned
+    /// into ports later with [`Lsof::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `lsof` executable is not found, or if the command
+    ///  exits with a non-zero exit code.
+    pub fn listening_ports_raw() -> Result<String, LsofError> {
+        Self::lsof(ResolveHostnames::No, ResolveServiceNames::No)
+    }
+
+    /// Like [`Lsof::listening_ports_raw()`], but `lsof` may resolve IP
+    /// addresses to hostnames (e.g., `localhost:631`).
+    ///
+    /// Shorthand for [`Lsof::listening_ports_raw_resolved()`] with
+    /// [`ResolveHostnames::Yes`] and [`ResolveServiceNames::No`]. This
+    /// can be very slow, each address is a DNS lookup.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `lsof` executable is not found, or if the command
+    ///  exits with a non-zero exit code.
+    pub fn listening_ports_raw_with_hostnames() -> Result<String, LsofError> {
+        Self::listening_ports_raw_resolved(ResolveHostnames::Yes, ResolveServiceNames::No)
+    }
+
+    /// Like [`Lsof::listening_ports_raw()`], but `lsof` may resolve IP
+    /// addresses to hostnames, and port numbers to service names (e.g.,
+    /// `*:http`).
+    ///
+    /// Ports with a service name have no [`ListeningPort::port_number()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `lsof` executable is not found, or if the command
+    ///  exits with a non-zero exit code.
+    pub fn listening_ports_raw_resolved(
+        resolve_hostnames: ResolveHostnames,
+        resolve_service_names: ResolveServiceNames,
+    ) -> Result<String, LsofError> {
+        Self::lsof(resolve_hostnames, resolve_service_names)
+    }
+
+    /// Parse `lsof -i -n -P` output (e.g., captured on another machine).
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing or incomplete.
+    pub fn parse(input: &str, include_udp: IncludeUdp) -> Result<Vec<ListeningPort>, LsofError> {
+        let mut output = input.lines();
+
+        let header_columns = Self::extract_header_columns(&mut output)?;
+        let detail_lines = Self::extract_detail_lines_of_listening_ports(&mut output, include_udp);
+
+        Ok(Self::map_detail_values_to_properties(
+            &header_columns,
+            &detail_lines,
+        ))
+    }
+
+    /// Parse `lsof -U -n` output, as printed on Linux.
+    ///
+    /// Only listening sockets bound to a path are kept. The socket type
+    /// (e.g., `type=STREAM`) is dropped from the name.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing or incomplete.
+    pub fn parse_unix(input: &str) -> Result<Vec<ListeningPort>, LsofError> {
+        let mut output = input.lines();
+
+        let header_columns = Self::extract_header_columns(&mut output)?;
+        let detail_lines: Vec<Vec<&str>> = output
+            .filter_map(Self::listening_unix_socket_columns)
+            // Unnamed sockets have no `NAME` left.
+            .filter(|columns| columns.len() >= header_columns.len())
+            .collect();
+
+        Ok(Self::map_detail_values_to_properties(
+            &header_columns,
+            &detail_lines,
+        ))
+    }
+
+    /// Like [`Lsof::listening_ports()`], but with [`Lsof::parse_strict()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `lsof` executable is not found, if the command
+    ///  exits with a non-zero exit code, or if a line is missing columns.
+    pub fn listening_ports_strict(
+        include_udp: IncludeUdp,
+    ) -> Result<Vec<ListeningPort>, LsofError> {
+        let output = Self::lsof(ResolveHostnames::No, ResolveServiceNames::No)?;
+        Self::parse_strict(&output, include_udp)
+    }
+
+    /// Parse `lsof` output, rejecting lines with missing columns.
+    ///
+    /// The regular parser assumes every listening socket line has a
+    /// value for each column of the header. This checks it does, and
+    /// reports the (1-based) number of the first line that doesn't.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing or incomplete, or if a detail
+    ///  line has fewer columns than the header.
+    pub fn parse_strict(
+        input: &str,
+        include_udp: IncludeUdp,
+    ) -> Result<Vec<ListeningPort>, LsofError> {
+        let mut output = input.lines();
 
         let header_columns = Self::extract_header_columns(&mut output)?;
-        let detail_lines = Self::extract_detail_lines_of_listening_ports(&mut output);
+
+        let mut detail_lines = Vec::new();
+        // Line 1 is the header.
+        for (line_number, line) in (2..).zip(output) {
+            let Some(columns) = Self::listening_socket_columns(line, include_udp) else {
+                continue;
+            };
+            if columns.len() < header_columns.len() {
+                return Err(LsofError {
+                    kind: LsofErrorKind::Parse,
+                    reason: format!(
+                        "Line {line_number} of the lsof output has {} columns, expected {}.",
+                        columns.len(),
+                        header_columns.len()
+                    ),
+                });
+            }
+            detail_lines.push(columns);
+        }
 
         Ok(Self::map_detail_values_to_properties(
             &header_columns,
@@ -102,8 +711,12 @@ impl Lsof {
     }
 
     #[cfg(not(tarpaulin_include))]
-    fn lsof() -> Result<String, LsofError> {
+    fn lsof(
+        resolve_hostnames: ResolveHostnames,
+        resolve_service_names: ResolveServiceNames,
+    ) -> Result<String, LsofError> {
         #![allow(unreachable_code)]
+        #![cfg_attr(test, allow(unused_variables))] // The fixture is as-is.
         #[cfg(test)]
         {
             let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -112,13 +725,7 @@ impl Lsof {
             return Ok(output);
         }
 
-        // Note: The `-F` options doesn't have everything we need, or at
-        // least not in a ready-to-print way.
-        let output = Command::new("lsof")
-            .arg("-i") // -i List IP sockets.
-            .arg("-n") // -n Do not resolve hostnames (no DNS).
-            .arg("-P") // -P Do not resolve port names (list port number instead of its name).
-            .output();
+        let output = Self::lsof_command(resolve_hostnames, resolve_service_names).output();
 
         match output {
             Ok(output) => Self::handle_output_ok(&output),
@@ -126,39 +733,82 @@ impl Lsof {
         }
     }
 
-    fn handle_output_ok(output: &Output) -> Result<String, LsofError> {
-        if output.status.success() {
-            // Exit 0.
-            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
-        } else {
-            // Non-zero exit code.
-            let exit_code = output.status.code();
-            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    #[cfg(not(tarpaulin_include))]
+    fn lsof_unix() -> Result<String, LsofError> {
+        #![allow(unreachable_code)]
+        #[cfg(test)]
+        {
+            let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/lsof-unix.txt");
+            let output = std::fs::read_to_string(fixture).expect("cannot read test fixture");
+            return Ok(output);
+        }
 
-            // Exit 1 can also mean "nothing found", in which case
-            // stderr is empty. "Nothing found" is not an error for us.
-            if (matches!(exit_code, Some(1)) || exit_code.is_none()) && stderr.trim().is_empty() {
-                // Fake an exit 0 with just the mandatory headers.
-                return Ok(Self::headers().join(" "));
-            }
+        let output = Self::lsof_unix_command().output();
+
+        match output {
+            Ok(output) => Self::handle_output_ok(&output),
+            Err(_) => Self::handle_output_err(),
+        }
+    }
+
+    fn lsof_unix_command() -> Command {
+        let mut command = Command::new("lsof");
+        command.arg("-U"); // -U List Unix domain sockets.
+        command.arg("-n"); // -n Do not resolve hostnames (no DNS).
+        command
+    }
+
+    fn lsof_command(
+        resolve_hostnames: ResolveHostnames,
+        resolve_service_names: ResolveServiceNames,
+    ) -> Command {
+        // Note: The `-F` options doesn't have everything we need, or at
+        // least not in a ready-to-print way.
+        let mut command = Command::new("lsof");
+        command.arg("-i"); // -i List IP sockets.
+        if resolve_hostnames == ResolveHostnames::No {
+            command.arg("-n"); // -n Do not resolve hostnames (no DNS).
+        }
+        if resolve_service_names == ResolveServiceNames::No {
+            command.arg("-P"); // -P Do not resolve port names (list port number instead of its name).
+        }
+        command
+    }
+
+    fn handle_output_ok(output: &Output) -> Result<String, LsofError> {
+        if output.status.success() {
+            // Exit 0.
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            // Non-zero exit code.
+            let exit_code = output.status.code();
+            let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+
+            // Exit 1 can also mean "nothing found", in which case
+            // stderr is empty. "Nothing found" is not an error for us.
+            if (matches!(exit_code, Some(1)) || exit_code.is_none()) && stderr.trim().is_empty() {
+                // Fake an exit 0 with just the mandatory headers.
+                return Ok(Self::headers().join(" "));
+            }
 
             Err(LsofError {
-                reason: "The lsof command has failed in an unexpected way.",
+                kind: LsofErrorKind::CommandFailed,
+                reason: String::from("The lsof command has failed in an unexpected way."),
             })
         }
     }
 
     fn handle_output_err() -> Result<String, LsofError> {
-        Err(LsofError {
-            reason: "Unable to locate the lsof executable on the system.",
-        })
+        Err(LsofError::missing_executable("lsof"))
     }
 
     /// Extract first line as column titles.
     fn extract_header_columns(output: &mut Lines) -> Result<Vec<String>, LsofError> {
         let Some(header) = output.next() else {
             return Err(LsofError {
-                reason: "The lsof output is missing the header.",
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The lsof output is missing the header."),
             });
         };
         let header = header.to_ascii_uppercase(); // To make sure.
@@ -166,7 +816,8 @@ impl Lsof {
 
         if !Self::header_contains_all_properties(&header) {
             return Err(LsofError {
-                reason: "The lsof output is missing expected properties.",
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The lsof output is missing expected properties."),
             });
         }
 
@@ -187,25 +838,57 @@ impl Lsof {
     }
 
     /// Extract the rest of the output as detail lines.
-    fn extract_detail_lines_of_listening_ports<'a>(output: &'a mut Lines) -> Vec<Vec<&'a str>> {
+    fn extract_detail_lines_of_listening_ports<'a>(
+        output: &'a mut Lines,
+        include_udp: IncludeUdp,
+    ) -> Vec<Vec<&'a str>> {
         output
-            // Probably overkill, but we case-insensitively remove the
-            // "(LISTEN)" property before collecting the line, as it
-            // doesn't have its own column (which would mess with the
-            // subsequent column mapping).
-            .filter_map(|line| {
-                let mut line: Vec<&str> = line.split_ascii_whitespace().collect();
-                for i in 0..line.len() {
-                    if line[i].to_ascii_uppercase() == "(LISTEN)" {
-                        line.remove(i);
-                        return Some(line);
-                    }
-                }
-                None
-            })
+            .filter_map(|line| Self::listening_socket_columns(line, include_udp))
             .collect()
     }
 
+    /// Columns of the line, if it is a listening socket.
+    fn listening_socket_columns(line: &str, include_udp: IncludeUdp) -> Option<Vec<&str>> {
+        // Probably overkill, but we case-insensitively remove the
+        // "(LISTEN)" property before collecting the line, as it
+        // doesn't have its own column (which would mess with the
+        // subsequent column mapping).
+        let mut line: Vec<&str> = line.split_ascii_whitespace().collect();
+        for i in 0..line.len() {
+            if line[i].eq_ignore_ascii_case("(LISTEN)") {
+                line.remove(i);
+                return Some(line);
+            }
+        }
+        if include_udp == IncludeUdp::Yes && Self::is_unconnected_udp_socket(&line) {
+            return Some(line);
+        }
+        None
+    }
+
+    /// Columns of the line, if it is a listening Unix socket.
+    ///
+    /// The name is followed by `type=STREAM` and the state, which have
+    /// no column of their own.
+    fn listening_unix_socket_columns(line: &str) -> Option<Vec<&str>> {
+        let mut line: Vec<&str> = line.split_ascii_whitespace().collect();
+        let state = line.pop()?;
+        if !state.eq_ignore_ascii_case("(LISTEN)") {
+            return None;
+        }
+        line.retain(|column| !column.starts_with("type="));
+        Some(line)
+    }
+
+    /// UDP sockets have no state, so the last columns are `NODE` and
+    /// `NAME`. Connected sockets have a `local->remote` name.
+    fn is_unconnected_udp_socket(line: &[&str]) -> bool {
+        let [.., node, name] = line else {
+            return false;
+        };
+        node.eq_ignore_ascii_case("UDP") && !name.contains("->")
+    }
+
     /// Associate column values to struct properties.
     fn map_detail_values_to_properties(
         header_columns: &[String],
@@ -224,10 +907,11 @@ impl Lsof {
             // and is cheap).
             let mut port = ListeningPort::new();
 
-            for col in 0..header_columns.len() {
-                let value = String::from(detail_line[col]);
+            for (col, header_column) in header_columns.iter().enumerate() {
+                // Short lines (e.g., `foo (LISTEN)`) leave the rest empty.
+                let value = String::from(detail_line.get(col).copied().unwrap_or_default());
 
-                match header_columns[col].as_str() {
+                match header_column.as_str() {
                     "COMMAND" => port.command = value,
                     "PID" => port.pid = value,
                     "USER" => port.user = value,
@@ -254,127 +938,605 @@ mod tests {
     #[test]
     fn lsoferror_debug() {
         let error = LsofError {
-            reason: "an error has occurred",
+            kind: LsofErrorKind::Other,
+            reason: String::from("an error has occurred"),
+        };
+
+        assert_eq!(format!("{error:?}"), "an error has occurred");
+    }
+
+    #[test]
+    fn lsoferror_display() {
+        let error = LsofError {
+            kind: LsofErrorKind::Other,
+            reason: String::from("an error has occurred"),
+        };
+
+        assert_eq!(error.to_string(), "an error has occurred");
+    }
+
+    #[test]
+    fn lsoferror_with_reason_dynamic() {
+        let line_number = 7;
+        let error = LsofError::with_reason(format!("Line {line_number} is malformed."));
+
+        assert_eq!(error.to_string(), "Line 7 is malformed.");
+        assert_eq!(
+            LsofError::with_reason("static"),
+            LsofError {
+                kind: LsofErrorKind::Other,
+                reason: String::from("static")
+            }
+        );
+    }
+
+    #[test]
+    fn lsof_successful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Lsof::handle_output_ok(&output).unwrap();
+
+        assert_eq!(res, "<stdout>");
+    }
+
+    #[test]
+    fn lsof_successful_unsuccessful_read() {
+        // Exit 1 with empty output is OK (just means nothing found).
+        let output = Output {
+            status: ExitStatus::from_raw(1),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"".to_vec(),
         };
 
-        assert_eq!(format!("{error:?}"), "an error has occurred");
+        let res = Lsof::handle_output_ok(&output).unwrap();
+
+        assert_eq!(res, Lsof::headers().join(" "));
+    }
+
+    #[test]
+    fn lsof_unsuccessful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(1),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Lsof::handle_output_ok(&output).unwrap_err();
+
+        assert_eq!(
+            res,
+            LsofError {
+                kind: LsofErrorKind::CommandFailed,
+                reason: String::from("The lsof command has failed in an unexpected way."),
+            }
+        );
+    }
+
+    #[test]
+    fn lsof_error_with_command() {
+        let res = Lsof::handle_output_err().unwrap_err();
+
+        assert_eq!(
+            res,
+            LsofError {
+                kind: LsofErrorKind::MissingExecutable,
+                reason: String::from("Unable to locate the lsof executable on the system."),
+            }
+        );
+    }
+
+    #[test]
+    fn lsoferror_missing_executable() {
+        let error = LsofError::missing_executable("lsof");
+
+        assert!(error.is_missing_executable());
+        // The kind decides, not the wording.
+        assert!(
+            !LsofError::with_reason("Unable to locate the lsof executable on the system.")
+                .is_missing_executable()
+        );
+        assert!(
+            LsofError::with_kind(LsofErrorKind::MissingExecutable, "lsof?").is_missing_executable()
+        );
+        assert!(!LsofError::with_reason("The lsof command has failed.").is_missing_executable());
+    }
+
+    #[test]
+    fn lsoferror_from_ss_and_netstat_keeps_kind() {
+        let error = LsofError::from(SsError::with_kind(
+            LsofErrorKind::MissingExecutable,
+            "No ss.",
+        ));
+
+        assert!(error.is_missing_executable());
+        assert_eq!(error.to_string(), "No ss.");
+
+        let error = LsofError::from(NetstatError::with_kind(
+            LsofErrorKind::CommandFailed,
+            "netstat failed.",
+        ));
+
+        assert_eq!(error.kind(), LsofErrorKind::CommandFailed);
+        assert!(!error.is_missing_executable());
+    }
+
+    #[test]
+    fn listening_ports_lsof_missing_falls_back() {
+        let listening_ports =
+            Lsof::listening_ports_with(Lsof::handle_output_err, IncludeUdp::No).unwrap();
+
+        assert_eq!(listening_ports, Ss::listening_ports().unwrap());
+    }
+
+    #[test]
+    fn listening_ports_lsof_failed_does_not_fall_back() {
+        let res = Lsof::listening_ports_with(
+            || Err(LsofError::with_reason("The lsof command has failed.")),
+            IncludeUdp::No,
+        )
+        .unwrap_err();
+
+        assert_eq!(res.to_string(), "The lsof command has failed.");
+    }
+
+    #[test]
+    fn listening_ports_lsof_missing_with_udp_does_not_fall_back() {
+        let res = Lsof::listening_ports_with(Lsof::handle_output_err, IncludeUdp::Yes).unwrap_err();
+
+        assert!(res.is_missing_executable());
+    }
+
+    #[test]
+    fn listeningport_default() {
+        assert_eq!(ListeningPort::new(), ListeningPort::default());
+    }
+
+    #[test]
+    fn listeningport_new() {
+        let port = ListeningPort::new();
+
+        assert_eq!(
+            port,
+            ListeningPort {
+                command: String::new(),
+                pid: String::new(),
+                user: String::new(),
+                type_: String::new(),
+                node: String::new(),
+                name: String::new(),
+                pinfo: None,
+                _cannot_instantiate: std::marker::PhantomData,
+            }
+        );
+    }
+
+    fn new_port_with_name(name: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.name = String::from(name);
+        port
+    }
+
+    #[test]
+    fn listeningport_ord_by_port_number() {
+        let mut ports = vec![
+            new_port_with_name("[::1]:8080"),
+            new_port_with_name("*:443"),
+            new_port_with_name("127.0.0.1:22"),
+            new_port_with_name("1337"),
+        ];
+
+        ports.sort();
+
+        assert_eq!(
+            ports,
+            vec![
+                new_port_with_name("127.0.0.1:22"),
+                new_port_with_name("*:443"),
+                new_port_with_name("1337"),
+                new_port_with_name("[::1]:8080"),
+            ]
+        );
+    }
+
+    #[test]
+    fn listeningport_ord_non_numeric_last() {
+        let mut ports = vec![
+            new_port_with_name("*:http"),
+            new_port_with_name("[::1]:65535"),
+            new_port_with_name(""),
+            new_port_with_name("*:0"),
+        ];
+
+        ports.sort();
+
+        assert_eq!(
+            ports,
+            vec![
+                new_port_with_name("*:0"),
+                new_port_with_name("[::1]:65535"),
+                new_port_with_name(""),
+                new_port_with_name("*:http"),
+            ]
+        );
+    }
+
+    #[test]
+    fn listeningport_ord_consistent_with_eq() {
+        let mut port_1 = new_port_with_name("*:80");
+        port_1.pid = String::from("1");
+        let mut port_2 = new_port_with_name("*:80");
+        port_2.pid = String::from("2");
+
+        assert_eq!(port_1.cmp(&port_1.clone()), Ordering::Equal);
+        assert_eq!(port_1.cmp(&port_2), Ordering::Less);
+        assert!(new_port_with_name("[::]:80") < new_port_with_name("0.0.0.0:81"));
+    }
+
+    // The `Lsof::listening_ports()` should be integration tests. But at
+    // this scale, it's easier like this.
+
+    #[test]
+    fn listening_ports() {
+        let listening_ports = Lsof::listening_ports(IncludeUdp::No).unwrap();
+
+        let port: ListeningPort = listening_ports
+            .into_iter()
+            .find(|x| x.pid == "2673")
+            .unwrap();
+
+        assert_eq!(
+            port,
+            ListeningPort {
+                command: String::from("docker-pr"),
+                pid: String::from("2673"),
+                user: String::from("root"),
+                type_: String::from("IPv4"),
+                node: String::from("TCP"),
+                name: String::from("*:333"),
+                pinfo: None,
+                _cannot_instantiate: std::marker::PhantomData,
+            }
+        );
+    }
+
+    #[test]
+    fn listeningport_try_from_regular() {
+        let port =
+            ListeningPort::try_from("sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)").unwrap();
+
+        assert_eq!(
+            port,
+            ListeningPort {
+                command: String::from("sshd"),
+                pid: String::from("1174"),
+                user: String::from("root"),
+                type_: String::from("IPv4"),
+                node: String::from("TCP"),
+                name: String::from("*:22"),
+                pinfo: None,
+                _cannot_instantiate: std::marker::PhantomData,
+            }
+        );
+    }
+
+    #[test]
+    fn listeningport_try_from_without_state() {
+        let port =
+            ListeningPort::try_from("avahi-dae 723 avahi 13u IPv6 21182 0t0 UDP *:5353 ").unwrap();
+
+        assert_eq!(port.node, "UDP");
+        assert_eq!(port.name, "*:5353");
+    }
+
+    #[test]
+    fn listeningport_try_from_error_short_line() {
+        let res = ListeningPort::try_from("sshd 1174 root 4u IPv6 TCP *:22 (LISTEN)").unwrap_err();
+
+        assert_eq!(
+            res,
+            ParseError {
+                reason: String::from("The lsof line has 8 columns, expected 9."),
+            }
+        );
+        assert_eq!(res.to_string(), "The lsof line has 8 columns, expected 9.");
+    }
+
+    #[test]
+    fn listeningport_try_from_fixture_lines() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lsof.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
+
+        let parsed: Vec<ListeningPort> = fixture
+            .lines()
+            .skip(1) // Header.
+            .filter(|line| Lsof::listening_socket_columns(line, IncludeUdp::Yes).is_some())
+            .map(|line| ListeningPort::try_from(line).unwrap())
+            .collect();
+
+        assert_eq!(parsed, Lsof::listening_ports(IncludeUdp::Yes).unwrap());
+    }
+
+    #[test]
+    fn parse_regular() {
+        let output = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+avahi-dae 723 avahi 12u IPv4 21181 0t0 UDP *:5353
+";
+
+        let listening_ports = Lsof::parse(output, IncludeUdp::No).unwrap();
+
+        assert_eq!(listening_ports.len(), 1);
+        assert_eq!(listening_ports[0].name, "*:22");
+    }
+
+    #[test]
+    fn parse_error_no_header() {
+        let res = Lsof::parse("", IncludeUdp::No).unwrap_err();
+
+        assert_eq!(
+            res,
+            LsofError {
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The lsof output is missing the header."),
+            }
+        );
+    }
+
+    #[test]
+    fn listening_ports_raw() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lsof.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
+
+        let raw = Lsof::listening_ports_raw().unwrap();
+
+        assert_eq!(raw, fixture);
+        assert_eq!(
+            Lsof::parse(&raw, IncludeUdp::No).unwrap(),
+            Lsof::listening_ports(IncludeUdp::No).unwrap()
+        );
+    }
+
+    #[test]
+    fn lsof_command_without_hostnames() {
+        let command = Lsof::lsof_command(ResolveHostnames::No, ResolveServiceNames::No);
+
+        assert_eq!(command.get_program(), "lsof");
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-i", "-n", "-P"]);
+    }
+
+    #[test]
+    fn lsof_command_with_hostnames() {
+        let command = Lsof::lsof_command(ResolveHostnames::Yes, ResolveServiceNames::No);
+
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-i", "-P"]);
+    }
+
+    #[test]
+    fn lsof_command_with_service_names() {
+        let command = Lsof::lsof_command(ResolveHostnames::No, ResolveServiceNames::Yes);
+
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-i", "-n"]);
+    }
+
+    #[test]
+    fn listening_ports_without_udp() {
+        let listening_ports = Lsof::listening_ports(IncludeUdp::No).unwrap();
+
+        assert!(listening_ports.iter().all(|x| x.node == "TCP"));
+    }
+
+    #[test]
+    fn listening_ports_with_udp() {
+        let listening_ports = Lsof::listening_ports(IncludeUdp::Yes).unwrap();
+
+        let port: ListeningPort = listening_ports
+            .into_iter()
+            .find(|x| x.pid == "723" && x.type_ == "IPv6")
+            .unwrap();
+
+        assert_eq!(
+            port,
+            ListeningPort {
+                command: String::from("avahi-dae"),
+                pid: String::from("723"),
+                user: String::from("avahi"),
+                type_: String::from("IPv6"),
+                node: String::from("UDP"),
+                name: String::from("*:5353"),
+                pinfo: None,
+                _cannot_instantiate: std::marker::PhantomData,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_short_line() {
+        let output = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+foo (LISTEN)
+";
+
+        let listening_ports = Lsof::parse(output, IncludeUdp::No).unwrap();
+
+        assert_eq!(
+            listening_ports,
+            [ListeningPortBuilder::new().command("foo").build()]
+        );
+    }
+
+    #[test]
+    fn listening_ports_strict_same_as_regular() {
+        let strict = Lsof::listening_ports_strict(IncludeUdp::Yes).unwrap();
+        let regular = Lsof::listening_ports(IncludeUdp::Yes).unwrap();
+
+        assert_eq!(strict, regular);
+    }
+
+    #[test]
+    fn parse_strict_regular() {
+        let output = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+";
+
+        let listening_ports = Lsof::parse_strict(output, IncludeUdp::No).unwrap();
+
+        assert_eq!(listening_ports.len(), 1);
+        assert_eq!(listening_ports[0].name, "*:22");
+    }
+
+    #[test]
+    fn parse_strict_error_short_row() {
+        let output = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+sshd 1174 root 4u IPv6 TCP *:22 (LISTEN)
+";
+
+        let res = Lsof::parse_strict(output, IncludeUdp::No).unwrap_err();
+
+        assert_eq!(
+            res,
+            LsofError {
+                kind: LsofErrorKind::Parse,
+                reason: String::from("Line 3 of the lsof output has 7 columns, expected 9."),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_strict_ignores_non_listening_lines() {
+        let output = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+short line
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+";
+
+        let listening_ports = Lsof::parse_strict(output, IncludeUdp::No).unwrap();
+
+        assert_eq!(listening_ports.len(), 1);
     }
 
     #[test]
-    fn lsoferror_display() {
-        let error = LsofError {
-            reason: "an error has occurred",
-        };
+    fn listening_ports_with_udp_excludes_connected_sockets() {
+        let listening_ports = Lsof::listening_ports(IncludeUdp::Yes).unwrap();
 
-        assert_eq!(error.to_string(), "an error has occurred");
+        // NetworkManager's DHCP client is connected to the server.
+        assert!(!listening_ports.iter().any(|x| x.pid == "728"));
+        // TCP ports are still there.
+        assert!(listening_ports.iter().any(|x| x.pid == "2673"));
     }
 
     #[test]
-    fn lsof_successful_read() {
-        let output = Output {
-            status: ExitStatus::from_raw(0),
-            stdout: b"<stdout>".to_vec(),
-            stderr: b"<stderr>".to_vec(),
-        };
+    fn udp_ports_regular() {
+        let udp_ports = Lsof::udp_ports().unwrap();
+
+        assert!(udp_ports.iter().all(|x| x.node == "UDP"));
+        assert!(udp_ports
+            .iter()
+            .any(|x| x.pid == "723" && x.type_ == "IPv6"));
+        // Connected.
+        assert!(!udp_ports.iter().any(|x| x.pid == "728"));
+    }
 
-        let res = Lsof::handle_output_ok(&output).unwrap();
+    #[test]
+    fn udp_ports_same_as_udp_capture() {
+        // `lsof -i udp -n -P`.
+        let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/lsof-udp.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
 
-        assert_eq!(res, "<stdout>");
+        let parsed = Lsof::parse(&fixture, IncludeUdp::Yes).unwrap();
+
+        assert_eq!(parsed, Lsof::udp_ports().unwrap());
     }
 
     #[test]
-    fn lsof_successful_unsuccessful_read() {
-        // Exit 1 with empty output is OK (just means nothing found).
-        let output = Output {
-            status: ExitStatus::from_raw(1),
-            stdout: b"<stdout>".to_vec(),
-            stderr: b"".to_vec(),
-        };
+    fn udp_capture_without_udp() {
+        let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/lsof-udp.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
 
-        let res = Lsof::handle_output_ok(&output).unwrap();
+        let parsed = Lsof::parse(&fixture, IncludeUdp::No).unwrap();
 
-        assert_eq!(res, Lsof::headers().join(" "));
+        assert!(parsed.is_empty());
     }
 
     #[test]
-    fn lsof_unsuccessful_read() {
-        let output = Output {
-            status: ExitStatus::from_raw(1),
-            stdout: b"<stdout>".to_vec(),
-            stderr: b"<stderr>".to_vec(),
-        };
-
-        let res = Lsof::handle_output_ok(&output).unwrap_err();
+    fn unix_sockets_regular() {
+        let unix_sockets = Lsof::unix_sockets().unwrap();
 
         assert_eq!(
-            res,
-            LsofError {
-                reason: "The lsof command has failed in an unexpected way.",
+            unix_sockets[0],
+            ListeningPort {
+                command: String::from("systemd"),
+                pid: String::from("1"),
+                user: String::from("root"),
+                type_: String::from("unix"),
+                node: String::from("17412"),
+                name: String::from("/run/systemd/private"),
+                pinfo: None,
+                _cannot_instantiate: std::marker::PhantomData,
             }
         );
     }
 
     #[test]
-    fn lsof_error_with_command() {
-        let res = Lsof::handle_output_err().unwrap_err();
+    fn unix_sockets_only_listening() {
+        let unix_sockets = Lsof::unix_sockets().unwrap();
 
+        let names: Vec<&str> = unix_sockets.iter().map(|x| x.name.as_str()).collect();
         assert_eq!(
-            res,
-            LsofError {
-                reason: "Unable to locate the lsof executable on the system.",
-            }
+            names,
+            [
+                "/run/systemd/private",
+                "/run/systemd/journal/stdout",
+                "/run/dbus/system_bus_socket",
+                "/run/avahi-daemon/socket",
+                "/run/cups/cups.sock",
+                "/var/run/postgresql/.s.PGSQL.5432",
+            ]
         );
     }
 
     #[test]
-    fn listeningport_default() {
-        assert_eq!(ListeningPort::new(), ListeningPort::default());
+    fn unix_sockets_skips_unnamed() {
+        let unix_sockets = Lsof::unix_sockets().unwrap();
+
+        // `ssh-agent` listens on a socket without a path.
+        assert!(!unix_sockets.iter().any(|x| x.pid == "6012"));
     }
 
     #[test]
-    fn listeningport_new() {
-        let port = ListeningPort::new();
+    fn parse_unix_empty() {
+        let unix_sockets = Lsof::parse_unix(&Lsof::headers().join(" ")).unwrap();
 
-        assert_eq!(
-            port,
-            ListeningPort {
-                command: String::new(),
-                pid: String::new(),
-                user: String::new(),
-                type_: String::new(),
-                node: String::new(),
-                name: String::new(),
-                pinfo: None,
-                _cannot_instantiate: std::marker::PhantomData,
-            }
-        );
+        assert!(unix_sockets.is_empty());
     }
 
-    // The `Lsof::listening_ports()` should be integration tests. But at
-    // this scale, it's easier like this.
-
     #[test]
-    fn listening_ports() {
-        let listening_ports = Lsof::listening_ports().unwrap();
+    fn parse_unix_missing_header() {
+        let res = Lsof::parse_unix("").unwrap_err();
 
-        let port: ListeningPort = listening_ports
-            .into_iter()
-            .find(|x| x.pid == "2673")
-            .unwrap();
+        assert_eq!(res.to_string(), "The lsof output is missing the header.");
+    }
 
-        assert_eq!(
-            port,
-            ListeningPort {
-                command: String::from("docker-pr"),
-                pid: String::from("2673"),
-                user: String::from("root"),
-                type_: String::from("IPv4"),
-                node: String::from("TCP"),
-                name: String::from("*:333"),
-                pinfo: None,
-                _cannot_instantiate: std::marker::PhantomData,
-            }
-        );
+    #[test]
+    fn lsof_unix_command_args() {
+        let command = Lsof::lsof_unix_command();
+
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-U", "-n"]);
     }
 
     #[test]
@@ -398,7 +1560,8 @@ mod tests {
         assert_eq!(
             error,
             LsofError {
-                reason: "The lsof output is missing the header."
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The lsof output is missing the header.")
             }
         );
     }
@@ -423,9 +1586,10 @@ mod tests {
         assert_eq!(
             error,
             LsofError {
+                kind: LsofErrorKind::Parse,
                 // This is considered an empty header line, and so falls
                 // into this error, instead of "no header"
-                reason: "The lsof output is missing expected properties."
+                reason: String::from("The lsof output is missing expected properties.")
             }
         );
     }
@@ -458,7 +1622,8 @@ mod tests {
         assert_eq!(
             error,
             LsofError {
-                reason: "The lsof output is missing expected properties.",
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The lsof output is missing expected properties."),
             }
         );
     }
@@ -483,7 +1648,8 @@ This is again not included
 ";
         let mut output = output.lines();
 
-        let detail_lines = Lsof::extract_detail_lines_of_listening_ports(&mut output);
+        let detail_lines =
+            Lsof::extract_detail_lines_of_listening_ports(&mut output, IncludeUdp::No);
 
         assert_eq!(
             detail_lines,
@@ -504,7 +1670,8 @@ This is again not included
 ";
         let mut output = output.lines();
 
-        let detail_lines = Lsof::extract_detail_lines_of_listening_ports(&mut output);
+        let detail_lines =
+            Lsof::extract_detail_lines_of_listening_ports(&mut output, IncludeUdp::No);
 
         assert_eq!(
             detail_lines,
@@ -515,6 +1682,46 @@ This is again not included
         );
     }
 
+    #[test]
+    fn extract_detail_lines_of_listening_ports_udp() {
+        let output = "\
+This is included (LISTEN)
+This is not included UDP *:53
+This is not included TCP *:80
+";
+        let mut output = output.lines();
+
+        let detail_lines =
+            Lsof::extract_detail_lines_of_listening_ports(&mut output, IncludeUdp::No);
+
+        assert_eq!(detail_lines, vec![vec!["This", "is", "included"]]);
+    }
+
+    #[test]
+    fn extract_detail_lines_of_listening_ports_include_udp() {
+        let output = "\
+This is included (LISTEN)
+This is included too UDP *:53
+This is included too udp [::1]:53
+This is not included UDP 10.0.2.15:68->10.0.2.2:67
+This is not included TCP *:80
+UDP
+";
+        let mut output = output.lines();
+
+        let detail_lines =
+            Lsof::extract_detail_lines_of_listening_ports(&mut output, IncludeUdp::Yes);
+
+        assert_eq!(
+            detail_lines,
+            vec![
+                vec!["This", "is", "included"],
+                vec!["This", "is", "included", "too", "UDP", "*:53"],
+                vec!["This", "is", "included", "too", "udp", "[::1]:53"],
+            ]
+        );
+    }
+
     #[test]
     fn map_detail_values_to_properties() {
         let header_columns = [
@@ -680,4 +1887,328 @@ This is again not included
 
         assert!(port.pinfo.is_none());
     }
+
+    #[test]
+    fn listeningport_display() {
+        let port = ListeningPortBuilder::new()
+            .command("docker-pr")
+            .pid("2673")
+            .user("root")
+            .type_("IPv4")
+            .node("TCP")
+            .name("*:333")
+            .build();
+
+        assert_eq!(port.to_string(), "docker-pr(2673) root TCP *:333");
+    }
+
+    #[test]
+    fn listeningport_display_empty_user() {
+        let port = ListeningPortBuilder::new()
+            .command("docker-pr")
+            .pid("2673")
+            .node("TCP")
+            .name("*:333")
+            .build();
+
+        assert_eq!(port.to_string(), "docker-pr(2673) TCP *:333");
+    }
+
+    #[test]
+    fn listeningportbuilder_regular() {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.pid = String::from("1174");
+
+        let port = ListeningPortBuilder::new()
+            .command("sshd")
+            .pid("1174")
+            .user("root")
+            .type_("IPv4")
+            .node("TCP")
+            .name("*:22")
+            .pinfo(pinfo.clone())
+            .build();
+
+        assert_eq!(
+            port,
+            ListeningPort {
+                command: String::from("sshd"),
+                pid: String::from("1174"),
+                user: String::from("root"),
+                type_: String::from("IPv4"),
+                node: String::from("TCP"),
+                name: String::from("*:22"),
+                pinfo: Some(pinfo),
+                _cannot_instantiate: std::marker::PhantomData,
+            }
+        );
+    }
+
+    #[test]
+    fn listeningportbuilder_default() {
+        assert_eq!(ListeningPortBuilder::new().build(), ListeningPort::new());
+    }
+
+    #[test]
+    fn address_wildcard() {
+        assert_eq!(
+            Address::parse("*:80"),
+            Some(Address {
+                host: Host::Wildcard,
+                port: 80
+            })
+        );
+    }
+
+    #[test]
+    fn address_ipv4() {
+        assert_eq!(
+            Address::parse("127.0.0.1:8080"),
+            Some(Address {
+                host: Host::IPv4(Ipv4Addr::LOCALHOST),
+                port: 8080
+            })
+        );
+    }
+
+    #[test]
+    fn address_ipv6() {
+        assert_eq!(
+            Address::parse("[::1]:443"),
+            Some(Address {
+                host: Host::IPv6(Ipv6Addr::LOCALHOST),
+                port: 443
+            })
+        );
+        assert_eq!(
+            Address::parse("[::]:22"),
+            Some(Address {
+                host: Host::IPv6(Ipv6Addr::UNSPECIFIED),
+                port: 22
+            })
+        );
+    }
+
+    #[test]
+    fn address_ipv6_with_zone() {
+        assert_eq!(
+            Address::parse("[fe80::1%lo0]:5353"),
+            Some(Address {
+                host: Host::IPv6("fe80::1".parse().unwrap()),
+                port: 5353
+            })
+        );
+    }
+
+    #[test]
+    fn address_bare_port() {
+        assert_eq!(
+            Address::parse("1337"),
+            Some(Address {
+                host: Host::Wildcard,
+                port: 1337
+            })
+        );
+    }
+
+    #[test]
+    fn address_invalid() {
+        for name in [
+            "",
+            "*:http",
+            "*:70000",
+            "def:",
+            "localhost:8080",
+            "[::1:443",
+            "::1:443",
+            "[localhost]:443",
+        ] {
+            assert_eq!(Address::parse(name), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn address_display_round_trip() {
+        for name in ["*:80", "127.0.0.1:8080", "[::1]:443", "[::]:22"] {
+            assert_eq!(Address::parse(name).unwrap().to_string(), name);
+        }
+    }
+
+    #[test]
+    fn listeningport_address() {
+        let port = ListeningPortBuilder::new().name("[::1]:443").build();
+
+        assert_eq!(
+            port.address(),
+            Some(Address {
+                host: Host::IPv6(Ipv6Addr::LOCALHOST),
+                port: 443
+            })
+        );
+    }
+
+    #[test]
+    fn port_number_regular() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("127.0.0.1:1337");
+
+        assert_eq!(port.port_number(), Some(1337));
+    }
+
+    #[test]
+    fn port_number_ipv6() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("[::1]:1337");
+
+        assert_eq!(port.port_number(), Some(1337));
+    }
+
+    #[test]
+    fn port_number_wildcard() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:80");
+
+        assert_eq!(port.port_number(), Some(80));
+    }
+
+    #[test]
+    fn port_number_bare() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("1337");
+
+        assert_eq!(port.port_number(), Some(1337));
+    }
+
+    #[test]
+    fn port_number_invalid() {
+        let mut port = ListeningPort::new();
+        for name in ["*:http", "def:", "", "*:70000"] {
+            port.name = String::from(name);
+
+            assert_eq!(port.port_number(), None, "{name}");
+        }
+    }
+
+    #[test]
+    fn is_loopback_ipv4() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("127.0.0.1:631");
+
+        assert!(port.is_loopback());
+    }
+
+    #[test]
+    fn is_loopback_ipv6() {
+        let mut port = ListeningPort::new();
+        for name in ["[::1]:631", "::1:631"] {
+            port.name = String::from(name);
+
+            assert!(port.is_loopback(), "{name}");
+        }
+    }
+
+    #[test]
+    fn is_loopback_wildcard() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:80");
+
+        assert!(!port.is_loopback());
+    }
+
+    #[test]
+    fn is_loopback_non_loopback_ip() {
+        let mut port = ListeningPort::new();
+        for name in ["10.0.2.15:68", "[fe80::1%lo0]:5353", "1337", ""] {
+            port.name = String::from(name);
+
+            assert!(!port.is_loopback(), "{name}");
+        }
+    }
+
+    #[test]
+    fn is_wildcard_regular() {
+        let mut port = ListeningPort::new();
+        for name in ["*:80", "0.0.0.0:80", "[::]:80", "*:http", "1337"] {
+            port.name = String::from(name);
+
+            assert!(port.is_wildcard(), "{name}");
+        }
+    }
+
+    #[test]
+    fn is_wildcard_specific_host() {
+        let mut port = ListeningPort::new();
+        for name in ["127.0.0.1:80", "[::1]:80", "10.0.2.15:68", "http", ""] {
+            port.name = String::from(name);
+
+            assert!(!port.is_wildcard(), "{name}");
+        }
+    }
+
+    #[test]
+    fn full_command_without_process_info() {
+        let port = ListeningPort::new();
+
+        assert_eq!(port.full_command(), None);
+    }
+
+    #[test]
+    fn full_command_with_process_info() {
+        let mut port = ListeningPort::new();
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("python3 -m http.server");
+        port.pinfo = Some(pinfo);
+
+        assert_eq!(port.full_command(), Some("python3 -m http.server"));
+    }
+
+    #[test]
+    fn process_name_without_process_info() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("docker-pr");
+
+        assert_eq!(port.process_name(), "docker-pr");
+    }
+
+    #[test]
+    fn process_name_with_process_info() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("docker-pr");
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("/usr/bin/docker-proxy -proto tcp -host-port 333");
+        port.pinfo = Some(pinfo);
+
+        assert_eq!(port.process_name(), "docker-proxy");
+    }
+
+    #[test]
+    fn process_name_with_process_info_without_path() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("python3");
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("python3 -m http.server");
+        port.pinfo = Some(pinfo);
+
+        assert_eq!(port.process_name(), "python3");
+    }
+
+    #[test]
+    fn process_name_with_empty_process_info() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("docker-pr");
+        port.pinfo = Some(ProcessInfo::new());
+
+        assert_eq!(port.process_name(), "docker-pr");
+    }
+
+    #[test]
+    fn process_name_with_trailing_slash() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("docker-pr");
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("/usr/bin/");
+        port.pinfo = Some(pinfo);
+
+        assert_eq!(port.process_name(), "docker-pr");
+    }
 }
@@ -0,0 +1,357 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::process::{Command, Output};
+
+use crate::cmd::lsof::{Address, ListeningPort, ListeningPortBuilder, LsofErrorKind};
+use crate::cmd::ss::Ss;
+
+#[derive(Eq, PartialEq)]
+pub struct NetstatError {
+    kind: LsofErrorKind,
+    reason: String,
+}
+
+impl NetstatError {
+    #[must_use]
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self::with_kind(LsofErrorKind::Other, reason)
+    }
+
+    #[must_use]
+    pub fn with_kind(kind: LsofErrorKind, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            reason: reason.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> LsofErrorKind {
+        self.kind
+    }
+}
+
+impl Error for NetstatError {}
+
+impl fmt::Debug for NetstatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl fmt::Display for NetstatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// `netstat`, for older systems with neither `lsof` nor `ss`.
+pub struct Netstat;
+
+impl Netstat {
+    /// Use `netstat` to list listening TCP ports.
+    ///
+    /// Like [`Ss::listening_ports()`], ports are as `lsof` would list
+    /// them, and sockets of processes we cannot see are left out.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `netstat` executable is not found, or if the
+    ///  command exits with a non-zero exit code.
+    pub fn listening_ports() -> Result<Vec<ListeningPort>, NetstatError> {
+        let output = Self::netstat()?;
+        let mut ports = Self::parse(&output)?;
+        let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+        Ss::resolve_users(&mut ports, &passwd);
+        Ok(ports)
+    }
+
+    /// Parse `netstat -tlnpeW` output.
+    ///
+    /// The `user` of the ports is a UID, as with [`Ss::parse()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing.
+    pub fn parse(input: &str) -> Result<Vec<ListeningPort>, NetstatError> {
+        // "Active Internet connections (only servers)", then the header.
+        let mut output = input.lines().skip_while(|line| !line.starts_with("Proto"));
+
+        if output.next().is_none() {
+            return Err(NetstatError {
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The netstat output is missing the header."),
+            });
+        }
+
+        Ok(output.filter_map(Self::parse_line).collect())
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn netstat() -> Result<String, NetstatError> {
+        #![allow(unreachable_code)]
+        #[cfg(test)]
+        {
+            let fixture = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/fixtures/netstat.txt");
+            let output = std::fs::read_to_string(fixture).expect("cannot read test fixture");
+            return Ok(output);
+        }
+
+        let output = Self::netstat_command().output();
+
+        match output {
+            Ok(output) => Self::handle_output_ok(&output),
+            Err(_) => Self::handle_output_err(),
+        }
+    }
+
+    fn netstat_command() -> Command {
+        let mut command = Command::new("netstat");
+        command.arg("-t"); // -t TCP sockets.
+        command.arg("-l"); // -l Listening sockets only.
+        command.arg("-n"); // -n Numeric addresses, ports and users.
+        command.arg("-p"); // -p PID and program name of the sockets.
+        command.arg("-e"); // -e Extended info (UID of the owner).
+        command.arg("-W"); // -W Do not truncate IPv6 addresses.
+        command
+    }
+
+    fn handle_output_ok(output: &Output) -> Result<String, NetstatError> {
+        if output.status.success() {
+            // Exit 0.
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            // Non-zero exit code.
+            Err(NetstatError {
+                kind: LsofErrorKind::CommandFailed,
+                reason: String::from("The netstat command has failed in an unexpected way."),
+            })
+        }
+    }
+
+    fn handle_output_err() -> Result<String, NetstatError> {
+        Err(NetstatError {
+            kind: LsofErrorKind::MissingExecutable,
+            reason: String::from("Unable to locate the netstat executable on the system."),
+        })
+    }
+
+    /// `Proto Recv-Q Send-Q Local Foreign State User Inode PID/Program`.
+    ///
+    /// The program name is the rest of the line, it may contain spaces
+    /// (e.g., `nginx: master`).
+    fn parse_line(line: &str) -> Option<ListeningPort> {
+        let columns: Vec<&str> = line.split_ascii_whitespace().collect();
+        let [proto, _recv_q, _send_q, local, _foreign, _state, uid, _inode, program @ ..] =
+            columns.as_slice()
+        else {
+            return None;
+        };
+        let (type_, address) = Self::parse_address(proto, local)?;
+        let program = program.join(" ");
+        // `-` if we cannot see the process.
+        let (pid, command) = program.split_once('/')?;
+
+        Some(
+            ListeningPortBuilder::new()
+                .command(command)
+                .pid(pid)
+                .user(uid)
+                .type_(type_)
+                .node("TCP")
+                .name(&address.to_string())
+                .build(),
+        )
+    }
+
+    /// IPv6 addresses are not in brackets (e.g., `:::80`, `::1:631`), so
+    /// `proto` (`tcp` or `tcp6`) tells which they are.
+    fn parse_address(proto: &str, local: &str) -> Option<(&'static str, Address)> {
+        let (host, port) = local.rsplit_once(':')?;
+
+        let (type_, host) = match (proto, host) {
+            ("tcp", "0.0.0.0") => ("IPv4", String::from("*")),
+            ("tcp", host) => ("IPv4", String::from(host)),
+            ("tcp6", "::") => ("IPv6", String::from("*")),
+            ("tcp6", host) => ("IPv6", format!("[{host}]")),
+            _ => return None,
+        };
+
+        Some((type_, Address::parse(&format!("{host}:{port}"))?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    #[test]
+    fn netstaterror_with_reason() {
+        let error = NetstatError::with_reason("netstat failed.");
+
+        assert_eq!(error.to_string(), "netstat failed.");
+        assert_eq!(format!("{error:?}"), "netstat failed.");
+    }
+
+    #[test]
+    fn netstat_successful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Netstat::handle_output_ok(&output).unwrap();
+
+        assert_eq!(res, "<stdout>");
+    }
+
+    #[test]
+    fn netstat_unsuccessful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(1 << 8),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Netstat::handle_output_ok(&output).unwrap_err();
+
+        assert_eq!(
+            res,
+            NetstatError::with_kind(
+                LsofErrorKind::CommandFailed,
+                "The netstat command has failed in an unexpected way."
+            )
+        );
+    }
+
+    #[test]
+    fn netstat_error_with_command() {
+        let res = Netstat::handle_output_err().unwrap_err();
+
+        assert_eq!(
+            res,
+            NetstatError::with_kind(
+                LsofErrorKind::MissingExecutable,
+                "Unable to locate the netstat executable on the system."
+            )
+        );
+    }
+
+    #[test]
+    fn netstat_command_args() {
+        let command = Netstat::netstat_command();
+
+        assert_eq!(command.get_program(), "netstat");
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-t", "-l", "-n", "-p", "-e", "-W"]);
+    }
+
+    #[test]
+    fn parse_regular() {
+        let listening_ports = Netstat::parse(&Netstat::netstat().unwrap()).unwrap();
+
+        assert_eq!(
+            listening_ports[0],
+            ListeningPortBuilder::new()
+                .command("systemd-resolve")
+                .pid("580")
+                .user("991")
+                .type_("IPv4")
+                .node("TCP")
+                .name("127.0.0.53:53")
+                .build()
+        );
+    }
+
+    #[test]
+    fn listening_ports_names() {
+        let listening_ports = Netstat::listening_ports().unwrap();
+
+        let names: Vec<(&str, &str, &str)> = listening_ports
+            .iter()
+            .map(|x| (x.pid.as_str(), x.type_.as_str(), x.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("580", "IPv4", "127.0.0.53:53"),
+                ("816", "IPv4", "127.0.0.1:631"),
+                ("2493", "IPv4", "*:8024"),
+                ("1021", "IPv4", "127.0.0.1:5432"),
+                ("1179", "IPv4", "*:80"),
+                ("6610", "IPv4", "127.0.0.1:8000"),
+                ("816", "IPv6", "[::1]:631"),
+                ("2500", "IPv6", "*:8024"),
+                ("6702", "IPv6", "[fe80::a00:27ff:fe4e:66a1]:9000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn listening_ports_same_as_ss() {
+        let netstat = Netstat::listening_ports().unwrap();
+        let ss = Ss::listening_ports().unwrap();
+
+        // `ss` lists every process of a socket, `netstat` only one.
+        assert!(netstat
+            .iter()
+            .all(|port| ss.iter().any(|x| x.pid == port.pid && x.name == port.name)));
+    }
+
+    #[test]
+    fn parse_program_name_with_spaces() {
+        let listening_ports = Netstat::parse(&Netstat::netstat().unwrap()).unwrap();
+
+        let nginx = listening_ports.iter().find(|x| x.pid == "1179").unwrap();
+        assert_eq!(nginx.command, "nginx: master");
+    }
+
+    #[test]
+    fn parse_skips_sockets_without_process() {
+        let listening_ports = Netstat::parse(&Netstat::netstat().unwrap()).unwrap();
+
+        assert!(!listening_ports.iter().any(|x| x.name == "*:22"));
+    }
+
+    #[test]
+    fn parse_missing_header() {
+        for output in ["", "Active Internet connections (only servers)\n"] {
+            let res = Netstat::parse(output).unwrap_err();
+
+            assert_eq!(res.to_string(), "The netstat output is missing the header.");
+        }
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let output = "\
+Proto Recv-Q Send-Q Local Address           Foreign Address         State       User       Inode      PID/Program name
+tcp        0      0 127.0.0.1:8000          0.0.0.0:*               LISTEN
+udp        0      0 127.0.0.1:8000          0.0.0.0:*               LISTEN      1000       61122      6610/python3
+tcp        0      0 127.0.0.1:abc           0.0.0.0:*               LISTEN      1000       61122      6610/python3
+";
+
+        assert!(Netstat::parse(output).unwrap().is_empty());
+    }
+}
@@ -14,6 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashSet;
 use std::error::Error;
 use std::fmt;
 use std::process::{Command, Output};
@@ -21,7 +22,16 @@ use std::str::Lines;
 
 #[derive(Eq, PartialEq)]
 pub struct PsError {
-    reason: &'static str,
+    reason: String,
+}
+
+impl PsError {
+    #[must_use]
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
+    }
 }
 
 impl Error for PsError {}
@@ -38,15 +48,29 @@ impl fmt::Display for PsError {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProcessInfo {
+    /// Effective user (what `ps aux` and `lsof` show as `USER`).
     pub user: String,
+    /// Real user. Differs from `user` for `setuid` programs. Empty if
+    /// unknown (e.g., parsed from `ps aux`).
+    pub ruser: String,
     pub pid: String,
+    /// Parent PID. Empty if unknown (e.g., parsed from `ps aux`).
+    pub ppid: String,
+    #[cfg_attr(feature = "serde", serde(rename = "cpu_percent"))]
     pub pc_cpu: String,
+    #[cfg_attr(feature = "serde", serde(rename = "mem_percent"))]
     pub pc_mem: String,
+    /// Virtual memory size, in KiB.
+    pub vsz: String,
+    /// Resident set size (physical memory used), in KiB.
+    pub rss: String,
     pub start: String,
     pub time: String,
     pub command: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
     _cannot_instantiate: std::marker::PhantomData<()>,
 }
 
@@ -55,15 +79,56 @@ impl ProcessInfo {
     pub fn new() -> Self {
         Self {
             user: String::new(),
+            ruser: String::new(),
             pid: String::new(),
+            ppid: String::new(),
             pc_cpu: String::new(),
             pc_mem: String::new(),
+            vsz: String::new(),
+            rss: String::new(),
             start: String::new(),
             time: String::new(),
             command: String::new(),
             _cannot_instantiate: std::marker::PhantomData,
         }
     }
+
+    /// CPU usage, in percent (e.g., `12.5`).
+    ///
+    /// `None` if there is no usable value (empty, non-numeric, NaN).
+    #[must_use]
+    pub fn cpu_float(&self) -> Option<f32> {
+        Self::parse_percentage(&self.pc_cpu)
+    }
+
+    /// Memory usage, in percent (e.g., `0.3`).
+    ///
+    /// `None` if there is no usable value (empty, non-numeric, NaN).
+    #[must_use]
+    pub fn mem_float(&self) -> Option<f32> {
+        Self::parse_percentage(&self.pc_mem)
+    }
+
+    fn parse_percentage(value: &str) -> Option<f32> {
+        value
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|value| value.is_finite())
+    }
+
+    /// Name of the executable, without path or arguments.
+    ///
+    /// `/usr/bin/python3 -m http.server` gives `python3`. Empty if there
+    /// is no command.
+    #[must_use]
+    pub fn command_basename(&self) -> &str {
+        self.command
+            .split_ascii_whitespace()
+            .next()
+            .and_then(|executable| executable.rsplit('/').next())
+            .unwrap_or_default()
+    }
 }
 
 impl Default for ProcessInfo {
@@ -72,18 +137,88 @@ impl Default for ProcessInfo {
     }
 }
 
+/// `USER/PID: COMMAND (cpu=%CPU%, mem=%MEM%)`, on one line.
+///
+/// For example, `root/2673: /usr/bin/docker-proxy (cpu=0.0%, mem=0.1%)`.
+/// The shape is always the same: empty fields are printed empty, except
+/// CPU and memory usage, which show `?` rather than a lone `%`.
+impl fmt::Display for ProcessInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let percent = |value: &str| {
+            if value.is_empty() {
+                String::from("?")
+            } else {
+                format!("{value}%")
+            }
+        };
+        write!(
+            f,
+            "{}/{}: {} (cpu={}, mem={})",
+            self.user,
+            self.pid,
+            self.command,
+            percent(&self.pc_cpu),
+            percent(&self.pc_mem),
+        )
+    }
+}
+
 pub struct Ps;
 
+/// Columns to ask `ps` for: those of `ps aux`, the real user, and the
+/// parent PID.
+///
+/// (`TTY` and `STAT` are left out, they are not used.)
+///
+/// Linux's `start` is `Jul 12` for older processes, which would be
+/// split into two columns. `start_time` is what `ps aux` shows there,
+/// but it is Linux-only.
+#[cfg(target_os = "linux")]
+const PS_COLUMNS: &str = "user,ruser,pid,ppid,%cpu,%mem,vsz,rss,start_time,time,command";
+#[cfg(not(target_os = "linux"))]
+const PS_COLUMNS: &str = "user,ruser,pid,ppid,%cpu,%mem,vsz,rss,start,time,command";
+
 impl Ps {
     /// Use `ps` to get process info.
     ///
+    /// `pids` may contain duplicates (e.g., a process listening on
+    /// both IPv4 and IPv6), the result has one entry per unique PID.
+    ///
     /// # Errors
     ///
     /// Errors if the `ps` executable is not found, or if the command
     ///  exits with a non-zero exit code.
     pub fn processes_info(pids: &[&String]) -> Result<Vec<ProcessInfo>, PsError> {
         let output = Self::ps()?;
-        let mut output = output.lines();
+        Self::parse(&output, pids)
+    }
+
+    /// Raw `ps -axo <PS_COLUMNS>` output, unparsed.
+    ///
+    /// That is, `USER RUSER PID PPID %CPU %MEM VSZ RSS START TIME COMMAND`
+    /// for every process.
+    ///
+    /// For diagnostics, or for parsing it differently. Symmetric to
+    /// [`Lsof::listening_ports_raw()`](crate::lsof::Lsof::listening_ports_raw).
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `ps` executable is not found, or if the command
+    ///  exits with a non-zero exit code.
+    pub fn processes_info_raw() -> Result<String, PsError> {
+        Self::ps()
+    }
+
+    /// Parse `ps -axo <PS_COLUMNS>` output, or `ps aux` output (e.g.,
+    /// captured on another machine).
+    ///
+    /// Same as [`Ps::processes_info()`], without running `ps`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing or incomplete.
+    pub fn parse(input: &str, pids: &[&String]) -> Result<Vec<ProcessInfo>, PsError> {
+        let mut output = input.lines();
 
         let header_columns = Self::extract_header_columns(&mut output)?;
         let detail_lines = Self::extract_detail_lines_of_processes(&mut output);
@@ -105,7 +240,7 @@ impl Ps {
             return Ok(output);
         }
 
-        let output = Command::new("ps").arg("aux").output();
+        let output = Command::new("ps").arg("-axo").arg(PS_COLUMNS).output();
 
         match output {
             Ok(output) => Self::handle_output_ok(&output),
@@ -120,14 +255,14 @@ impl Ps {
         } else {
             // Non-zero exit code.
             Err(PsError {
-                reason: "The ps command has failed in an unexpected way.",
+                reason: String::from("The ps command has failed in an unexpected way."),
             })
         }
     }
 
     fn handle_output_err() -> Result<String, PsError> {
         Err(PsError {
-            reason: "Unable to locate the ps executable on the system.",
+            reason: String::from("Unable to locate the ps executable on the system."),
         })
     }
 
@@ -135,7 +270,7 @@ impl Ps {
     fn extract_header_columns(output: &mut Lines) -> Result<Vec<String>, PsError> {
         let Some(header) = output.next() else {
             return Err(PsError {
-                reason: "The ps output is missing the header.",
+                reason: String::from("The ps output is missing the header."),
             });
         };
         let header = header.to_ascii_uppercase(); // To make sure.
@@ -145,7 +280,7 @@ impl Ps {
 
         if !Self::header_contains_all_properties(&header) {
             return Err(PsError {
-                reason: "The ps output is missing expected properties.",
+                reason: String::from("The ps output is missing expected properties."),
             });
         }
 
@@ -158,6 +293,9 @@ impl Ps {
             .map(|col| match col {
                 // 'START' may be called 'STARTED' in certain versions.
                 &"STARTED" => "START",
+                // 'TIME' is 'ELAPSED' on OpenBSD and NetBSD, and
+                // '%TIME' on some BusyBox versions.
+                &"ELAPSED" | &"%TIME" => "TIME",
                 _ => col,
             })
             .collect()
@@ -172,6 +310,11 @@ impl Ps {
         true
     }
 
+    /// Required columns.
+    ///
+    /// `RUSER`, `PPID`, `VSZ` and `RSS` are read if present. `ps aux`
+    /// output (e.g., captured for `--ps-file`) doesn't have `RUSER` and
+    /// `PPID`.
     fn headers() -> &'static [&'static str] {
         &["USER", "PID", "%CPU", "%MEM", "START", "TIME", "COMMAND"]
     }
@@ -198,14 +341,18 @@ impl Ps {
         for detail_line in detail_lines {
             let mut process = ProcessInfo::new();
 
-            for col in 0..header_columns.len() {
-                let value = String::from(detail_line[col]);
+            for (col, header_column) in header_columns.iter().enumerate() {
+                let value = String::from(Self::extract_safely(detail_line, col));
 
-                match header_columns[col].as_str() {
+                match header_column.as_str() {
                     "USER" => process.user = value,
+                    "RUSER" => process.ruser = value,
                     "PID" => process.pid = value,
+                    "PPID" => process.ppid = value,
                     "%CPU" => process.pc_cpu = value,
                     "%MEM" => process.pc_mem = value,
+                    "VSZ" => process.vsz = value,
+                    "RSS" => process.rss = value,
                     "START" => process.start = value,
                     "TIME" => process.time = value,
                     "COMMAND" => {
@@ -215,7 +362,7 @@ impl Ps {
                         // Note: This has the side-effect of compressing
                         // multiple spaces into one. Not ideal, but we
                         // can argue it's a feature, not a shortcoming.
-                        let remaining = detail_line[col..].join(" ");
+                        let remaining = detail_line.get(col..).unwrap_or_default().join(" ");
                         process.command = remaining;
                     }
                     _ => continue,
@@ -228,10 +375,20 @@ impl Ps {
         ps
     }
 
+    /// Value of the column, or `""` if the line is too short.
+    ///
+    /// `ps` may emit partial lines (e.g., a zombie process has no
+    /// command).
+    fn extract_safely<'a>(detail_line: &[&'a str], col: usize) -> &'a str {
+        detail_line.get(col).copied().unwrap_or_default()
+    }
+
     fn keep_only_relevant_pids(pinfo: Vec<ProcessInfo>, pids: &[&String]) -> Vec<ProcessInfo> {
+        let mut pids: HashSet<&str> = pids.iter().map(|pid| pid.as_str()).collect();
         pinfo
             .into_iter()
-            .filter(|process| pids.contains(&&process.pid))
+            // Removing the PID on match keeps one entry per PID.
+            .filter(|process| pids.remove(process.pid.as_str()))
             .collect()
     }
 }
@@ -248,10 +405,103 @@ mod tests {
         pinfo
     }
 
+    #[test]
+    fn processinfo_display() {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.user = String::from("root");
+        pinfo.pid = String::from("2673");
+        pinfo.pc_cpu = String::from("0.0");
+        pinfo.pc_mem = String::from("0.1");
+        pinfo.command = String::from("/usr/bin/docker-proxy -proto tcp");
+
+        assert_eq!(
+            pinfo.to_string(),
+            "root/2673: /usr/bin/docker-proxy -proto tcp (cpu=0.0%, mem=0.1%)"
+        );
+    }
+
+    #[test]
+    fn processinfo_display_empty() {
+        let pinfo = ProcessInfo::new();
+
+        assert_eq!(pinfo.to_string(), "/:  (cpu=?, mem=?)");
+    }
+
+    #[test]
+    fn command_basename_regular() {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("/usr/bin/python3 -m http.server");
+
+        assert_eq!(pinfo.command_basename(), "python3");
+    }
+
+    #[test]
+    fn command_basename_without_path() {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("redis-server *:6379");
+
+        assert_eq!(pinfo.command_basename(), "redis-server");
+    }
+
+    #[test]
+    fn command_basename_empty() {
+        let pinfo = ProcessInfo::new();
+
+        assert_eq!(pinfo.command_basename(), "");
+    }
+
+    #[test]
+    fn cpu_float_regular() {
+        let mut pinfo = ProcessInfo::new();
+        for (value, expected) in [
+            ("0.0", Some(0.0)),
+            ("100.0", Some(100.0)),
+            ("12.5", Some(12.5)),
+        ] {
+            pinfo.pc_cpu = String::from(value);
+
+            assert_eq!(pinfo.cpu_float(), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn cpu_float_invalid() {
+        let mut pinfo = ProcessInfo::new();
+        for value in ["", "abc", "1,5", "NaN", "inf"] {
+            pinfo.pc_cpu = String::from(value);
+
+            assert_eq!(pinfo.cpu_float(), None, "{value}");
+        }
+    }
+
+    #[test]
+    fn mem_float_regular() {
+        let mut pinfo = ProcessInfo::new();
+        for (value, expected) in [
+            ("0.0", Some(0.0)),
+            ("100.0", Some(100.0)),
+            ("0.3", Some(0.3)),
+        ] {
+            pinfo.pc_mem = String::from(value);
+
+            assert_eq!(pinfo.mem_float(), expected, "{value}");
+        }
+    }
+
+    #[test]
+    fn mem_float_invalid() {
+        let mut pinfo = ProcessInfo::new();
+        for value in ["", "abc"] {
+            pinfo.pc_mem = String::from(value);
+
+            assert_eq!(pinfo.mem_float(), None, "{value}");
+        }
+    }
+
     #[test]
     fn pserror_debug() {
         let error = PsError {
-            reason: "an error has occurred",
+            reason: String::from("an error has occurred"),
         };
 
         assert_eq!(format!("{error:?}"), "an error has occurred");
@@ -260,12 +510,20 @@ mod tests {
     #[test]
     fn pserror_display() {
         let error = PsError {
-            reason: "an error has occurred",
+            reason: String::from("an error has occurred"),
         };
 
         assert_eq!(error.to_string(), "an error has occurred");
     }
 
+    #[test]
+    fn pserror_with_reason_dynamic() {
+        let code = 42;
+        let error = PsError::with_reason(format!("ps exited with code {code}."));
+
+        assert_eq!(error.to_string(), "ps exited with code 42.");
+    }
+
     #[test]
     fn ps_successful_read() {
         let output = Output {
@@ -292,7 +550,7 @@ mod tests {
         assert_eq!(
             res,
             PsError {
-                reason: "The ps command has failed in an unexpected way.",
+                reason: String::from("The ps command has failed in an unexpected way."),
             }
         );
     }
@@ -304,7 +562,7 @@ mod tests {
         assert_eq!(
             res,
             PsError {
-                reason: "Unable to locate the ps executable on the system.",
+                reason: String::from("Unable to locate the ps executable on the system."),
             }
         );
     }
@@ -322,9 +580,13 @@ mod tests {
             process,
             ProcessInfo {
                 user: String::new(),
+                ruser: String::new(),
                 pid: String::new(),
+                ppid: String::new(),
                 pc_cpu: String::new(),
                 pc_mem: String::new(),
+                vsz: String::new(),
+                rss: String::new(),
                 start: String::new(),
                 time: String::new(),
                 command: String::new(),
@@ -349,9 +611,13 @@ mod tests {
             process,
             ProcessInfo {
                 user: String::from("root"),
+                ruser: String::new(),
                 pid: String::from("2673"),
+                ppid: String::new(),
                 pc_cpu: String::from("0.0"),
                 pc_mem: String::from("0.0"),
+                vsz: String::from("1745868"),
+                rss: String::from("3712"),
                 start: String::from("09:27"),
                 time: String::from("0:02"),
                 command: String::from("/usr/bin/docker-proxy -proto tcp -host-ip 0.0.0.0 -host-port 333 -container-ip 172.19.0.4 -container-port 22"),
@@ -360,6 +626,110 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_regular() {
+        let output = "\
+USER PID %CPU %MEM VSZ RSS TTY STAT START TIME COMMAND
+root 1174 0.0 0.1 15432 9012 ? Ss 09:27 0:00 sshd: /usr/sbin/sshd -D
+avahi 723 0.0 0.0 8520 3712 ? Ss 09:27 0:00 avahi-daemon: running
+";
+        let pid = String::from("1174");
+
+        let processes_info = Ps::parse(output, &[&pid]).unwrap();
+
+        assert_eq!(processes_info.len(), 1);
+        assert_eq!(processes_info[0].user, "root");
+        assert_eq!(processes_info[0].command, "sshd: /usr/sbin/sshd -D");
+    }
+
+    #[test]
+    fn parse_ppid_fixture() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ps-axo.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
+        let pid = String::from("2673");
+
+        let processes_info = Ps::parse(&fixture, &[&pid]).unwrap();
+
+        assert_eq!(
+            processes_info,
+            vec![ProcessInfo {
+                user: String::from("root"),
+                ruser: String::from("root"),
+                pid: String::from("2673"),
+                ppid: String::from("998"),
+                pc_cpu: String::from("0.0"),
+                pc_mem: String::from("0.0"),
+                vsz: String::from("1745868"),
+                rss: String::from("3712"),
+                start: String::from("09:27"),
+                time: String::from("00:00:02"),
+                command: String::from("/usr/bin/docker-proxy -proto tcp -host-ip 0.0.0.0 -host-port 333 -container-ip 172.19.0.4 -container-port 22"),
+                _cannot_instantiate: std::marker::PhantomData,
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_vsz_and_rss() {
+        let output = "\
+USER PID %CPU %MEM VSZ RSS TTY STAT START TIME COMMAND
+root 1174 0.0 0.1 15432 9012 ? Ss 09:27 0:00 sshd: /usr/sbin/sshd -D
+";
+        let pid = String::from("1174");
+
+        let processes_info = Ps::parse(output, &[&pid]).unwrap();
+
+        assert_eq!(processes_info[0].vsz, "15432");
+        assert_eq!(processes_info[0].rss, "9012");
+    }
+
+    #[test]
+    fn parse_without_ppid_column() {
+        let output = "\
+USER PID %CPU %MEM START TIME COMMAND
+root 1174 0.0 0.1 09:27 0:00 sshd: /usr/sbin/sshd -D
+";
+        let pid = String::from("1174");
+
+        let processes_info = Ps::parse(output, &[&pid]).unwrap();
+
+        assert_eq!(processes_info[0].ppid, "");
+        assert_eq!(processes_info[0].command, "sshd: /usr/sbin/sshd -D");
+    }
+
+    #[test]
+    fn parse_ruser() {
+        let output = "\
+USER RUSER PID %CPU %MEM START TIME COMMAND
+root www-data 1174 0.0 0.1 09:27 0:00 /usr/bin/sudo -u www-data nginx
+";
+        let pid = String::from("1174");
+
+        let processes_info = Ps::parse(output, &[&pid]).unwrap();
+
+        assert_eq!(processes_info[0].user, "root");
+        assert_eq!(processes_info[0].ruser, "www-data");
+    }
+
+    #[test]
+    fn processes_info_raw() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ps.txt");
+        let fixture = std::fs::read_to_string(fixture).unwrap();
+
+        assert_eq!(Ps::processes_info_raw().unwrap(), fixture);
+    }
+
+    #[test]
+    fn processes_info_duplicate_pids() {
+        let pid = String::from("874");
+        let processes_info = Ps::processes_info(&[&pid, &pid, &pid]).unwrap();
+
+        assert_eq!(processes_info.len(), 1);
+        assert_eq!(processes_info[0].pid, "874");
+    }
+
     #[test]
     fn processes_info_where_command_has_no_spaces() {
         let processes_info = Ps::processes_info(&[&String::from("874")]).unwrap();
@@ -370,9 +740,13 @@ mod tests {
             process,
             ProcessInfo {
                 user: String::from("colord"),
+                ruser: String::new(),
                 pid: String::from("874"),
+                ppid: String::new(),
                 pc_cpu: String::from("0.0"),
                 pc_mem: String::from("0.1"),
+                vsz: String::from("245332"),
+                rss: String::from("12904"),
                 start: String::from("09:27"),
                 time: String::from("0:00"),
                 command: String::from("/usr/libexec/colord"),
@@ -402,7 +776,7 @@ mod tests {
         assert_eq!(
             error,
             PsError {
-                reason: "The ps output is missing the header."
+                reason: String::from("The ps output is missing the header.")
             }
         );
     }
@@ -429,7 +803,7 @@ mod tests {
             PsError {
                 // This is considered an empty header line, and so falls
                 // into this error, instead of "no header"
-                reason: "The ps output is missing expected properties."
+                reason: String::from("The ps output is missing expected properties.")
             }
         );
     }
@@ -462,7 +836,7 @@ mod tests {
         assert_eq!(
             error,
             PsError {
-                reason: "The ps output is missing expected properties.",
+                reason: String::from("The ps output is missing expected properties."),
             }
         );
     }
@@ -488,6 +862,35 @@ mod tests {
         assert_eq!(columns, Ps::headers());
     }
 
+    #[test]
+    fn extract_header_columns_alternative_names_for_time() {
+        for alias in ["ELAPSED", "%TIME"] {
+            let headers = Ps::headers().join(" ").replace("TIME", alias);
+
+            let mut output = headers.lines();
+
+            let columns = Ps::extract_header_columns(&mut output).unwrap();
+
+            assert_eq!(columns, Ps::headers());
+        }
+    }
+
+    #[test]
+    fn elapsed_header_populates_time() {
+        let output = "\
+USER PID %CPU %MEM STARTED ELAPSED COMMAND
+root 1 0.0 0.1 Jul12 0:03.14 /sbin/init
+";
+        let mut output = output.lines();
+
+        let header_columns = Ps::extract_header_columns(&mut output).unwrap();
+        let detail_lines = Ps::extract_detail_lines_of_processes(&mut output);
+        let ps = Ps::map_detail_values_to_properties(&header_columns, &detail_lines);
+
+        assert_eq!(ps[0].time, "0:03.14");
+        assert_eq!(ps[0].start, "Jul12");
+    }
+
     #[test]
     fn extract_detail_lines_of_processes_regular() {
         let output = "\
@@ -535,9 +938,13 @@ This is included too
             ps,
             vec![ProcessInfo {
                 user: String::from("<user>"),
+                ruser: String::new(),
                 pid: String::from("<pid>"),
+                ppid: String::new(),
                 pc_cpu: String::from("<pc_cpu>"),
                 pc_mem: String::from("<pc_mem>"),
+                vsz: String::new(),
+                rss: String::new(),
                 start: String::from("<start>"),
                 time: String::from("<time>"),
                 command: String::from("<command that started the process>"),
@@ -546,6 +953,50 @@ This is included too
         );
     }
 
+    #[test]
+    fn map_detail_values_to_properties_short_line() {
+        let header_columns = [
+            String::from("USER"),
+            String::from("PID"),
+            String::from("%CPU"),
+            String::from("%MEM"),
+            String::from("START"),
+            String::from("TIME"),
+            String::from("COMMAND"),
+        ];
+
+        // Zombie, without command.
+        let detail_lines = [vec!["<user>", "<pid>", "<pc_cpu>", "<pc_mem>", "<start>"]];
+
+        let ps = Ps::map_detail_values_to_properties(&header_columns, &detail_lines);
+
+        assert_eq!(
+            ps,
+            vec![ProcessInfo {
+                user: String::from("<user>"),
+                ruser: String::new(),
+                pid: String::from("<pid>"),
+                ppid: String::new(),
+                pc_cpu: String::from("<pc_cpu>"),
+                pc_mem: String::from("<pc_mem>"),
+                vsz: String::new(),
+                rss: String::new(),
+                start: String::from("<start>"),
+                time: String::new(),
+                command: String::new(),
+                _cannot_instantiate: std::marker::PhantomData
+            }],
+        );
+    }
+
+    #[test]
+    fn extract_safely_out_of_bounds() {
+        let detail_line = ["a", "b"];
+
+        assert_eq!(Ps::extract_safely(&detail_line, 1), "b");
+        assert_eq!(Ps::extract_safely(&detail_line, 2), "");
+    }
+
     #[test]
     fn map_detail_values_to_properties_no_detail_lines() {
         let header_columns = [
@@ -582,9 +1033,13 @@ This is included too
             ps,
             vec![ProcessInfo {
                 user: String::new(),
+                ruser: String::new(),
                 pid: String::from("<pid>"),
+                ppid: String::new(),
                 pc_cpu: String::new(),
                 pc_mem: String::new(),
+                vsz: String::new(),
+                rss: String::new(),
                 start: String::new(),
                 time: String::new(),
                 command: String::new(),
@@ -608,4 +1063,15 @@ This is included too
         assert_eq!(processes[0].pid, "1");
         assert_eq!(processes[1].pid, "3");
     }
+
+    #[test]
+    fn keep_only_relevant_pids_duplicates() {
+        let processes = vec![new_pinfo_with_pid("1"), new_pinfo_with_pid("2")];
+        let pid = String::from("1");
+
+        let processes = Ps::keep_only_relevant_pids(processes, &[&pid, &pid]);
+
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid, "1");
+    }
 }
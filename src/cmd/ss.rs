@@ -0,0 +1,457 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::process::{Command, Output};
+
+use crate::cmd::lsof::{Address, ListeningPort, ListeningPortBuilder, LsofErrorKind};
+
+#[derive(Eq, PartialEq)]
+pub struct SsError {
+    kind: LsofErrorKind,
+    reason: String,
+}
+
+impl SsError {
+    #[must_use]
+    pub fn with_reason(reason: impl Into<String>) -> Self {
+        Self::with_kind(LsofErrorKind::Other, reason)
+    }
+
+    #[must_use]
+    pub fn with_kind(kind: LsofErrorKind, reason: impl Into<String>) -> Self {
+        Self {
+            kind,
+            reason: reason.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn kind(&self) -> LsofErrorKind {
+        self.kind
+    }
+}
+
+impl Error for SsError {}
+
+impl fmt::Debug for SsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+impl fmt::Display for SsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// `ss`, for systems without `lsof` (e.g., slim Linux containers).
+pub struct Ss;
+
+impl Ss {
+    /// Use `ss` to list listening TCP ports.
+    ///
+    /// Ports are as `lsof` would list them: one per process (`ss` lists
+    /// one per socket), with `lsof`-style names (`*:80`), and user
+    /// names instead of UIDs.
+    ///
+    /// Sockets of processes we cannot see (e.g., those of other users
+    /// when not root) are left out, `lsof` doesn't list them either.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the `ss` executable is not found, or if the command
+    ///  exits with a non-zero exit code.
+    pub fn listening_ports() -> Result<Vec<ListeningPort>, SsError> {
+        let output = Self::ss()?;
+        let mut ports = Self::parse(&output)?;
+        let passwd = fs::read_to_string("/etc/passwd").unwrap_or_default();
+        Self::resolve_users(&mut ports, &passwd);
+        Ok(ports)
+    }
+
+    /// Parse `ss -tlnpe` output.
+    ///
+    /// The `user` of the ports is the UID of the socket's owner, see
+    /// [`Ss::resolve_users()`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if the header is missing.
+    pub fn parse(input: &str) -> Result<Vec<ListeningPort>, SsError> {
+        let mut output = input.lines();
+
+        let Some(header) = output.next() else {
+            return Err(SsError {
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The ss output is missing the header."),
+            });
+        };
+        if !header.starts_with("State") {
+            return Err(SsError {
+                kind: LsofErrorKind::Parse,
+                reason: String::from("The ss output is missing the header."),
+            });
+        }
+
+        Ok(output.flat_map(Self::parse_line).collect())
+    }
+
+    /// Replace UIDs with user names, from `/etc/passwd` content.
+    ///
+    /// UIDs that are not in there are left as-is (like `lsof` does).
+    pub fn resolve_users(ports: &mut [ListeningPort], passwd: &str) {
+        let users: HashMap<&str, &str> = passwd
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?;
+                Some((uid, name))
+            })
+            .collect();
+
+        for port in ports {
+            if let Some(name) = users.get(port.user.as_str()) {
+                port.user = String::from(*name);
+            }
+        }
+    }
+
+    #[cfg(not(tarpaulin_include))]
+    fn ss() -> Result<String, SsError> {
+        #![allow(unreachable_code)]
+        #[cfg(test)]
+        {
+            let fixture =
+                std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/ss.txt");
+            let output = std::fs::read_to_string(fixture).expect("cannot read test fixture");
+            return Ok(output);
+        }
+
+        let output = Self::ss_command().output();
+
+        match output {
+            Ok(output) => Self::handle_output_ok(&output),
+            Err(_) => Self::handle_output_err(),
+        }
+    }
+
+    fn ss_command() -> Command {
+        let mut command = Command::new("ss");
+        command.arg("-t"); // -t TCP sockets.
+        command.arg("-l"); // -l Listening sockets only.
+        command.arg("-n"); // -n Do not resolve service names.
+        command.arg("-p"); // -p Processes using the sockets.
+        command.arg("-e"); // -e Extended info (UID of the owner).
+        command
+    }
+
+    fn handle_output_ok(output: &Output) -> Result<String, SsError> {
+        if output.status.success() {
+            // Exit 0.
+            Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+        } else {
+            // Non-zero exit code.
+            Err(SsError {
+                kind: LsofErrorKind::CommandFailed,
+                reason: String::from("The ss command has failed in an unexpected way."),
+            })
+        }
+    }
+
+    fn handle_output_err() -> Result<String, SsError> {
+        Err(SsError {
+            kind: LsofErrorKind::MissingExecutable,
+            reason: String::from("Unable to locate the ss executable on the system."),
+        })
+    }
+
+    /// `State Recv-Q Send-Q Local Peer`, then `key:value` details.
+    ///
+    /// One port per process using the socket.
+    fn parse_line(line: &str) -> Vec<ListeningPort> {
+        let columns: Vec<&str> = line.split_ascii_whitespace().collect();
+        let [_state, _recv_q, _send_q, local, _peer, details @ ..] = columns.as_slice() else {
+            return Vec::new();
+        };
+        let Some((type_, address)) = Self::parse_address(local) else {
+            return Vec::new();
+        };
+        let name = address.to_string();
+
+        // The owner is only printed if it isn't root.
+        let uid = details
+            .iter()
+            .find_map(|detail| detail.strip_prefix("uid:"))
+            .unwrap_or("0");
+
+        details
+            .iter()
+            .find_map(|detail| detail.strip_prefix("users:"))
+            .map(Self::parse_users)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(command, pid)| {
+                ListeningPortBuilder::new()
+                    .command(command)
+                    .pid(pid)
+                    .user(uid)
+                    .type_(type_)
+                    .node("TCP")
+                    .name(&name)
+                    .build()
+            })
+            .collect()
+    }
+
+    /// `ss` has its own way of writing addresses:
+    ///
+    /// - `0.0.0.0:80` and `[::]:80`, for `lsof`'s `*:80`.
+    /// - `*:80`, for dual-stack sockets (IPv6, for `lsof`).
+    /// - `127.0.0.53%lo:53`, with the interface the socket is bound to.
+    fn parse_address(local: &str) -> Option<(&'static str, Address)> {
+        let (host, port) = local.rsplit_once(':')?;
+        let host = host.split_once('%').map_or(host, |(host, _interface)| host);
+
+        let (type_, host) = match host {
+            "0.0.0.0" => ("IPv4", "*"),
+            "[::]" | "*" => ("IPv6", "*"),
+            host if host.starts_with('[') => ("IPv6", host),
+            host => ("IPv4", host),
+        };
+
+        Some((type_, Address::parse(&format!("{host}:{port}"))?))
+    }
+
+    /// `(("nginx",pid=1180,fd=6),("nginx",pid=1179,fd=6))`.
+    fn parse_users(users: &str) -> Vec<(&str, &str)> {
+        let users = users.trim_start_matches('(').trim_end_matches(')');
+        users
+            .split("),(")
+            .filter_map(|user| {
+                let (command, rest) = user.strip_prefix('"')?.rsplit_once("\",")?;
+                let pid = rest
+                    .split(',')
+                    .find_map(|field| field.strip_prefix("pid="))?;
+                Some((command, pid))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+    use std::process::ExitStatus;
+
+    const PASSWD: &str = "\
+root:x:0:0:root:/root:/bin/bash
+systemd-resolve:x:991:991:systemd Resolver:/:/usr/sbin/nologin
+postgres:x:129:137:PostgreSQL administrator,,,:/var/lib/postgresql:/bin/bash
+quentin:x:1000:1000:Quentin,,,:/home/quentin:/bin/bash
+";
+
+    #[test]
+    fn sserror_with_reason() {
+        let error = SsError::with_reason("ss failed.");
+
+        assert_eq!(error.to_string(), "ss failed.");
+        assert_eq!(format!("{error:?}"), "ss failed.");
+    }
+
+    #[test]
+    fn ss_successful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(0),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Ss::handle_output_ok(&output).unwrap();
+
+        assert_eq!(res, "<stdout>");
+    }
+
+    #[test]
+    fn ss_unsuccessful_read() {
+        let output = Output {
+            status: ExitStatus::from_raw(1 << 8),
+            stdout: b"<stdout>".to_vec(),
+            stderr: b"<stderr>".to_vec(),
+        };
+
+        let res = Ss::handle_output_ok(&output).unwrap_err();
+
+        assert_eq!(
+            res,
+            SsError::with_kind(
+                LsofErrorKind::CommandFailed,
+                "The ss command has failed in an unexpected way."
+            )
+        );
+    }
+
+    #[test]
+    fn ss_error_with_command() {
+        let res = Ss::handle_output_err().unwrap_err();
+
+        assert_eq!(
+            res,
+            SsError::with_kind(
+                LsofErrorKind::MissingExecutable,
+                "Unable to locate the ss executable on the system."
+            )
+        );
+    }
+
+    #[test]
+    fn ss_command_args() {
+        let command = Ss::ss_command();
+
+        assert_eq!(command.get_program(), "ss");
+        let args: Vec<&std::ffi::OsStr> = command.get_args().collect();
+        assert_eq!(args, ["-t", "-l", "-n", "-p", "-e"]);
+    }
+
+    #[test]
+    fn listening_ports_regular() {
+        let mut listening_ports = Ss::parse(&Ss::ss().unwrap()).unwrap();
+        Ss::resolve_users(&mut listening_ports, PASSWD);
+
+        assert_eq!(
+            listening_ports[0],
+            ListeningPortBuilder::new()
+                .command("systemd-resolve")
+                .pid("580")
+                .user("systemd-resolve")
+                .type_("IPv4")
+                .node("TCP")
+                .name("127.0.0.53:53")
+                .build()
+        );
+    }
+
+    #[test]
+    fn listening_ports_names() {
+        let listening_ports = Ss::listening_ports().unwrap();
+
+        let names: Vec<(&str, &str, &str)> = listening_ports
+            .iter()
+            .map(|x| (x.pid.as_str(), x.type_.as_str(), x.name.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("580", "IPv4", "127.0.0.53:53"),
+                ("816", "IPv4", "127.0.0.1:631"),
+                ("2493", "IPv4", "*:8024"),
+                ("1021", "IPv4", "127.0.0.1:5432"),
+                ("1180", "IPv4", "*:80"),
+                ("1179", "IPv4", "*:80"),
+                ("6610", "IPv4", "127.0.0.1:8000"),
+                ("816", "IPv6", "[::1]:631"),
+                ("2500", "IPv6", "*:8024"),
+                ("1180", "IPv6", "*:443"),
+                ("1179", "IPv6", "*:443"),
+                ("6702", "IPv6", "[fe80::a00:27ff:fe4e:66a1]:9000"),
+            ]
+        );
+    }
+
+    #[test]
+    fn listening_ports_skips_sockets_without_process() {
+        let listening_ports = Ss::listening_ports().unwrap();
+
+        // sshd's socket, `ss` was not allowed to see the process.
+        assert!(!listening_ports.iter().any(|x| x.name == "*:22"));
+    }
+
+    #[test]
+    fn parse_keeps_uids() {
+        let output = "\
+State  Recv-Q Send-Q Local Address:Port  Peer Address:PortProcess
+LISTEN 0      5          127.0.0.1:8000       0.0.0.0:*    users:((\"python3\",pid=6610,fd=3)) uid:1000 ino:61122 sk:6 <->
+LISTEN 0      128        127.0.0.1:631        0.0.0.0:*    users:((\"cupsd\",pid=816,fd=8)) ino:22618 sk:2 <->
+";
+
+        let listening_ports = Ss::parse(output).unwrap();
+
+        assert_eq!(listening_ports[0].user, "1000");
+        // Root is implicit.
+        assert_eq!(listening_ports[1].user, "0");
+    }
+
+    #[test]
+    fn parse_empty() {
+        let output = "State  Recv-Q Send-Q Local Address:Port  Peer Address:PortProcess\n";
+
+        assert!(Ss::parse(output).unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_missing_header() {
+        for output in ["", "LISTEN 0 5 127.0.0.1:8000 0.0.0.0:*"] {
+            let res = Ss::parse(output).unwrap_err();
+
+            assert_eq!(res.to_string(), "The ss output is missing the header.");
+        }
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let output = "\
+State  Recv-Q Send-Q Local Address:Port  Peer Address:PortProcess
+LISTEN 0      5
+LISTEN 0      5          127.0.0.1:abc        0.0.0.0:*    users:((\"python3\",pid=6610,fd=3))
+";
+
+        assert!(Ss::parse(output).unwrap().is_empty());
+    }
+
+    #[test]
+    fn resolve_users_regular() {
+        let mut ports = vec![
+            ListeningPortBuilder::new().user("0").build(),
+            ListeningPortBuilder::new().user("1000").build(),
+            ListeningPortBuilder::new().user("4242").build(),
+        ];
+
+        Ss::resolve_users(&mut ports, PASSWD);
+
+        let users: Vec<&str> = ports.iter().map(|x| x.user.as_str()).collect();
+        assert_eq!(users, ["root", "quentin", "4242"]);
+    }
+
+    #[test]
+    fn parse_users_multiple() {
+        let users = Ss::parse_users("((\"nginx\",pid=1180,fd=6),(\"nginx\",pid=1179,fd=6))");
+
+        assert_eq!(users, [("nginx", "1180"), ("nginx", "1179")]);
+    }
+
+    #[test]
+    fn parse_users_command_with_comma() {
+        let users = Ss::parse_users("((\"a,b\",pid=42,fd=3))");
+
+        assert_eq!(users, [("a,b", "42")]);
+    }
+}
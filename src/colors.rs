@@ -0,0 +1,189 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! ANSI colors for the table.
+
+use std::fmt;
+
+const RESET: &str = "\x1b[0m";
+
+/// When to color the output (`--color`).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ColorMode {
+    Always,
+    /// Only when writing to a terminal.
+    #[default]
+    Auto,
+    Never,
+}
+
+impl ColorMode {
+    /// Mode of a `--color` value.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "always" => Some(Self::Always),
+            "auto" => Some(Self::Auto),
+            "never" => Some(Self::Never),
+            _ => None,
+        }
+    }
+
+    /// Value of `--color`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Auto => "auto",
+            Self::Never => "never",
+        }
+    }
+
+    /// Whether to color output going to a terminal or not.
+    pub fn enabled(self, is_terminal: bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Auto => is_terminal,
+            Self::Never => false,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Style {
+    Bold,
+    Yellow,
+    Cyan,
+    /// Swapped foreground and background colors.
+    Reverse,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Self::Bold => "1",
+            Self::Yellow => "33",
+            Self::Cyan => "36",
+            Self::Reverse => "7",
+        }
+    }
+
+    fn start(self) -> String {
+        format!("\x1b[{}m", self.code())
+    }
+
+    pub fn paint(self, text: &str) -> String {
+        format!("{}{text}{RESET}", self.start())
+    }
+
+    /// Paint a whole line, including its already painted parts.
+    ///
+    /// Painted parts end with a reset, which would also end the style
+    /// of the line, so it is started again after each of them.
+    pub fn paint_line(self, line: &str) -> String {
+        let start = self.start();
+        let line = line.replace(RESET, &format!("{RESET}{start}"));
+        format!("{start}{line}{RESET}")
+    }
+
+    /// Paint `text`, padded to `width` outside the escape codes.
+    ///
+    /// Table layouts count escape codes as characters. Padding every
+    /// cell of a column to the same width before painting it gives
+    /// them all the same length, so the column stays aligned.
+    pub fn paint_padded(self, text: &str, width: usize, alignment: fmt::Alignment) -> String {
+        let padding = width.saturating_sub(text.chars().count());
+        let (left, right) = match alignment {
+            fmt::Alignment::Left => (0, padding),
+            fmt::Alignment::Right => (padding, 0),
+            fmt::Alignment::Center => (padding / 2, padding - padding / 2),
+        };
+        format!(
+            "{}{}{}",
+            " ".repeat(left),
+            self.paint(text),
+            " ".repeat(right)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn color_mode_from_name_round_trip() {
+        for mode in [ColorMode::Always, ColorMode::Auto, ColorMode::Never] {
+            assert_eq!(ColorMode::from_name(mode.name()), Some(mode));
+        }
+        assert_eq!(ColorMode::from_name("sometimes"), None);
+    }
+
+    #[test]
+    fn color_mode_enabled() {
+        assert!(ColorMode::Always.enabled(false));
+        assert!(ColorMode::Auto.enabled(true));
+        assert!(!ColorMode::Auto.enabled(false));
+        assert!(!ColorMode::Never.enabled(true));
+    }
+
+    #[test]
+    fn style_paint() {
+        assert_eq!(Style::Bold.paint("sshd"), "\x1b[1msshd\x1b[0m");
+        assert_eq!(Style::Yellow.paint("1174"), "\x1b[33m1174\x1b[0m");
+        assert_eq!(Style::Cyan.paint("*:22"), "\x1b[36m*:22\x1b[0m");
+    }
+
+    #[test]
+    fn style_paint_line() {
+        assert_eq!(
+            Style::Reverse.paint_line("sshd 22"),
+            "\x1b[7msshd 22\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn style_paint_line_already_painted() {
+        let line = format!("{} 22", Style::Bold.paint("sshd"));
+
+        assert_eq!(
+            Style::Reverse.paint_line(&line),
+            "\x1b[7m\x1b[1msshd\x1b[0m\x1b[7m 22\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn style_paint_padded() {
+        assert_eq!(
+            Style::Cyan.paint_padded("*:22", 6, fmt::Alignment::Left),
+            "\x1b[36m*:22\x1b[0m  "
+        );
+        assert_eq!(
+            Style::Cyan.paint_padded("*:22", 6, fmt::Alignment::Right),
+            "  \x1b[36m*:22\x1b[0m"
+        );
+        assert_eq!(
+            Style::Cyan.paint_padded("*:22", 7, fmt::Alignment::Center),
+            " \x1b[36m*:22\x1b[0m  "
+        );
+    }
+
+    #[test]
+    fn style_paint_padded_wider_than_width() {
+        assert_eq!(
+            Style::Bold.paint_padded("sshd", 2, fmt::Alignment::Left),
+            "\x1b[1msshd\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+#![allow(clippy::module_name_repetitions)]
+
+pub mod ansible;
+pub mod debug;
+pub mod env;
+pub mod graphql;
+pub mod json;
+pub mod k8s;
+pub mod nginx;
+pub mod openmetrics;
+pub mod systemd;
+
+pub use ansible::AnsibleVarsFormatter;
+pub use debug::{DebugFormatter, PrettyDebugFormatter};
+pub use env::EnvFormatter;
+pub use graphql::GraphQLFormatter;
+pub use json::JsonFormatter;
+pub use k8s::K8sLabelsFormatter;
+pub use nginx::NginxUpstreamFormatter;
+pub use openmetrics::OpenMetricsFormatter;
+pub use systemd::SystemdSocketFormatter;
+
+use crate::cmd::lsof::ListeningPort;
+
+/// Render listening ports in a machine-readable format.
+pub trait Formatter {
+    /// Format all ports into a single document.
+    fn format(&self, listening_ports: &[ListeningPort]) -> String;
+}
+
+/// Unique port numbers of each process, in order of appearance.
+///
+/// Processes are identified by [`ListeningPort::process_name()`]. Ports
+/// without a numeric port are skipped.
+fn port_numbers_by_command(listening_ports: &[ListeningPort]) -> Vec<(&str, Vec<u16>)> {
+    let mut processes: Vec<(&str, Vec<u16>)> = Vec::new();
+
+    for port in listening_ports {
+        let Some(number) = port.port_number() else {
+            continue;
+        };
+
+        let command = port.process_name();
+        let ports = if let Some((_, ports)) = processes.iter_mut().find(|(c, _)| *c == command) {
+            ports
+        } else {
+            processes.push((command, Vec::new()));
+            &mut processes.last_mut().expect("just pushed").1
+        };
+
+        if !ports.contains(&number) {
+            ports.push(number);
+        }
+    }
+
+    processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+    use crate::cmd::ps::ProcessInfo;
+
+    #[test]
+    fn port_numbers_by_command_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .name("*:22")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:443")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("[::]:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .name("*:ssh")
+                .build(),
+        ];
+
+        let processes = port_numbers_by_command(&ports);
+
+        assert_eq!(processes, [("nginx", vec![8080, 443]), ("sshd", vec![22])]);
+    }
+
+    #[test]
+    fn port_numbers_by_command_uses_process_name() {
+        let mut port = ListeningPortBuilder::new()
+            .command("docker-pr")
+            .name("*:8080")
+            .build();
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("/usr/bin/docker-proxy -host-port 8080");
+        port.pinfo = Some(pinfo);
+
+        let ports = [port];
+        let processes = port_numbers_by_command(&ports);
+
+        assert_eq!(processes, [("docker-proxy", vec![8080])]);
+    }
+
+    #[test]
+    fn port_numbers_by_command_empty() {
+        assert!(port_numbers_by_command(&[]).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// Ansible variables, to be used in a `vars:` block.
+///
+/// ```yaml
+/// listening_ports:
+///   - command: "nginx"
+///     pid: 1234
+///     user: "root"
+///     type: "IPv4"
+///     node: "TCP"
+///     name: "*:80"
+///     port: 80
+/// ```
+///
+/// Strings are always double-quoted, so that values like `*:80` (`*`
+/// denotes an alias in YAML) are read back as-is.
+pub struct AnsibleVarsFormatter;
+
+impl Formatter for AnsibleVarsFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        if listening_ports.is_empty() {
+            return String::from("listening_ports: []\n");
+        }
+
+        let mut output = String::from("listening_ports:\n");
+        for port in listening_ports {
+            let pid = port
+                .pid
+                .parse::<u32>()
+                .map_or_else(|_| quote(&port.pid), |pid| pid.to_string());
+            let port_number = port
+                .port_number()
+                .map_or_else(|| String::from("null"), |n| n.to_string());
+
+            _ = writeln!(output, "  - command: {}", quote(&port.command));
+            _ = writeln!(output, "    pid: {pid}");
+            _ = writeln!(output, "    user: {}", quote(&port.user));
+            _ = writeln!(output, "    type: {}", quote(&port.type_));
+            _ = writeln!(output, "    node: {}", quote(&port.node));
+            _ = writeln!(output, "    name: {}", quote(&port.name));
+            _ = writeln!(output, "    port: {port_number}");
+        }
+        output
+    }
+}
+
+/// YAML double-quoted string.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if c.is_control() => _ = write!(quoted, "\\u{:04X}", u32::from(c)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .pid("1234")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:80")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .pid("42")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("127.0.0.1:22")
+                .build(),
+        ];
+
+        let output = AnsibleVarsFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            r#"listening_ports:
+  - command: "nginx"
+    pid: 1234
+    user: "root"
+    type: "IPv4"
+    node: "TCP"
+    name: "*:80"
+    port: 80
+  - command: "sshd"
+    pid: 42
+    user: "root"
+    type: "IPv4"
+    node: "TCP"
+    name: "127.0.0.1:22"
+    port: 22
+"#
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = AnsibleVarsFormatter.format(&[]);
+
+        assert_eq!(output, "listening_ports: []\n");
+    }
+
+    #[test]
+    fn format_unparsable_numbers() {
+        let ports = [ListeningPortBuilder::new()
+            .command("nginx")
+            .pid("<pid>")
+            .user("root")
+            .type_("IPv4")
+            .node("TCP")
+            .name("*:http")
+            .build()];
+
+        let output = AnsibleVarsFormatter.format(&ports);
+
+        assert!(output.contains("    pid: \"<pid>\"\n"));
+        assert!(output.contains("    port: null\n"));
+    }
+
+    #[test]
+    fn quote_regular() {
+        assert_eq!(quote("nginx"), r#""nginx""#);
+    }
+
+    #[test]
+    fn quote_escapes_special_characters() {
+        assert_eq!(quote(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(quote("a\tb\u{1b}"), r#""a\u0009b\u001B""#);
+    }
+}
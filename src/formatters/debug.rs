@@ -0,0 +1,107 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// `Debug` representation of each port, one per line.
+///
+/// Shows the exact values of the parsed fields (including stray
+/// whitespace), to debug the parsers.
+pub struct DebugFormatter;
+
+impl Formatter for DebugFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::new();
+        for port in listening_ports {
+            _ = writeln!(output, "{port:?}");
+        }
+        output
+    }
+}
+
+/// Pretty-printed `Debug` representation of each port.
+///
+/// Like [`DebugFormatter`], but multiline, which is easier to read for
+/// a single port.
+pub struct PrettyDebugFormatter;
+
+impl Formatter for PrettyDebugFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::new();
+        for port in listening_ports {
+            _ = writeln!(output, "{port:#?}");
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_regular() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx ");
+        port.name = String::from("*:80");
+
+        let output = DebugFormatter.format(&[port.clone(), port]);
+
+        let line = r#"ListeningPort { command: "nginx ", pid: "", user: "", type_: "", node: "", name: "*:80", pinfo: None, _cannot_instantiate: PhantomData<()> }"#;
+        assert_eq!(output, format!("{line}\n{line}\n"));
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = DebugFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn format_pretty_regular() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.name = String::from("*:80");
+
+        let output = PrettyDebugFormatter.format(&[port]);
+
+        assert_eq!(
+            output,
+            r#"ListeningPort {
+    command: "nginx",
+    pid: "",
+    user: "",
+    type_: "",
+    node: "",
+    name: "*:80",
+    pinfo: None,
+    _cannot_instantiate: PhantomData<()>,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn format_pretty_empty() {
+        let output = PrettyDebugFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+}
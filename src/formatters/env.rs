@@ -0,0 +1,179 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// Shell variable assignments, to be `eval`'d.
+///
+/// ```sh
+/// PORT_8080_PID='1234'
+/// PORT_8080_CMD='nginx'
+/// PORT_8080_USER='root'
+/// PORT_8080_TYPE='IPv4'
+/// PORT_8080_NODE='TCP'
+/// PORT_8080_NAME='*:8080'
+/// ```
+///
+/// Variables are named after the port number, so a port that is bound
+/// multiple times (e.g., IPv4 and IPv6) is only output once, for the
+/// first socket. Ports without a numeric port are skipped.
+pub struct EnvFormatter;
+
+impl Formatter for EnvFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::new();
+        let mut seen = Vec::new();
+
+        for port in listening_ports {
+            let Some(number) = port.port_number() else {
+                continue;
+            };
+            if seen.contains(&number) {
+                continue;
+            }
+            seen.push(number);
+
+            for (field, value) in [
+                ("PID", &port.pid),
+                ("CMD", &port.command),
+                ("USER", &port.user),
+                ("TYPE", &port.type_),
+                ("NODE", &port.node),
+                ("NAME", &port.name),
+            ] {
+                _ = writeln!(output, "PORT_{number}_{field}={}", quote(value));
+            }
+        }
+
+        output
+    }
+}
+
+/// Single-quote for POSIX shells (`'` becomes `'\''`).
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .pid("1234")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .pid("1234")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:443")
+                .build(),
+        ];
+
+        let output = EnvFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+PORT_8080_PID='1234'
+PORT_8080_CMD='nginx'
+PORT_8080_USER='root'
+PORT_8080_TYPE='IPv4'
+PORT_8080_NODE='TCP'
+PORT_8080_NAME='*:8080'
+PORT_443_PID='1234'
+PORT_443_CMD='nginx'
+PORT_443_USER='root'
+PORT_443_TYPE='IPv4'
+PORT_443_NODE='TCP'
+PORT_443_NAME='*:443'
+"
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = EnvFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn format_same_port_is_output_once() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .pid("1234")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("other")
+                .pid("5678")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("[::1]:8080")
+                .build(),
+        ];
+
+        let output = EnvFormatter.format(&ports);
+
+        assert_eq!(output.matches("PORT_8080_PID=").count(), 1);
+        assert!(output.contains("PORT_8080_PID='1234'\n"));
+    }
+
+    #[test]
+    fn format_non_numeric_port_is_skipped() {
+        let ports = [ListeningPortBuilder::new()
+            .command("nginx")
+            .pid("1234")
+            .user("root")
+            .type_("IPv4")
+            .node("TCP")
+            .name("*:http")
+            .build()];
+
+        let output = EnvFormatter.format(&ports);
+
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn quote_regular() {
+        assert_eq!(quote("nginx"), "'nginx'");
+    }
+
+    #[test]
+    fn quote_single_quotes() {
+        assert_eq!(quote("it's"), r"'it'\''s'");
+    }
+}
@@ -0,0 +1,105 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::json::{quote, JsonFormatter};
+use crate::formatters::Formatter;
+
+/// JSON array of ports, wrapped in a GraphQL response envelope.
+///
+/// ```json
+/// {"data":{"listeningPorts":[...]}}
+/// ```
+///
+/// The ports are formatted by [`JsonFormatter`].
+pub struct GraphQLFormatter {
+    field_name: String,
+}
+
+impl GraphQLFormatter {
+    /// Name of the field holding the ports is `listeningPorts`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_field_name("listeningPorts")
+    }
+
+    /// Use a custom name for the field holding the ports.
+    #[must_use]
+    pub fn with_field_name(field_name: &str) -> Self {
+        Self {
+            field_name: field_name.to_string(),
+        }
+    }
+}
+
+impl Default for GraphQLFormatter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Formatter for GraphQLFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let ports = JsonFormatter.format(listening_ports);
+        format!(
+            "{{\"data\":{{{}:{}}}}}\n",
+            quote(&self.field_name),
+            ports.trim_end()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_regular() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.name = String::from("*:80");
+
+        let output = GraphQLFormatter::new().format(&[port]);
+
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"data":{"listeningPorts":[{"command":"nginx","pid":"","user":"","#,
+                r#""type":"","node":"","name":"*:80"}]}}"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = GraphQLFormatter::new().format(&[]);
+
+        assert_eq!(output, "{\"data\":{\"listeningPorts\":[]}}\n");
+    }
+
+    #[test]
+    fn format_custom_field_name() {
+        let output = GraphQLFormatter::with_field_name("ports").format(&[]);
+
+        assert_eq!(output, "{\"data\":{\"ports\":[]}}\n");
+    }
+
+    #[test]
+    fn default_field_name() {
+        assert_eq!(GraphQLFormatter::default().field_name, "listeningPorts");
+    }
+}
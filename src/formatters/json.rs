@@ -0,0 +1,127 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// JSON array of ports.
+///
+/// ```json
+/// [{"command":"nginx","pid":"1234","user":"root","type":"IPv4","node":"TCP","name":"*:80"}]
+/// ```
+///
+/// Values are output as strings, exactly as reported by `lsof`.
+pub struct JsonFormatter;
+
+impl Formatter for JsonFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let ports: Vec<String> = listening_ports
+            .iter()
+            .map(|port| {
+                format!(
+                    "{{\"command\":{},\"pid\":{},\"user\":{},\"type\":{},\"node\":{},\"name\":{}}}",
+                    quote(&port.command),
+                    quote(&port.pid),
+                    quote(&port.user),
+                    quote(&port.type_),
+                    quote(&port.node),
+                    quote(&port.name),
+                )
+            })
+            .collect();
+
+        format!("[{}]\n", ports.join(","))
+    }
+}
+
+/// JSON string, quoted and escaped.
+#[must_use]
+pub fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => _ = write!(quoted, "\\u{:04x}", u32::from(c)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .pid("1234")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:80")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .pid("42")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("[::1]:22")
+                .build(),
+        ];
+
+        let output = JsonFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            concat!(
+                r#"[{"command":"nginx","pid":"1234","user":"root","type":"IPv4","node":"TCP","name":"*:80"},"#,
+                r#"{"command":"sshd","pid":"42","user":"root","type":"IPv4","node":"TCP","name":"[::1]:22"}]"#,
+                "\n",
+            )
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = JsonFormatter.format(&[]);
+
+        assert_eq!(output, "[]\n");
+    }
+
+    #[test]
+    fn quote_regular() {
+        assert_eq!(quote("nginx"), r#""nginx""#);
+    }
+
+    #[test]
+    fn quote_escapes_special_characters() {
+        assert_eq!(quote(r#"a"b\c"#), r#""a\"b\\c""#);
+        assert_eq!(quote("a\nb\tc\r\u{1b}"), r#""a\nb\tc\r\u001b""#);
+    }
+}
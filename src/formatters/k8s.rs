@@ -0,0 +1,141 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::{port_numbers_by_command, Formatter};
+
+/// Kubernetes label selectors, one per process (`kubectl get -l ...`).
+///
+/// ```text
+/// app=nginx,port in (8080,443)
+/// app=sshd,port in (22)
+/// ```
+///
+/// Processes are identified by their name, and listed in order
+/// of appearance. Ports without a numeric port are skipped.
+pub struct K8sLabelsFormatter;
+
+impl Formatter for K8sLabelsFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::new();
+        for (command, ports) in port_numbers_by_command(listening_ports) {
+            let ports: Vec<String> = ports.iter().map(ToString::to_string).collect();
+            _ = writeln!(output, "app={command},port in ({})", ports.join(","));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:443")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .name("*:22")
+                .build(),
+        ];
+
+        let output = K8sLabelsFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+app=nginx,port in (8080,443)
+app=sshd,port in (22)
+"
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = K8sLabelsFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn format_groups_non_consecutive_processes() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .name("*:22")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:443")
+                .build(),
+        ];
+
+        let output = K8sLabelsFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+app=nginx,port in (8080,443)
+app=sshd,port in (22)
+"
+        );
+    }
+
+    #[test]
+    fn format_duplicate_ports_are_output_once() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("[::]:8080")
+                .build(),
+        ];
+
+        let output = K8sLabelsFormatter.format(&ports);
+
+        assert_eq!(output, "app=nginx,port in (8080)\n");
+    }
+
+    #[test]
+    fn format_non_numeric_port_is_skipped() {
+        let ports = [ListeningPortBuilder::new()
+            .command("nginx")
+            .name("*:http")
+            .build()];
+
+        let output = K8sLabelsFormatter.format(&ports);
+
+        assert_eq!(output, "");
+    }
+}
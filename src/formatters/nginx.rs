@@ -0,0 +1,98 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::{port_numbers_by_command, Formatter};
+
+/// nginx `upstream` blocks, one per process.
+///
+/// ```nginx
+/// upstream nginx {
+///     server 127.0.0.1:8080;
+///     server 127.0.0.1:443;
+/// }
+/// ```
+///
+/// Processes are identified by their name, and listed in order
+/// of appearance. Servers always point to the loopback interface, as
+/// the goal is to proxy local services.
+pub struct NginxUpstreamFormatter;
+
+impl Formatter for NginxUpstreamFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut blocks = Vec::new();
+
+        for (command, ports) in port_numbers_by_command(listening_ports) {
+            let mut block = format!("upstream {command} {{\n");
+            for port in ports {
+                _ = writeln!(block, "    server 127.0.0.1:{port};");
+            }
+            block.push_str("}\n");
+            blocks.push(block);
+        }
+
+        blocks.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("*:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .name("*:22")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .name("[::1]:443")
+                .build(),
+        ];
+
+        let output = NginxUpstreamFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+upstream nginx {
+    server 127.0.0.1:8080;
+    server 127.0.0.1:443;
+}
+
+upstream sshd {
+    server 127.0.0.1:22;
+}
+"
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = NginxUpstreamFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+}
@@ -0,0 +1,131 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// [OpenMetrics](https://openmetrics.io) text exposition
+/// (`application/openmetrics-text`).
+///
+/// ```text
+/// # TYPE ports_listening info
+/// # HELP ports_listening Socket listening for connections.
+/// ports_listening_info{command="nginx",pid="1234",...,port="80"} 1
+/// # EOF
+/// ```
+///
+/// Every socket is an `info` sample, carrying the port's properties as
+/// labels.
+pub struct OpenMetricsFormatter;
+
+impl Formatter for OpenMetricsFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::from(
+            "\
+# TYPE ports_listening info
+# HELP ports_listening Socket listening for connections.
+",
+        );
+
+        for port in listening_ports {
+            let port_number = port
+                .port_number()
+                .map(|n| n.to_string())
+                .unwrap_or_default();
+            _ = writeln!(
+                output,
+                "ports_listening_info{{command={},pid={},user={},type={},node={},name={},port={}}} 1",
+                quote(&port.command),
+                quote(&port.pid),
+                quote(&port.user),
+                quote(&port.type_),
+                quote(&port.node),
+                quote(&port.name),
+                quote(&port_number),
+            );
+        }
+
+        output.push_str("# EOF\n");
+        output
+    }
+}
+
+/// Label value.
+fn quote(value: &str) -> String {
+    let escaped = value
+        .replace('\\', r"\\")
+        .replace('"', r#"\""#)
+        .replace('\n', r"\n");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_regular() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.pid = String::from("1234");
+        port.user = String::from("root");
+        port.type_ = String::from("IPv4");
+        port.node = String::from("TCP");
+        port.name = String::from("*:80");
+
+        let output = OpenMetricsFormatter.format(&[port]);
+
+        assert_eq!(
+            output,
+            r#"# TYPE ports_listening info
+# HELP ports_listening Socket listening for connections.
+ports_listening_info{command="nginx",pid="1234",user="root",type="IPv4",node="TCP",name="*:80",port="80"} 1
+# EOF
+"#
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = OpenMetricsFormatter.format(&[]);
+
+        assert_eq!(
+            output,
+            "\
+# TYPE ports_listening info
+# HELP ports_listening Socket listening for connections.
+# EOF
+"
+        );
+    }
+
+    #[test]
+    fn format_non_numeric_port() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:http");
+
+        let output = OpenMetricsFormatter.format(&[port]);
+
+        assert!(output.contains(r#",name="*:http",port=""} 1"#));
+    }
+
+    #[test]
+    fn quote_escapes_special_characters() {
+        assert_eq!(quote("a\"b\\c\nd"), r#""a\"b\\c\nd""#);
+    }
+}
@@ -0,0 +1,172 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use crate::cmd::lsof::ListeningPort;
+use crate::formatters::Formatter;
+
+/// Draft systemd `.socket` units, one per process.
+///
+/// ```ini
+/// # nginx.socket
+/// [Socket]
+/// ListenStream=80
+/// ListenStream=127.0.0.1:8080
+///
+/// [Install]
+/// WantedBy=sockets.target
+/// ```
+///
+/// TCP sockets are `ListenStream`, UDP sockets are `ListenDatagram`.
+/// Sockets bound to all interfaces (`*`) only have a port. Processes
+/// are identified by their name, and listed in order of appearance.
+pub struct SystemdSocketFormatter;
+
+impl Formatter for SystemdSocketFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut units = Vec::new();
+
+        for (command, listens) in listens_by_command(listening_ports) {
+            let mut unit = format!("# {command}.socket\n[Socket]\n");
+            for listen in listens {
+                _ = writeln!(unit, "{listen}");
+            }
+            unit.push_str("\n[Install]\nWantedBy=sockets.target\n");
+            units.push(unit);
+        }
+
+        units.join("\n")
+    }
+}
+
+/// Unique `Listen*=` directives of each process, in order of appearance.
+fn listens_by_command(listening_ports: &[ListeningPort]) -> Vec<(&str, Vec<String>)> {
+    let mut processes: Vec<(&str, Vec<String>)> = Vec::new();
+
+    for port in listening_ports {
+        let directive = if port.node.eq_ignore_ascii_case("UDP") {
+            "ListenDatagram"
+        } else {
+            "ListenStream"
+        };
+        let address = port.name.strip_prefix("*:").unwrap_or(&port.name);
+        let listen = format!("{directive}={address}");
+
+        let command = port.process_name();
+        if let Some((_, listens)) = processes.iter_mut().find(|(x, _)| *x == command) {
+            if !listens.contains(&listen) {
+                listens.push(listen);
+            }
+        } else {
+            processes.push((command, vec![listen]));
+        }
+    }
+
+    processes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cmd::lsof::ListeningPortBuilder;
+
+    #[test]
+    fn format_regular() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .node("TCP")
+                .name("*:80")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .node("TCP")
+                .name("127.0.0.1:8080")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("avahi-dae")
+                .node("UDP")
+                .name("*:5353")
+                .build(),
+        ];
+
+        let output = SystemdSocketFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+# nginx.socket
+[Socket]
+ListenStream=80
+ListenStream=127.0.0.1:8080
+
+[Install]
+WantedBy=sockets.target
+
+# avahi-dae.socket
+[Socket]
+ListenDatagram=5353
+
+[Install]
+WantedBy=sockets.target
+"
+        );
+    }
+
+    #[test]
+    fn format_empty() {
+        let output = SystemdSocketFormatter.format(&[]);
+
+        assert_eq!(output, "");
+    }
+
+    #[test]
+    fn format_duplicate_listens_are_output_once() {
+        let ports = [
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .node("TCP")
+                .name("*:80")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .node("TCP")
+                .name("*:80")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("nginx")
+                .node("TCP")
+                .name("[::1]:80")
+                .build(),
+        ];
+
+        let output = SystemdSocketFormatter.format(&ports);
+
+        assert_eq!(
+            output,
+            "\
+# nginx.socket
+[Socket]
+ListenStream=80
+ListenStream=[::1]:80
+
+[Install]
+WantedBy=sockets.target
+"
+        );
+    }
+}
@@ -0,0 +1,378 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Help content, and its renderings.
+
+mod json;
+mod man;
+mod markdown;
+mod txt;
+
+pub use json::JsonRenderer;
+pub use man::ManRenderer;
+pub use markdown::MarkdownRenderer;
+pub use txt::TxtRenderer;
+
+/// Render help content in a given format.
+pub trait HelpRenderer {
+    fn render(&self, help: &Help) -> String;
+}
+
+/// Everything `--help` has to say.
+pub struct Help {
+    pub bin: String,
+    pub description: String,
+    /// Arguments, after the bin name.
+    pub usage: String,
+    /// Explanation of the port filters, one sentence per line.
+    pub filters: Vec<String>,
+    pub options: Vec<HelpOption>,
+    /// Values of `--format`.
+    pub formats: Vec<HelpValue>,
+}
+
+pub struct HelpOption {
+    pub short: Option<&'static str>,
+    pub name: &'static str,
+    /// Placeholder of the value the option takes, if any.
+    pub value: Option<&'static str>,
+    pub description: String,
+}
+
+impl HelpOption {
+    fn new(short: Option<&'static str>, name: &'static str, description: &str) -> Self {
+        Self {
+            short,
+            name,
+            value: None,
+            description: String::from(description),
+        }
+    }
+
+    fn with_value(name: &'static str, value: &'static str, description: &str) -> Self {
+        Self {
+            short: None,
+            name,
+            value: Some(value),
+            description: String::from(description),
+        }
+    }
+
+    fn short(mut self, short: &'static str) -> Self {
+        self.short = Some(short);
+        self
+    }
+}
+
+pub struct HelpValue {
+    pub name: &'static str,
+    pub description: String,
+}
+
+impl HelpValue {
+    fn new(name: &'static str, description: &str) -> Self {
+        Self {
+            name,
+            description: String::from(description),
+        }
+    }
+}
+
+impl Help {
+    #[must_use]
+    #[allow(clippy::too_many_lines)] // One entry per option.
+    pub fn new() -> Self {
+        let bin = env!("CARGO_BIN_NAME");
+
+        let mut help = Self {
+            bin: String::from(bin),
+            description: String::from(env!("CARGO_PKG_DESCRIPTION")),
+            usage: String::from("[OPTIONS] [PORT[-RANGE] ...]"),
+            filters: vec![
+                String::from("Filter on ports by passing port numbers or port ranges."),
+                format!("For example `{bin} 8000 8003` or `{bin} 8000-8005`."),
+            ],
+            options: vec![
+                HelpOption::new(Some("-h"), "--help", "Show this message and exit."),
+                HelpOption::new(Some("-v"), "--version", "Show the version and exit."),
+                HelpOption::new(Some("-vv"), "--verbose", "Additional process info."),
+                HelpOption::new(Some("-vvv"), "--very-verbose", "Even more extra info."),
+                HelpOption::new(None, "--include-udp", "Also list UDP sockets."),
+                HelpOption::new(None, "--udp", "Same as --include-udp."),
+                HelpOption::new(
+                    None,
+                    "--unix",
+                    "Also list Unix domain sockets (Linux only).",
+                ),
+                HelpOption::with_value(
+                    "--include-pids-file",
+                    "<PATH>",
+                    "Only list PIDs in file (one per line).",
+                ),
+                HelpOption::with_value("--pid", "<PID>", "Only list ports of PID (repeatable)."),
+                HelpOption::with_value(
+                    "--exclude-port",
+                    "<PORT>",
+                    "Hide port or range (repeatable).",
+                ),
+                HelpOption::with_value("--user", "<NAME>", "Only list ports of user (repeatable)."),
+                HelpOption::with_value(
+                    "--exclude-user",
+                    "<NAME>",
+                    "Hide ports of user (repeatable).",
+                ),
+                HelpOption::with_value(
+                    "--exclude-command",
+                    "<PATTERN>",
+                    "Hide commands containing PATTERN (repeatable).",
+                ),
+                HelpOption::with_value(
+                    "--protocol",
+                    "<PROTO>",
+                    "Only list tcp(4|6) or udp(4|6) (repeatable).",
+                )
+                .short("-p"),
+                HelpOption::with_value(
+                    "--min-cpu",
+                    "<PCT>",
+                    "Only list processes using at least PCT% CPU.",
+                ),
+                HelpOption::with_value(
+                    "--max-cpu",
+                    "<PCT>",
+                    "Only list processes using at most PCT% CPU.",
+                ),
+                HelpOption::with_value(
+                    "--min-mem",
+                    "<PCT>",
+                    "Only list processes using at least PCT% memory.",
+                ),
+                HelpOption::with_value(
+                    "--max-mem",
+                    "<PCT>",
+                    "Only list processes using at most PCT% memory.",
+                ),
+                HelpOption::with_value(
+                    "--since",
+                    "<DURATION>",
+                    "Only list processes started within DURATION (e.g., 1h).",
+                ),
+                HelpOption::new(None, "--ipv4", "Only list IPv4 sockets."),
+                HelpOption::new(None, "--ipv6", "Only list IPv6 sockets."),
+                HelpOption::new(
+                    None,
+                    "--loopback-only",
+                    "Only list ports bound to loopback.",
+                ),
+                HelpOption::new(
+                    None,
+                    "--wildcard-only",
+                    "Only list ports bound to all interfaces.",
+                ),
+                HelpOption::new(None, "--warn-no-results", "Say so if no ports are found."),
+                HelpOption::new(None, "--strict-parse", "Fail on malformed lsof output."),
+                HelpOption::with_value(
+                    "--lsof-file",
+                    "<PATH>",
+                    "Read `lsof -i -n -P` output from PATH.",
+                ),
+                HelpOption::with_value("--ps-file", "<PATH>", "Read `ps aux` output from PATH."),
+                HelpOption::new(None, "--stdin", "Read lsof output from stdin."),
+                HelpOption::new(
+                    None,
+                    "--show-ruser",
+                    "Show the real user, USER is the effective one (with -vv, -vvv).",
+                ),
+                HelpOption::with_value(
+                    "--column-separator",
+                    "<STR>",
+                    "Separate table columns with STR.",
+                ),
+                HelpOption::new(None, "--no-align", "Do not pad table columns."),
+                HelpOption::new(
+                    None,
+                    "--tab-separated",
+                    "Separate columns with tabs, without padding (TSV).",
+                ),
+                HelpOption::new(None, "--no-header", "Do not print the table header."),
+                HelpOption::with_value(
+                    "--color",
+                    "<WHEN>",
+                    "Color the table: always, auto (default) or never.",
+                ),
+                HelpOption::with_value(
+                    "--highlight",
+                    "<PORT>",
+                    "Highlight rows of port or range, in color (repeatable).",
+                ),
+                HelpOption::with_value(
+                    "--columns",
+                    "<COLS>",
+                    "Show these columns (e.g., `pid,name,cpu`).",
+                ),
+                HelpOption::new(
+                    None,
+                    "--numeric-port",
+                    "Show the port number in its own column.",
+                ),
+                HelpOption::new(
+                    None,
+                    "--aggregate-ports",
+                    "One row per process, with all its ports.",
+                ),
+                HelpOption::with_value(
+                    "--port-alias",
+                    "<FILE>",
+                    "Show aliases of ports (`8080 = \"dev-api\"`).",
+                ),
+                HelpOption::new(None, "--service-names", "Show ports by name (`*:http`)."),
+                HelpOption::new(
+                    None,
+                    "--resolve-hostnames",
+                    "Resolve IPs to hostnames (slow, one DNS lookup each).",
+                ),
+                HelpOption::new(None, "--no-dns", "Do not resolve hostnames (default)."),
+                HelpOption::new(
+                    None,
+                    "--resolve-service-names",
+                    "Let lsof resolve ports to service names.",
+                ),
+                HelpOption::with_value("--output-file", "<PATH>", "Write output to PATH."),
+                HelpOption::new(None, "--print0-pids", "Print unique PIDs, NUL-separated."),
+                HelpOption::new(None, "--pid-only", "Only print unique PIDs, one per line."),
+                HelpOption::new(
+                    None,
+                    "--kill",
+                    "Send SIGTERM to the processes (asks first).",
+                ),
+                HelpOption::new(None, "--force", "With --kill, do not ask for confirmation."),
+                HelpOption::new(None, "--count", "Only print the number of ports."),
+                HelpOption::new(None, "--quiet", "Only print HOST:PORT, one per line."),
+                HelpOption::new(None, "--summary", "Print the number of ports per user."),
+                HelpOption::new(
+                    None,
+                    "--summary-by-command",
+                    "Print the number of ports per command.",
+                ),
+                HelpOption::with_value(
+                    "--sort",
+                    "<KEY>",
+                    "Sort by port, pid, command, user, cpu or mem.",
+                ),
+                HelpOption::new(None, "--reverse", "Reverse the order of the ports."),
+                HelpOption::with_value("--group-by", "<KEY>", "Group rows by user or command."),
+                HelpOption::with_value("--top", "<N>", "Only show the first N ports."),
+                HelpOption::new(
+                    None,
+                    "--watch[=SECS]",
+                    "Refresh every SECS seconds (default 2).",
+                ),
+                HelpOption::new(None, "--diff", "With --watch, only print changes."),
+                HelpOption::new(None, "--null", "NUL-terminate --format template lines."),
+                HelpOption::with_value(
+                    "--format",
+                    "<FORMAT>",
+                    "Output in another format (see below).",
+                ),
+                HelpOption::with_value(
+                    "--graphql-field-name",
+                    "<NAME>",
+                    "Field holding the ports in GraphQL output.",
+                ),
+                HelpOption::new(
+                    None,
+                    "--print-args",
+                    "Print the arguments of these options, and exit.",
+                ),
+                HelpOption::with_value(
+                    "--help-format",
+                    "<FMT>",
+                    "Show this message as txt, man, markdown or json.",
+                ),
+            ],
+            formats: vec![
+                HelpValue::new("ansible-vars", "Ansible variables (YAML)."),
+                HelpValue::new(
+                    "env",
+                    &format!("Shell variables (`eval $({bin} --format env)`)."),
+                ),
+                HelpValue::new("graphql", "GraphQL response (JSON)."),
+                HelpValue::new("k8s-labels", "Kubernetes label selectors, one per process."),
+                HelpValue::new("nginx-upstream", "nginx upstream blocks, one per process."),
+                HelpValue::new("openmetrics", "OpenMetrics text exposition."),
+                HelpValue::new("rust-debug", "Rust debug representation of each port."),
+                HelpValue::new("rust-pretty", "Same, pretty-printed."),
+                HelpValue::new(
+                    "systemd-socket",
+                    "Draft systemd socket units, one per process.",
+                ),
+                HelpValue::new(
+                    "'{pid} {name}'",
+                    "One line per port (also `{user}`, `{pinfo.command}`, etc.).",
+                ),
+            ],
+        };
+
+        if cfg!(feature = "urlencoding") {
+            let at = help
+                .options
+                .iter()
+                .position(|option| option.name == "--print-args")
+                .map_or(help.options.len(), |i| i + 1);
+            help.options.splice(
+                at..at,
+                [
+                    HelpOption::new(
+                        None,
+                        "--print-query",
+                        "Print these options as a URL query string, and exit.",
+                    ),
+                    HelpOption::with_value(
+                        "--from-query",
+                        "<QUERY>",
+                        "Read all options from a URL query string.",
+                    ),
+                ],
+            );
+        }
+
+        help
+    }
+}
+
+impl Default for Help {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+fn sample() -> Help {
+    Help {
+        bin: String::from("ports"),
+        description: String::from("List listening ports."),
+        usage: String::from("[OPTIONS]"),
+        filters: vec![
+            String::from("Filter on ports."),
+            String::from("For example `ports 80`."),
+        ],
+        options: vec![
+            HelpOption::new(Some("-h"), "--help", "Show this message and exit."),
+            HelpOption::with_value("--include-pids-file", "<PATH>", "Only list PIDs in file."),
+        ],
+        formats: vec![HelpValue::new("env", "Shell variables.")],
+    }
+}
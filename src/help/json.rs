@@ -0,0 +1,89 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use ports::formatters::json::quote;
+
+use super::{Help, HelpRenderer};
+
+/// JSON, for tooling.
+///
+/// ```json
+/// {"description":"...","usage":"...","options":[{"name":"--help","short":"-h","value":null,"description":"..."}],"formats":[...]}
+/// ```
+pub struct JsonRenderer;
+
+impl HelpRenderer for JsonRenderer {
+    fn render(&self, help: &Help) -> String {
+        let options: Vec<String> = help
+            .options
+            .iter()
+            .map(|option| {
+                format!(
+                    "{{\"name\":{},\"short\":{},\"value\":{},\"description\":{}}}",
+                    quote(option.name),
+                    option.short.map_or_else(|| String::from("null"), quote),
+                    option.value.map_or_else(|| String::from("null"), quote),
+                    quote(&option.description),
+                )
+            })
+            .collect();
+
+        let formats: Vec<String> = help
+            .formats
+            .iter()
+            .map(|format| {
+                format!(
+                    "{{\"name\":{},\"description\":{}}}",
+                    quote(format.name),
+                    quote(&format.description),
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\"name\":{},\"description\":{},\"usage\":{},\"filters\":{},\"options\":[{}],\"formats\":[{}]}}\n",
+            quote(&help.bin),
+            quote(&help.description),
+            quote(&format!("{} {}", help.bin, help.usage)),
+            quote(&help.filters.join(" ")),
+            options.join(","),
+            formats.join(","),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_regular() {
+        let output = JsonRenderer.render(&super::super::sample());
+
+        assert_eq!(
+            output,
+            concat!(
+                r#"{"name":"ports","description":"List listening ports.","usage":"ports [OPTIONS]","#,
+                r#""filters":"Filter on ports. For example `ports 80`.","#,
+                r#""options":["#,
+                r#"{"name":"--help","short":"-h","value":null,"description":"Show this message and exit."},"#,
+                r#"{"name":"--include-pids-file","short":null,"value":"<PATH>","description":"Only list PIDs in file."}"#,
+                r#"],"formats":[{"name":"env","description":"Shell variables."}]}"#,
+                "\n"
+            )
+        );
+    }
+}
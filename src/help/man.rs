@@ -0,0 +1,121 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use super::{Help, HelpRenderer};
+
+/// roff, for `man` (`ports --help-format man | man -l -`).
+pub struct ManRenderer;
+
+impl HelpRenderer for ManRenderer {
+    fn render(&self, help: &Help) -> String {
+        let mut output = String::new();
+
+        _ = writeln!(output, ".TH {} 1", escape(&help.bin.to_ascii_uppercase()));
+        output.push_str(".SH NAME\n");
+        _ = writeln!(
+            output,
+            "{} \\- {}",
+            escape(&help.bin),
+            escape(&help.description)
+        );
+        output.push_str(".SH SYNOPSIS\n");
+        _ = writeln!(output, ".B {}", escape(&help.bin));
+        _ = writeln!(output, "{}", escape(&help.usage));
+
+        output.push_str(".SH DESCRIPTION\n");
+        for line in &help.filters {
+            _ = writeln!(output, "{}", escape(line));
+        }
+
+        output.push_str(".SH OPTIONS\n");
+        for option in &help.options {
+            output.push_str(".TP\n");
+            let mut flag = String::from(".B ");
+            if let Some(short) = option.short {
+                _ = write!(flag, "{}, ", escape(short));
+            }
+            flag.push_str(&escape(option.name));
+            if let Some(value) = option.value {
+                _ = write!(flag, " \\fI{}\\fR", escape(value));
+            }
+            _ = writeln!(output, "{flag}");
+            _ = writeln!(output, "{}", escape(&option.description));
+        }
+
+        output.push_str(".SH FORMATS\n");
+        for format in &help.formats {
+            output.push_str(".TP\n");
+            _ = writeln!(output, ".B {}", escape(format.name));
+            _ = writeln!(output, "{}", escape(&format.description));
+        }
+
+        output
+    }
+}
+
+/// Make text safe for roff.
+fn escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with(['.', '\'']) {
+        // Would be read as a request.
+        return format!("\\&{text}");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_regular() {
+        let output = ManRenderer.render(&super::super::sample());
+
+        assert_eq!(
+            output,
+            r".TH PORTS 1
+.SH NAME
+ports \- List listening ports.
+.SH SYNOPSIS
+.B ports
+[OPTIONS]
+.SH DESCRIPTION
+Filter on ports.
+For example `ports 80`.
+.SH OPTIONS
+.TP
+.B \-h, \-\-help
+Show this message and exit.
+.TP
+.B \-\-include\-pids\-file \fI<PATH>\fR
+Only list PIDs in file.
+.SH FORMATS
+.TP
+.B env
+Shell variables.
+"
+        );
+    }
+
+    #[test]
+    fn escape_special_characters() {
+        assert_eq!(escape(r"a-b\c"), r"a\-b\ec");
+        assert_eq!(escape(".start"), r"\&.start");
+        assert_eq!(escape("'start"), r"\&'start");
+    }
+}
@@ -0,0 +1,120 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use super::{Help, HelpRenderer};
+
+/// Markdown, for documentation.
+pub struct MarkdownRenderer;
+
+impl HelpRenderer for MarkdownRenderer {
+    fn render(&self, help: &Help) -> String {
+        let mut output = String::new();
+
+        _ = writeln!(output, "# {}\n", help.bin);
+        _ = writeln!(output, "{}\n", help.description);
+
+        output.push_str("## Usage\n\n");
+        _ = writeln!(output, "```\n{} {}\n```\n", help.bin, help.usage);
+
+        output.push_str("## Filters\n\n");
+        _ = writeln!(output, "{}\n", help.filters.join("\n"));
+
+        output.push_str("## Options\n\n");
+        output.push_str("| Option | Description |\n");
+        output.push_str("| --- | --- |\n");
+        for option in &help.options {
+            let mut flag = String::new();
+            if let Some(short) = option.short {
+                _ = write!(flag, "`{short}`, ");
+            }
+            _ = write!(flag, "`{}", option.name);
+            if let Some(value) = option.value {
+                _ = write!(flag, " {value}");
+            }
+            flag.push('`');
+            _ = writeln!(output, "| {flag} | {} |", escape(&option.description));
+        }
+
+        output.push_str("\n## Formats\n\n");
+        output.push_str("| Format | Description |\n");
+        output.push_str("| --- | --- |\n");
+        for format in &help.formats {
+            _ = writeln!(
+                output,
+                "| `{}` | {} |",
+                format.name,
+                escape(&format.description)
+            );
+        }
+
+        output
+    }
+}
+
+/// Make text safe for a table cell.
+fn escape(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_regular() {
+        let output = MarkdownRenderer.render(&super::super::sample());
+
+        assert_eq!(
+            output,
+            "\
+# ports
+
+List listening ports.
+
+## Usage
+
+```
+ports [OPTIONS]
+```
+
+## Filters
+
+Filter on ports.
+For example `ports 80`.
+
+## Options
+
+| Option | Description |
+| --- | --- |
+| `-h`, `--help` | Show this message and exit. |
+| `--include-pids-file <PATH>` | Only list PIDs in file. |
+
+## Formats
+
+| Format | Description |
+| --- | --- |
+| `env` | Shell variables. |
+"
+        );
+    }
+
+    #[test]
+    fn escape_pipe() {
+        assert_eq!(escape("a | b"), r"a \| b");
+    }
+}
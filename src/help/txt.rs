@@ -0,0 +1,101 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt::Write;
+
+use super::{Help, HelpRenderer};
+
+/// Width of the first column (options, formats).
+const COLUMN_WIDTH: usize = 20;
+
+/// Plain text, for the terminal.
+pub struct TxtRenderer;
+
+impl HelpRenderer for TxtRenderer {
+    fn render(&self, help: &Help) -> String {
+        let mut output = String::new();
+
+        _ = writeln!(output, "{}\n", help.description);
+        _ = writeln!(output, "Usage: {} {}\n", help.bin, help.usage);
+
+        output.push_str("Filters:\n");
+        for line in &help.filters {
+            _ = writeln!(output, "  {line}");
+        }
+
+        output.push_str("\nOptions:\n");
+        for option in &help.options {
+            let mut flag = String::new();
+            if let Some(short) = option.short {
+                _ = write!(flag, "{short}, ");
+            }
+            flag.push_str(option.name);
+            if let Some(value) = option.value {
+                _ = write!(flag, " {value}");
+            }
+            write_row(&mut output, &flag, &option.description);
+        }
+
+        output.push_str("\nFormats:\n");
+        for format in &help.formats {
+            write_row(&mut output, format.name, &format.description);
+        }
+
+        output
+    }
+}
+
+/// Descriptions are aligned, and wrap to the next line if the first
+/// column is too wide.
+fn write_row(output: &mut String, first: &str, description: &str) {
+    if first.len() > COLUMN_WIDTH {
+        _ = writeln!(output, "  {first}");
+        _ = writeln!(output, "  {:COLUMN_WIDTH$}  {description}", "");
+    } else {
+        _ = writeln!(output, "  {first:COLUMN_WIDTH$}  {description}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_regular() {
+        let output = TxtRenderer.render(&super::super::sample());
+
+        assert_eq!(
+            output,
+            "\
+List listening ports.
+
+Usage: ports [OPTIONS]
+
+Filters:
+  Filter on ports.
+  For example `ports 80`.
+
+Options:
+  -h, --help            Show this message and exit.
+  --include-pids-file <PATH>
+                        Only list PIDs in file.
+
+Formats:
+  env                   Shell variables.
+"
+        );
+    }
+}
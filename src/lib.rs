@@ -14,7 +14,25 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod aliases;
 pub mod cmd;
+pub mod formatters;
+pub mod services;
+pub mod time_parse;
 
+pub use cmd::backend;
+pub use cmd::kill;
 pub use cmd::lsof;
+pub use cmd::netstat;
 pub use cmd::ps;
+pub use cmd::ss;
+
+pub use cmd::backend::{Backend, BackendSelector};
+pub use cmd::kill::{kill_processes, KillError};
+pub use cmd::lsof::{
+    Address, Host, IncludeUdp, ListeningPort, ListeningPortBuilder, Lsof, LsofError, LsofErrorKind,
+    ParseError, ResolveHostnames, ResolveServiceNames,
+};
+pub use cmd::netstat::{Netstat, NetstatError};
+pub use cmd::ps::{ProcessInfo, Ps, PsError};
+pub use cmd::ss::{Ss, SsError};
@@ -14,15 +14,41 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+mod colors;
+mod help;
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::env;
 use std::error::Error;
 use std::fmt;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, SystemTime};
 
+use chrono::{DateTime, Local, TimeZone};
 use lessify::OutputPaged;
 use verynicetable::Table;
 
-use ports::lsof::{ListeningPort, Lsof};
-use ports::ps::Ps;
+use colors::{ColorMode, Style};
+use help::{Help, HelpRenderer, JsonRenderer, ManRenderer, MarkdownRenderer, TxtRenderer};
+use ports::aliases::AliasMap;
+use ports::formatters::{
+    AnsibleVarsFormatter, DebugFormatter, EnvFormatter, Formatter, GraphQLFormatter,
+    K8sLabelsFormatter, NginxUpstreamFormatter, OpenMetricsFormatter, PrettyDebugFormatter,
+    SystemdSocketFormatter,
+};
+use ports::kill::kill_processes;
+use ports::lsof::{
+    Address, IncludeUdp, ListeningPort, Lsof, ResolveHostnames, ResolveServiceNames,
+};
+use ports::ps::{ProcessInfo, Ps};
+use ports::services::ServiceNames;
+use ports::time_parse::parse_ps_start_time_relative_to;
+
+const DEFAULT_WATCH_INTERVAL: Duration = Duration::from_secs(2);
 
 #[derive(Debug, Eq, PartialEq, PartialOrd)]
 enum Mode {
@@ -32,38 +58,403 @@ enum Mode {
 }
 
 #[derive(Debug, Eq, PartialEq)]
+enum OutputFormat {
+    AnsibleVars,
+    Env,
+    GraphQl,
+    K8sLabels,
+    NginxUpstream,
+    OpenMetrics,
+    RustDebug,
+    RustPretty,
+    SystemdSocket,
+    Template(Template),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum SortKey {
+    Port,
+    Pid,
+    Command,
+    User,
+    Cpu,
+    Mem,
+}
+
+impl SortKey {
+    /// Value of `--sort`.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Port => "port",
+            Self::Pid => "pid",
+            Self::Command => "command",
+            Self::User => "user",
+            Self::Cpu => "cpu",
+            Self::Mem => "mem",
+        }
+    }
+
+    /// CPU and memory usage come from `ps`.
+    fn needs_process_info(&self) -> bool {
+        matches!(self, Self::Cpu | Self::Mem)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum GroupBy {
+    User,
+    Command,
+}
+
+impl GroupBy {
+    /// Value of `--group-by`.
+    fn name(self) -> &'static str {
+        match self {
+            Self::User => "user",
+            Self::Command => "command",
+        }
+    }
+
+    /// Untruncated process name for commands, if the port is enriched.
+    fn key(self, port: &ListeningPort) -> &str {
+        match self {
+            Self::User => &port.user,
+            Self::Command => port.process_name(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Column {
+    Command,
+    Pid,
+    User,
+    Ruser,
+    Type,
+    Node,
+    Name,
+    Port,
+    Alias,
+    Ppid,
+    Cpu,
+    Mem,
+    Vsz,
+    Rss,
+    Start,
+    Time,
+    FullCommand,
+}
+
+impl Column {
+    const ALL: [Self; 17] = [
+        Self::Command,
+        Self::Pid,
+        Self::User,
+        Self::Ruser,
+        Self::Type,
+        Self::Node,
+        Self::Name,
+        Self::Port,
+        Self::Alias,
+        Self::Ppid,
+        Self::Cpu,
+        Self::Mem,
+        Self::Vsz,
+        Self::Rss,
+        Self::Start,
+        Self::Time,
+        Self::FullCommand,
+    ];
+
+    /// Color of the column's values, with `--color`.
+    fn style(self) -> Option<Style> {
+        match self {
+            Self::Command => Some(Style::Bold),
+            Self::Pid => Some(Style::Yellow),
+            Self::Name => Some(Style::Cyan),
+            _ => None,
+        }
+    }
+
+    /// Column of a `--columns` value.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|column| column.name() == name)
+    }
+
+    /// Value of `--columns`.
+    fn name(self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Pid => "pid",
+            Self::User => "user",
+            Self::Ruser => "ruser",
+            Self::Type => "type",
+            Self::Node => "node",
+            Self::Name => "name",
+            Self::Port => "port",
+            Self::Alias => "alias",
+            Self::Ppid => "ppid",
+            Self::Cpu => "cpu",
+            Self::Mem => "mem",
+            Self::Vsz => "vsz",
+            Self::Rss => "rss",
+            Self::Start => "start",
+            Self::Time => "time",
+            Self::FullCommand => "full-command",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Command | Self::FullCommand => "COMMAND",
+            Self::Pid => "PID",
+            Self::User => "USER",
+            Self::Ruser => "RUSER",
+            Self::Type => "TYPE",
+            Self::Node => "NODE",
+            Self::Name => "HOST:PORT",
+            Self::Port => "PORT",
+            Self::Alias => "ALIAS",
+            Self::Ppid => "PPID",
+            Self::Cpu => "%CPU",
+            Self::Mem => "%MEM",
+            Self::Vsz => "VSZ",
+            Self::Rss => "RSS",
+            Self::Start => "START",
+            Self::Time => "TIME",
+        }
+    }
+
+    fn alignment(self) -> fmt::Alignment {
+        match self {
+            Self::Pid
+            | Self::Name
+            | Self::Port
+            | Self::Ppid
+            | Self::Cpu
+            | Self::Mem
+            | Self::Vsz
+            | Self::Rss
+            | Self::Start
+            | Self::Time => fmt::Alignment::Right,
+            Self::Command
+            | Self::User
+            | Self::Ruser
+            | Self::Type
+            | Self::Node
+            | Self::Alias
+            | Self::FullCommand => fmt::Alignment::Left,
+        }
+    }
+
+    /// Everything but `lsof`'s own columns comes from `ps`.
+    fn needs_process_info(self) -> bool {
+        matches!(
+            self,
+            Self::Ruser
+                | Self::Ppid
+                | Self::Cpu
+                | Self::Mem
+                | Self::Vsz
+                | Self::Rss
+                | Self::Start
+                | Self::Time
+                | Self::FullCommand
+        )
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+enum HelpFormat {
+    Txt,
+    Man,
+    Markdown,
+    Json,
+}
+
+#[allow(clippy::struct_excessive_bools)] // CLI flags.
+#[derive(Debug, PartialEq)]
 struct Config {
     help: bool,
+    help_format: HelpFormat,
     version: bool,
+    print_args: bool,
+    #[cfg(feature = "urlencoding")]
+    print_query: bool,
     mode: Mode,
     filters: Vec<String>,
+    pid_filters: Vec<String>,
+    pids_file: Option<PathBuf>,
+    format: Option<OutputFormat>,
+    graphql_field_name: Option<String>,
+    include_udp: bool,
+    show_ruser: bool,
+    column_separator: Option<String>,
+    no_align: bool,
+    warn_no_results: bool,
+    strict_parse: bool,
+    aggregate_ports: bool,
+    port_alias: Option<String>,
+    print0_pids: bool,
+    sort_key: Option<SortKey>,
+    reverse: bool,
+    user_filters: Vec<String>,
+    protocol_filters: Vec<String>,
+    ipv4: bool,
+    ipv6: bool,
+    excluded_ports: Vec<String>,
+    service_names: bool,
+    watch: Option<Duration>,
+    diff: bool,
+    count: bool,
+    no_header: bool,
+    columns: Vec<Column>,
+    numeric_port: bool,
+    output_file: Option<PathBuf>,
+    top: Option<usize>,
+    excluded_users: Vec<String>,
+    excluded_commands: Vec<String>,
+    loopback_only: bool,
+    wildcard_only: bool,
+    lsof_file: Option<PathBuf>,
+    ps_file: Option<PathBuf>,
+    stdin: bool,
+    color: ColorMode,
+    highlight_ports: Vec<String>,
+    group_by: Option<GroupBy>,
+    summary: bool,
+    summary_by_command: bool,
+    min_cpu: Option<f32>,
+    max_cpu: Option<f32>,
+    min_mem: Option<f32>,
+    max_mem: Option<f32>,
+    since: Option<Duration>,
+    null_terminate: bool,
+    tab_separated: bool,
+    quiet: bool,
+    pid_only: bool,
+    kill: bool,
+    force: bool,
+    resolve_hostnames: bool,
+    resolve_service_names: bool,
+    unix: bool,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             help: false,
+            help_format: HelpFormat::Txt,
             version: false,
+            print_args: false,
+            #[cfg(feature = "urlencoding")]
+            print_query: false,
             mode: Mode::Regular,
             filters: Vec::new(),
+            pid_filters: Vec::new(),
+            pids_file: None,
+            format: None,
+            graphql_field_name: None,
+            include_udp: false,
+            show_ruser: false,
+            column_separator: None,
+            no_align: false,
+            warn_no_results: false,
+            strict_parse: false,
+            aggregate_ports: false,
+            port_alias: None,
+            print0_pids: false,
+            sort_key: None,
+            reverse: false,
+            user_filters: Vec::new(),
+            protocol_filters: Vec::new(),
+            ipv4: false,
+            ipv6: false,
+            excluded_ports: Vec::new(),
+            service_names: false,
+            watch: None,
+            diff: false,
+            count: false,
+            no_header: false,
+            columns: Vec::new(),
+            numeric_port: false,
+            output_file: None,
+            top: None,
+            excluded_users: Vec::new(),
+            excluded_commands: Vec::new(),
+            loopback_only: false,
+            wildcard_only: false,
+            lsof_file: None,
+            ps_file: None,
+            stdin: false,
+            color: ColorMode::Auto,
+            highlight_ports: Vec::new(),
+            group_by: None,
+            summary: false,
+            summary_by_command: false,
+            min_cpu: None,
+            max_cpu: None,
+            min_mem: None,
+            max_mem: None,
+            since: None,
+            null_terminate: false,
+            tab_separated: false,
+            quiet: false,
+            pid_only: false,
+            kill: false,
+            force: false,
+            resolve_hostnames: false,
+            resolve_service_names: false,
+            unix: false,
         }
     }
 }
 
 impl Config {
+    #[allow(clippy::too_many_lines)] // One arm per option.
     fn new(args: impl Iterator<Item = String>) -> Result<Self, String> {
         let mut config = Self::default();
 
-        for arg in args.skip(1) {
+        let mut args = args.skip(1);
+        while let Some(arg) = args.next() {
             match arg.as_str() {
                 "-h" | "--help" => {
                     config.help = true;
                     break;
                 }
+                "--help-format" => {
+                    let format = Self::value_of("--help-format", &mut args)?;
+                    config.help_format = match format.as_str() {
+                        "txt" => HelpFormat::Txt,
+                        "man" => HelpFormat::Man,
+                        "markdown" => HelpFormat::Markdown,
+                        "json" => HelpFormat::Json,
+                        format => return Err(format!("Unknown help format: '{format}'")),
+                    };
+                    config.help = true;
+                    break;
+                }
                 "-v" | "--version" => {
                     config.version = true;
                     break;
                 }
+                "--print-args" => {
+                    config.print_args = true;
+                }
+                #[cfg(feature = "urlencoding")]
+                "--print-query" => {
+                    config.print_query = true;
+                }
+                #[cfg(feature = "urlencoding")]
+                "--from-query" => {
+                    let query = Self::value_of("--from-query", &mut args)?;
+                    if config != Self::default() || args.next().is_some() {
+                        return Err(String::from(
+                            "'--from-query' cannot be combined with other options",
+                        ));
+                    }
+                    return Self::from_url_query_string(&query).map_err(|e| e.to_string());
+                }
                 "-vv" | "--verbose" => {
                     if config.mode >= Mode::Verbose {
                         continue; // Only increase verbosity.
@@ -76,637 +467,6545 @@ impl Config {
                     }
                     config.mode = Mode::VeryVerbose;
                 }
-                arg if arg.parse::<u16>().is_ok() => {
-                    // 0-65535
-                    config.filters.push(String::from(arg));
-                }
-                // TODO[refactor]: Once 'if let guard' feature drops.
-                //   arg if let Some((Some(start), Some(end))) =
-                //       arg.split_once('-').and_then(|range| {
-                //           Some((range.0.parse::<u16>().ok(), range.1.parse::<u16>().ok()))
-                //       }) =>
-                arg if arg.split_once('-').is_some_and(|range| {
-                    range.0.parse::<u16>().is_ok() && range.1.parse::<u16>().is_ok()
-                }) =>
-                {
-                    // TODO: Unnecessary once previous TODO gets resolved.
-                    let range = arg
-                        .split_once('-')
-                        .map(|x| (x.0.parse::<u16>().unwrap(), x.1.parse::<u16>().unwrap()))
-                        .unwrap();
-
-                    let range_start = std::cmp::min(range.0, range.1);
-                    let range_end = std::cmp::max(range.0, range.1);
-
-                    // The bigger the range, the more we allocate...
-                    // But it doesn't look like a bottleneck on a human
-                    // time scale. If it ever gets to be a problem,
-                    // we'll need to handle ranges differently.
-                    let ports: Vec<String> = (range_start..=range_end)
-                        .map(|port| port.to_string())
-                        .collect();
-
-                    config.filters.extend(ports);
+                "--include-udp" | "--udp" => {
+                    config.include_udp = true;
+                }
+                "--include-pids-file" => {
+                    let path = Self::value_of("--include-pids-file", &mut args)?;
+                    config.pids_file = Some(PathBuf::from(path));
+                }
+                "--pid" => {
+                    let pid = Self::value_of("--pid", &mut args)?;
+                    let Ok(pid) = pid.parse::<u32>() else {
+                        return Err(format!("Invalid PID: '{pid}'"));
+                    };
+                    config.pid_filters.push(pid.to_string());
+                }
+                "--user" => {
+                    let user = Self::value_of("--user", &mut args)?;
+                    let user = user.trim().to_lowercase();
+                    if user.is_empty() {
+                        return Err(String::from("Empty user name"));
+                    }
+                    config.user_filters.push(user);
+                }
+                "--exclude-user" => {
+                    let user = Self::value_of("--exclude-user", &mut args)?;
+                    let user = user.trim().to_lowercase();
+                    if user.is_empty() {
+                        return Err(String::from("Empty user name"));
+                    }
+                    config.excluded_users.push(user);
+                }
+                "--exclude-command" => {
+                    let pattern = Self::value_of("--exclude-command", &mut args)?;
+                    let pattern = pattern.to_lowercase();
+                    if pattern.is_empty() {
+                        return Err(String::from("Empty command pattern"));
+                    }
+                    config.excluded_commands.push(pattern);
+                }
+                "--exclude-port" => {
+                    let port = Self::value_of("--exclude-port", &mut args)?;
+                    let Some(ports) = Self::expand_ports(&port) else {
+                        return Err(format!("Invalid port: '{port}'"));
+                    };
+                    config.excluded_ports.extend(ports);
+                }
+                "-p" | "--protocol" => {
+                    let protocol = Self::value_of("--protocol", &mut args)?;
+                    let protocol = protocol.trim().to_lowercase();
+                    if !matches!(
+                        protocol.as_str(),
+                        "tcp" | "udp" | "tcp4" | "udp4" | "tcp6" | "udp6"
+                    ) {
+                        return Err(format!("Unknown protocol: '{protocol}'"));
+                    }
+                    if protocol.starts_with("udp") {
+                        // Would always come out empty otherwise.
+                        config.include_udp = true;
+                    }
+                    config.protocol_filters.push(protocol);
+                }
+                "--warn-no-results" => {
+                    config.warn_no_results = true;
+                }
+                "--strict-parse" => {
+                    config.strict_parse = true;
+                }
+                "--show-ruser" => {
+                    config.show_ruser = true;
+                }
+                "--aggregate-ports" => {
+                    config.aggregate_ports = true;
+                }
+                "--port-alias" => {
+                    let path = Self::value_of("--port-alias", &mut args)?;
+                    config.port_alias = Some(path);
+                }
+                "--lsof-file" => {
+                    let path = Self::value_of("--lsof-file", &mut args)?;
+                    config.lsof_file = Some(PathBuf::from(path));
+                }
+                "--ps-file" => {
+                    let path = Self::value_of("--ps-file", &mut args)?;
+                    config.ps_file = Some(PathBuf::from(path));
+                }
+                "--output-file" => {
+                    let path = Self::value_of("--output-file", &mut args)?;
+                    config.output_file = Some(PathBuf::from(path));
+                }
+                "--print0-pids" => {
+                    config.print0_pids = true;
+                }
+                "--sort" => {
+                    let key = Self::value_of("--sort", &mut args)?;
+                    let key = match key.as_str() {
+                        "port" => SortKey::Port,
+                        "pid" => SortKey::Pid,
+                        "command" => SortKey::Command,
+                        "user" => SortKey::User,
+                        "cpu" => SortKey::Cpu,
+                        "mem" => SortKey::Mem,
+                        key => return Err(format!("Unknown sort key: '{key}'")),
+                    };
+                    if key.needs_process_info() && config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                    config.sort_key = Some(key);
+                }
+                "--group-by" => {
+                    let group_by = Self::value_of("--group-by", &mut args)?;
+                    config.group_by = Some(match group_by.as_str() {
+                        "user" => GroupBy::User,
+                        "command" => GroupBy::Command,
+                        group_by => return Err(format!("Unknown group: '{group_by}'")),
+                    });
+                }
+                "--reverse" => {
+                    config.reverse = true;
+                }
+                "--columns" => {
+                    let names = Self::value_of("--columns", &mut args)?;
+                    let mut columns = Vec::new();
+                    for name in names.split(',') {
+                        let Some(column) = Column::from_name(name.trim()) else {
+                            return Err(format!("Unknown column: '{}'", name.trim()));
+                        };
+                        columns.push(column);
+                    }
+                    if columns.iter().any(|column| column.needs_process_info())
+                        && config.mode < Mode::Verbose
+                    {
+                        config.mode = Mode::Verbose;
+                    }
+                    config.columns = columns;
+                }
+                "--ipv4" => {
+                    config.ipv4 = true;
+                }
+                "--ipv6" => {
+                    config.ipv6 = true;
+                }
+                "--service-names" => {
+                    config.service_names = true;
+                }
+                "--diff" => {
+                    config.diff = true;
+                }
+                "--count" => {
+                    config.count = true;
+                }
+                "--no-header" => {
+                    config.no_header = true;
+                }
+                "--numeric-port" => {
+                    config.numeric_port = true;
+                }
+                "--loopback-only" => {
+                    config.loopback_only = true;
+                }
+                "--wildcard-only" => {
+                    config.wildcard_only = true;
+                }
+                "--stdin" => {
+                    config.stdin = true;
+                }
+                "--summary" => {
+                    config.summary = true;
+                }
+                "--summary-by-command" => {
+                    config.summary_by_command = true;
+                }
+                "--null" => {
+                    config.null_terminate = true;
+                }
+                "--tab-separated" => {
+                    config.tab_separated = true;
+                }
+                "--quiet" => {
+                    config.quiet = true;
+                }
+                "--pid-only" => {
+                    config.pid_only = true;
+                }
+                "--kill" => {
+                    config.kill = true;
+                }
+                "--force" => {
+                    config.force = true;
+                }
+                "--resolve-hostnames" => {
+                    config.resolve_hostnames = true;
+                }
+                "--no-dns" => {
+                    // The default, but it can override an alias.
+                    config.resolve_hostnames = false;
+                }
+                "--resolve-service-names" => {
+                    config.resolve_service_names = true;
+                }
+                "--unix" => {
+                    config.unix = true;
+                }
+                "--format" => {
+                    let format = Self::value_of("--format", &mut args)?;
+                    let format = match format.as_str() {
+                        "ansible-vars" => OutputFormat::AnsibleVars,
+                        "env" => OutputFormat::Env,
+                        "graphql" => OutputFormat::GraphQl,
+                        "k8s-labels" => OutputFormat::K8sLabels,
+                        "nginx-upstream" => OutputFormat::NginxUpstream,
+                        "openmetrics" => OutputFormat::OpenMetrics,
+                        "rust-debug" => OutputFormat::RustDebug,
+                        "rust-pretty" => OutputFormat::RustPretty,
+                        "systemd-socket" => OutputFormat::SystemdSocket,
+                        format if format.contains('{') => {
+                            OutputFormat::Template(Template::parse(format)?)
+                        }
+                        format => return Err(format!("Unknown format: '{format}'")),
+                    };
+                    if let OutputFormat::Template(template) = &format {
+                        if template.needs_process_info() && config.mode < Mode::Verbose {
+                            config.mode = Mode::Verbose;
+                        }
+                    }
+                    config.format = Some(format);
+                }
+                "--column-separator" => {
+                    let separator = Self::value_of("--column-separator", &mut args)?;
+                    config.column_separator = Some(separator);
+                }
+                "--no-align" => {
+                    config.no_align = true;
+                }
+                "--color" => {
+                    let mode = Self::value_of("--color", &mut args)?;
+                    let Some(mode) = ColorMode::from_name(&mode) else {
+                        return Err(format!("Unknown color mode: '{mode}'"));
+                    };
+                    config.color = mode;
+                }
+                "--highlight" => {
+                    let port = Self::value_of("--highlight", &mut args)?;
+                    let Some(ports) = Self::expand_ports(&port) else {
+                        return Err(format!("Invalid port: '{port}'"));
+                    };
+                    config.highlight_ports.extend(ports);
+                }
+                "--graphql-field-name" => {
+                    let name = Self::value_of("--graphql-field-name", &mut args)?;
+                    config.graphql_field_name = Some(name);
+                }
+                "--min-cpu" => {
+                    let threshold = Self::value_of("--min-cpu", &mut args)?;
+                    config.min_cpu = Some(parse_usage_threshold(&threshold, "CPU")?);
+                    if config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                }
+                "--max-cpu" => {
+                    let threshold = Self::value_of("--max-cpu", &mut args)?;
+                    config.max_cpu = Some(parse_usage_threshold(&threshold, "CPU")?);
+                    if config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                }
+                "--min-mem" => {
+                    let threshold = Self::value_of("--min-mem", &mut args)?;
+                    config.min_mem = Some(parse_usage_threshold(&threshold, "memory")?);
+                    if config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                }
+                "--max-mem" => {
+                    let threshold = Self::value_of("--max-mem", &mut args)?;
+                    config.max_mem = Some(parse_usage_threshold(&threshold, "memory")?);
+                    if config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                }
+                "--since" => {
+                    let since = Self::value_of("--since", &mut args)?;
+                    config.since = Some(parse_since(&since)?);
+                    if config.mode < Mode::Verbose {
+                        config.mode = Mode::Verbose;
+                    }
+                }
+                "--top" => {
+                    let top = Self::value_of("--top", &mut args)?;
+                    config.top = Some(parse_top(&top)?);
+                }
+                "--watch" => {
+                    config.watch = Some(DEFAULT_WATCH_INTERVAL);
+                }
+                arg if arg.starts_with("--watch=") => {
+                    let seconds = &arg["--watch=".len()..];
+                    config.watch = Some(parse_watch_interval(seconds)?);
                 }
                 arg => {
-                    return Err(format!("Unknown argument: '{arg}'"));
+                    let Some(ports) = Self::expand_ports(arg) else {
+                        return Err(format!("Unknown argument: '{arg}'"));
+                    };
+                    config.filters.extend(ports);
+                }
+            }
+        }
+
+        if config.diff && config.watch.is_none() {
+            return Err(String::from("'--diff' requires '--watch'"));
+        }
+        if config.null_terminate && !matches!(config.format, Some(OutputFormat::Template(_))) {
+            // Other formats are documents, not one record per port.
+            return Err(String::from("'--null' requires a '--format' template"));
+        }
+        if config.service_names && config.port_alias.is_some() {
+            // Aliases are looked up by port number.
+            return Err(String::from(
+                "'--service-names' and '--port-alias' cannot be combined",
+            ));
+        }
+        if config.resolve_service_names && config.port_alias.is_some() {
+            return Err(String::from(
+                "'--resolve-service-names' and '--port-alias' cannot be combined",
+            ));
+        }
+        if config.stdin && config.lsof_file.is_some() {
+            return Err(String::from(
+                "'--stdin' and '--lsof-file' cannot be combined",
+            ));
+        }
+        if config.stdin && config.watch.is_some() {
+            // Stdin can only be read once.
+            return Err(String::from("'--stdin' and '--watch' cannot be combined"));
+        }
+        for (usage, min, max) in [
+            ("cpu", config.min_cpu, config.max_cpu),
+            ("mem", config.min_mem, config.max_mem),
+        ] {
+            if let (Some(min), Some(max)) = (min, max) {
+                if min > max {
+                    return Err(format!(
+                        "'--min-{usage}' ({min}) is greater than '--max-{usage}' ({max})"
+                    ));
                 }
             }
         }
+        if config.summary && config.summary_by_command {
+            return Err(String::from(
+                "'--summary' and '--summary-by-command' cannot be combined",
+            ));
+        }
+        if config.force && !config.kill {
+            return Err(String::from("'--force' requires '--kill'"));
+        }
+        if config.kill {
+            if config.watch.is_some() {
+                return Err(String::from("'--kill' and '--watch' cannot be combined"));
+            }
+            // The PIDs may not be the ones of this machine.
+            if config.stdin {
+                return Err(String::from("'--kill' and '--stdin' cannot be combined"));
+            }
+            if config.lsof_file.is_some() {
+                return Err(String::from(
+                    "'--kill' and '--lsof-file' cannot be combined",
+                ));
+            }
+            // Or it would signal every listening process.
+            if config.filters.is_empty()
+                && config.pid_filters.is_empty()
+                && config.pids_file.is_none()
+            {
+                return Err(String::from("'--kill' requires a port or a '--pid' filter"));
+            }
+        }
+        for (option, enabled) in [
+            ("--resolve-hostnames", config.resolve_hostnames),
+            ("--resolve-service-names", config.resolve_service_names),
+        ] {
+            if enabled && (config.stdin || config.lsof_file.is_some()) {
+                // Captured output is already resolved, or not.
+                let input = if config.stdin {
+                    "--stdin"
+                } else {
+                    "--lsof-file"
+                };
+                return Err(format!("'{option}' and '{input}' cannot be combined"));
+            }
+        }
+        if config.unix && (config.stdin || config.lsof_file.is_some()) {
+            // Captures are of `lsof -i`, they have no Unix sockets.
+            let input = if config.stdin {
+                "--stdin"
+            } else {
+                "--lsof-file"
+            };
+            return Err(format!("'--unix' and '{input}' cannot be combined"));
+        }
+        if config.quiet && config.format.is_some() {
+            return Err(String::from("'--quiet' and '--format' cannot be combined"));
+        }
+        if config.tab_separated && config.column_separator.is_some() {
+            return Err(String::from(
+                "'--tab-separated' and '--column-separator' cannot be combined",
+            ));
+        }
+        if config.tab_separated && config.group_by.is_some() {
+            // Group sub-headers would not be tab-separated records.
+            return Err(String::from(
+                "'--tab-separated' and '--group-by' cannot be combined",
+            ));
+        }
+        if config.loopback_only && config.wildcard_only {
+            // A port is never both, nothing would be listed.
+            return Err(String::from(
+                "'--loopback-only' and '--wildcard-only' cannot be combined",
+            ));
+        }
 
         Ok(config)
     }
-}
 
-#[cfg(not(tarpaulin_include))]
-fn main() -> Result<(), Box<dyn Error>> {
-    let config = Config::new(env::args()).unwrap_or_else(|e| {
-        eprintln!("{e}");
-        help();
-        std::process::exit(2);
-    });
+    /// Port (e.g., `8000`) or range (e.g., `8000-8005`) as ports.
+    fn expand_ports(arg: &str) -> Option<Vec<String>> {
+        if arg.parse::<u16>().is_ok() {
+            // 0-65535
+            return Some(vec![String::from(arg)]);
+        }
 
-    if config.help {
-        help();
-        return Ok(());
+        let (start, end) = arg.split_once('-')?;
+        let (start, end) = (start.parse::<u16>().ok()?, end.parse::<u16>().ok()?);
+
+        let range_start = std::cmp::min(start, end);
+        let range_end = std::cmp::max(start, end);
+
+        // The bigger the range, the more we allocate...
+        // But it doesn't look like a bottleneck on a human
+        // time scale. If it ever gets to be a problem,
+        // we'll need to handle ranges differently.
+        let ports: Vec<String> = (range_start..=range_end)
+            .map(|port| port.to_string())
+            .collect();
+
+        Some(ports)
     }
-    if config.version {
-        version();
-        return Ok(());
+
+    /// Consume the value of an option (e.g., `--format json`).
+    fn value_of(option: &str, args: &mut impl Iterator<Item = String>) -> Result<String, String> {
+        args.next()
+            .ok_or_else(|| format!("Missing value for '{option}'"))
     }
+}
 
-    run(&config)
+impl OutputFormat {
+    /// Value of `--format`.
+    fn name(&self) -> &str {
+        match self {
+            Self::AnsibleVars => "ansible-vars",
+            Self::Env => "env",
+            Self::GraphQl => "graphql",
+            Self::K8sLabels => "k8s-labels",
+            Self::NginxUpstream => "nginx-upstream",
+            Self::OpenMetrics => "openmetrics",
+            Self::RustDebug => "rust-debug",
+            Self::RustPretty => "rust-pretty",
+            Self::SystemdSocket => "systemd-socket",
+            Self::Template(template) => &template.source,
+        }
+    }
 }
 
-#[cfg(not(tarpaulin_include))]
-fn help() {
-    print!(
-        "\
-{description}
-
-Usage: {bin} [OPTIONS] [PORT[-RANGE] ...]
-
-Filters:
-  Filter on ports by passing port numbers or port ranges.
-  For example `{bin} 8000 8003` or `{bin} 8000-8005`.
-
-Options:
-  -h, --help            Show this message and exit.
-  -v, --version         Show the version and exit.
-  -vv, --verbose        Additional process info.
-  -vvv, --very-verbose  Even more extra info.
-",
-        description = env!("CARGO_PKG_DESCRIPTION"),
-        bin = env!("CARGO_BIN_NAME"),
-    );
+/// Custom `--format`, like `{pid}: {name}`, rendered once per port.
+///
+/// Placeholders are checked when parsing, so that a typo fails before
+/// anything is printed. `{{` and `}}` are literal braces.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Template {
+    source: String,
+    parts: Vec<TemplatePart>,
 }
 
-#[cfg(not(tarpaulin_include))]
-fn version() {
-    println!("{} {}", env!("CARGO_BIN_NAME"), env!("CARGO_PKG_VERSION"));
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum TemplatePart {
+    Literal(String),
+    Placeholder(Placeholder),
 }
 
-#[cfg(not(tarpaulin_include))]
-fn run(config: &Config) -> Result<(), Box<dyn Error>> {
-    let mut listening_ports = Lsof::listening_ports()?;
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Placeholder {
+    Command,
+    Pid,
+    User,
+    Type,
+    Node,
+    Name,
+    PinfoUser,
+    PinfoPid,
+    PinfoPpid,
+    PinfoPcCpu,
+    PinfoPcMem,
+    PinfoVsz,
+    PinfoRss,
+    PinfoStart,
+    PinfoTime,
+    PinfoCommand,
+}
 
-    if !config.filters.is_empty() {
-        filter_ports(&mut listening_ports, &config.filters);
+impl Placeholder {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "command" => Self::Command,
+            "pid" => Self::Pid,
+            "user" => Self::User,
+            "type" => Self::Type,
+            "node" => Self::Node,
+            "name" => Self::Name,
+            "pinfo.user" => Self::PinfoUser,
+            "pinfo.pid" => Self::PinfoPid,
+            "pinfo.ppid" => Self::PinfoPpid,
+            "pinfo.pc_cpu" => Self::PinfoPcCpu,
+            "pinfo.pc_mem" => Self::PinfoPcMem,
+            "pinfo.vsz" => Self::PinfoVsz,
+            "pinfo.rss" => Self::PinfoRss,
+            "pinfo.start" => Self::PinfoStart,
+            "pinfo.time" => Self::PinfoTime,
+            "pinfo.command" => Self::PinfoCommand,
+            _ => return None,
+        })
     }
 
-    if listening_ports.is_empty() {
-        return Ok(());
+    /// `pinfo.*` placeholders come from `ps`.
+    fn needs_process_info(self) -> bool {
+        matches!(
+            self,
+            Self::PinfoUser
+                | Self::PinfoPid
+                | Self::PinfoPpid
+                | Self::PinfoPcCpu
+                | Self::PinfoPcMem
+                | Self::PinfoVsz
+                | Self::PinfoRss
+                | Self::PinfoStart
+                | Self::PinfoTime
+                | Self::PinfoCommand
+        )
     }
 
-    match config.mode {
-        Mode::Regular => regular(listening_ports),
-        Mode::Verbose => verbose(listening_ports),
-        Mode::VeryVerbose => very_verbose(listening_ports),
+    /// Empty if the port has no process info.
+    fn value(self, port: &ListeningPort) -> &str {
+        let pinfo = || port.pinfo.as_ref();
+        match self {
+            Self::Command => &port.command,
+            Self::Pid => &port.pid,
+            Self::User => &port.user,
+            Self::Type => &port.type_,
+            Self::Node => &port.node,
+            Self::Name => &port.name,
+            Self::PinfoUser => pinfo().map_or("", |x| &x.user),
+            Self::PinfoPid => pinfo().map_or("", |x| &x.pid),
+            Self::PinfoPpid => pinfo().map_or("", |x| &x.ppid),
+            Self::PinfoPcCpu => pinfo().map_or("", |x| &x.pc_cpu),
+            Self::PinfoPcMem => pinfo().map_or("", |x| &x.pc_mem),
+            Self::PinfoVsz => pinfo().map_or("", |x| &x.vsz),
+            Self::PinfoRss => pinfo().map_or("", |x| &x.rss),
+            Self::PinfoStart => pinfo().map_or("", |x| &x.start),
+            Self::PinfoTime => pinfo().map_or("", |x| &x.time),
+            Self::PinfoCommand => pinfo().map_or("", |x| &x.command),
+        }
     }
 }
 
-fn filter_ports(listening_ports: &mut Vec<ListeningPort>, allowed: &[String]) {
-    listening_ports.retain(|x| {
-        let mut listening_on = x.name.as_str(); // '*:1337'
-        if let Some((_, port)) = listening_on.rsplit_once(':') {
-            listening_on = port;
-        };
-        allowed.contains(&listening_on.to_string())
-    });
-}
+impl Template {
+    fn parse(source: &str) -> Result<Self, String> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = source.chars();
+        while let Some(c) = chars.next() {
+            let rest = chars.as_str();
+            match c {
+                '{' | '}' if rest.starts_with(c) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                '{' => {
+                    let Some(end) = rest.find('}') else {
+                        return Err(format!("Unclosed '{{' in format: '{source}'"));
+                    };
+                    let name = &rest[..end];
+                    let Some(placeholder) = Placeholder::from_name(name) else {
+                        return Err(format!("Unknown placeholder: '{{{name}}}'"));
+                    };
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Placeholder(placeholder));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => return Err(format!("Unmatched '}}' in format: '{source}'")),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self {
+            source: String::from(source),
+            parts,
+        })
+    }
 
-// Yes, bad, I know. But I want the same signature for all modes.
-#[allow(clippy::needless_pass_by_value, clippy::unnecessary_wraps)]
-#[cfg(not(tarpaulin_include))]
-fn regular(listening_ports: Vec<ListeningPort>) -> Result<(), Box<dyn Error>> {
-    let listening_ports: Vec<Vec<&String>> = listening_ports
-        .iter()
-        .map(|port| {
-            vec![
-                &port.command,
-                &port.pid,
-                &port.user,
-                &port.type_,
-                &port.node,
-                &port.name,
-            ]
+    fn needs_process_info(&self) -> bool {
+        self.parts.iter().any(|part| match part {
+            TemplatePart::Placeholder(placeholder) => placeholder.needs_process_info(),
+            TemplatePart::Literal(_) => false,
         })
-        .collect();
+    }
 
-    Table::new()
-        .headers(&["COMMAND", "PID", "USER", "TYPE", "NODE", "HOST:PORT"])
-        .alignments(&[
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-        ])
-        .data(&listening_ports)
-        .output_paged();
+    fn render(&self, port: &ListeningPort) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Literal(literal) => literal,
+                TemplatePart::Placeholder(placeholder) => placeholder.value(port),
+            })
+            .collect()
+    }
+}
 
-    Ok(())
+/// Renders a [`Template`] per port, each followed by `terminator`.
+struct TemplateFormatter {
+    template: Template,
+    /// `\n`, or `\0` with `--null`.
+    terminator: char,
 }
 
-#[cfg(not(tarpaulin_include))]
-fn verbose(mut listening_ports: Vec<ListeningPort>) -> Result<(), Box<dyn Error>> {
-    // Enable more info through `ps aux`.
-    let pids: Vec<&String> = listening_ports.iter().map(|port| &port.pid).collect();
-    let processes_info = Ps::processes_info(&pids)?;
+impl Formatter for TemplateFormatter {
+    fn format(&self, listening_ports: &[ListeningPort]) -> String {
+        let mut output = String::new();
+        for port in listening_ports {
+            output.push_str(&self.template.render(port));
+            output.push(self.terminator);
+        }
+        output
+    }
+}
 
-    for port in &mut listening_ports {
-        port.enrich_with_process_info(&processes_info);
+impl Config {
+    /// Arguments that recreate this configuration.
+    ///
+    /// For example, `["--verbose", "80", "443"]`. Only options that
+    /// differ from the default are included, and port filters come
+    /// last.
+    fn serialize_to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        for (option, value) in self.non_default_options() {
+            match (option, value) {
+                // Optional value, or it'd be read as a port filter.
+                ("watch", Some(value)) => args.push(format!("--watch={value}")),
+                (option, value) => {
+                    args.push(format!("--{option}"));
+                    args.extend(value);
+                }
+            }
+        }
+        args.extend(self.filters.iter().cloned());
+        args
     }
 
-    let empty = String::new();
-    let listening_ports: Vec<Vec<&String>> = listening_ports
-        .iter()
-        .map(|port| {
-            vec![
-                &port.command,
-                &port.pid,
-                &port.user,
-                &port.type_,
-                &port.node,
-                &port.name,
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.command),
-            ]
-        })
-        .collect();
+    /// Options that differ from the default, with their value if any.
+    #[allow(clippy::too_many_lines)] // One entry per option.
+    fn non_default_options(&self) -> Vec<(&'static str, Option<String>)> {
+        // `--kill` and `--force` are left out on purpose: whoever runs
+        // the recreated configuration must opt in to sending signals
+        // themselves, rather than inherit it.
+        let mut options = Vec::new();
 
-    Table::new()
-        .headers(&[
-            "COMMAND",
-            "PID",
-            "USER",
-            "TYPE",
-            "NODE",
-            "HOST:PORT",
-            "COMMAND",
-        ])
-        .alignments(&[
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-            fmt::Alignment::Left,
-        ])
-        .data(&listening_ports)
-        .output_paged();
+        match self.mode {
+            Mode::Regular => (),
+            Mode::Verbose => options.push(("verbose", None)),
+            Mode::VeryVerbose => options.push(("very-verbose", None)),
+        }
+        if let Some(key) = &self.sort_key {
+            options.push(("sort", Some(String::from(key.name()))));
+        }
+        if let Some(group_by) = self.group_by {
+            options.push(("group-by", Some(String::from(group_by.name()))));
+        }
+        if !self.columns.is_empty() {
+            let names: Vec<&str> = self.columns.iter().map(|column| column.name()).collect();
+            options.push(("columns", Some(names.join(","))));
+        }
+        if let Some(format) = &self.format {
+            options.push(("format", Some(String::from(format.name()))));
+        }
+        if self.color != ColorMode::Auto {
+            options.push(("color", Some(String::from(self.color.name()))));
+        }
+        for port in &self.highlight_ports {
+            options.push(("highlight", Some(port.clone())));
+        }
+        if let Some(top) = self.top {
+            options.push(("top", Some(top.to_string())));
+        }
+        for (option, threshold) in [
+            ("min-cpu", self.min_cpu),
+            ("max-cpu", self.max_cpu),
+            ("min-mem", self.min_mem),
+            ("max-mem", self.max_mem),
+        ] {
+            if let Some(threshold) = threshold {
+                options.push((option, Some(threshold.to_string())));
+            }
+        }
+        if let Some(since) = self.since {
+            options.push(("since", Some(format!("{}s", since.as_secs()))));
+        }
+        if let Some(interval) = self.watch {
+            options.push(("watch", Some(interval.as_secs().to_string())));
+        }
+        for (option, values) in [
+            ("pid", &self.pid_filters),
+            ("user", &self.user_filters),
+            ("exclude-user", &self.excluded_users),
+            ("exclude-command", &self.excluded_commands),
+            ("protocol", &self.protocol_filters),
+            ("exclude-port", &self.excluded_ports),
+        ] {
+            for value in values {
+                options.push((option, Some(value.clone())));
+            }
+        }
+        for (option, value) in [
+            ("graphql-field-name", &self.graphql_field_name),
+            ("column-separator", &self.column_separator),
+            ("port-alias", &self.port_alias),
+        ] {
+            if let Some(value) = value {
+                options.push((option, Some(value.clone())));
+            }
+        }
+        for (option, path) in [
+            ("include-pids-file", &self.pids_file),
+            ("lsof-file", &self.lsof_file),
+            ("ps-file", &self.ps_file),
+            ("output-file", &self.output_file),
+        ] {
+            if let Some(path) = path {
+                options.push((option, Some(path.to_string_lossy().into_owned())));
+            }
+        }
+        for (option, enabled) in [
+            ("include-udp", self.include_udp),
+            ("show-ruser", self.show_ruser),
+            ("no-align", self.no_align),
+            ("warn-no-results", self.warn_no_results),
+            ("strict-parse", self.strict_parse),
+            ("aggregate-ports", self.aggregate_ports),
+            ("print0-pids", self.print0_pids),
+            ("reverse", self.reverse),
+            ("ipv4", self.ipv4),
+            ("ipv6", self.ipv6),
+            ("service-names", self.service_names),
+            ("diff", self.diff),
+            ("count", self.count),
+            ("no-header", self.no_header),
+            ("numeric-port", self.numeric_port),
+            ("loopback-only", self.loopback_only),
+            ("wildcard-only", self.wildcard_only),
+            ("stdin", self.stdin),
+            ("summary", self.summary),
+            ("summary-by-command", self.summary_by_command),
+            ("null", self.null_terminate),
+            ("tab-separated", self.tab_separated),
+            ("quiet", self.quiet),
+            ("pid-only", self.pid_only),
+            ("resolve-hostnames", self.resolve_hostnames),
+            ("resolve-service-names", self.resolve_service_names),
+            ("unix", self.unix),
+        ] {
+            if enabled {
+                options.push((option, None));
+            }
+        }
 
-    Ok(())
+        options
+    }
 }
 
-#[cfg(not(tarpaulin_include))]
-fn very_verbose(mut listening_ports: Vec<ListeningPort>) -> Result<(), Box<dyn Error>> {
-    // Enable more info through `ps aux`.
-    let pids: Vec<&String> = listening_ports.iter().map(|port| &port.pid).collect();
-    let processes_info = Ps::processes_info(&pids)?;
+#[cfg(feature = "urlencoding")]
+#[derive(Eq, PartialEq)]
+struct ConfigError {
+    reason: String,
+}
 
-    for port in &mut listening_ports {
-        port.enrich_with_process_info(&processes_info);
+#[cfg(feature = "urlencoding")]
+impl ConfigError {
+    fn with_reason(reason: impl Into<String>) -> Self {
+        Self {
+            reason: reason.into(),
+        }
     }
+}
+
+#[cfg(feature = "urlencoding")]
+impl Error for ConfigError {}
+
+#[cfg(feature = "urlencoding")]
+impl fmt::Debug for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+#[cfg(feature = "urlencoding")]
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[cfg(feature = "urlencoding")]
+impl Config {
+    /// Serialize options to a URL query string.
+    ///
+    /// For example, `format=env&include-udp=true&filters=80,443`. This
+    /// holds the same options as [`Config::serialize_to_args()`].
+    fn to_url_query_string(&self) -> String {
+        let mut params: Vec<(&str, String)> = self
+            .non_default_options()
+            .into_iter()
+            .map(|(option, value)| match (option, value) {
+                ("verbose" | "very-verbose", None) => ("mode", String::from(option)),
+                (option, Some(value)) => (option, value),
+                (option, None) => (option, String::from("true")),
+            })
+            .collect();
+        if !self.filters.is_empty() {
+            params.push(("filters", self.filters.join(",")));
+        }
+
+        params
+            .iter()
+            .map(|(key, value)| format!("{key}={}", urlencoding::encode(value)))
+            .collect::<Vec<String>>()
+            .join("&")
+    }
+
+    /// Parse a query string produced by [`Config::to_url_query_string()`].
+    ///
+    /// Parameters are turned back into arguments, so validation is the
+    /// same as on the command line.
+    fn from_url_query_string(s: &str) -> Result<Self, ConfigError> {
+        let mut args = vec![String::new()]; // Bin path.
+
+        for param in s.split('&').filter(|param| !param.is_empty()) {
+            let (key, value) = param.split_once('=').unwrap_or((param, ""));
+            let value = urlencoding::decode(value)
+                .map_err(|_| ConfigError::with_reason(format!("Invalid value for '{key}'")))?
+                .into_owned();
+
+            match key {
+                "mode" => match value.as_str() {
+                    "verbose" | "very-verbose" => args.push(format!("--{value}")),
+                    mode => {
+                        return Err(ConfigError::with_reason(format!("Unknown mode: '{mode}'")))
+                    }
+                },
+                "watch" => args.push(format!("--watch={value}")),
+                "filters" => {
+                    for filter in value.split(',') {
+                        if filter.starts_with('-') {
+                            // Would be read as an option.
+                            return Err(ConfigError::with_reason(format!(
+                                "Invalid filter: '{filter}'"
+                            )));
+                        }
+                        args.push(String::from(filter));
+                    }
+                }
+                "sort" | "columns" | "format" | "user" | "protocol" | "exclude-port"
+                | "graphql-field-name" | "column-separator" | "port-alias" | "output-file"
+                | "top" | "exclude-user" | "exclude-command" | "pid" | "include-pids-file"
+                | "lsof-file" | "ps-file" | "color" | "highlight" | "group-by" | "min-cpu"
+                | "max-cpu" | "min-mem" | "max-mem" | "since" => {
+                    args.push(format!("--{key}"));
+                    args.push(value);
+                }
+                "include-udp"
+                | "show-ruser"
+                | "no-align"
+                | "warn-no-results"
+                | "strict-parse"
+                | "aggregate-ports"
+                | "print0-pids"
+                | "reverse"
+                | "ipv4"
+                | "ipv6"
+                | "service-names"
+                | "diff"
+                | "count"
+                | "no-header"
+                | "numeric-port"
+                | "loopback-only"
+                | "wildcard-only"
+                | "stdin"
+                | "summary"
+                | "summary-by-command"
+                | "null"
+                | "tab-separated"
+                | "quiet"
+                | "pid-only"
+                | "resolve-hostnames"
+                | "resolve-service-names"
+                | "unix" => match value.as_str() {
+                    "true" => args.push(format!("--{key}")),
+                    "false" => (),
+                    value => {
+                        return Err(ConfigError::with_reason(format!(
+                            "Invalid value for '{key}': '{value}'"
+                        )))
+                    }
+                },
+                key => {
+                    return Err(ConfigError::with_reason(format!(
+                        "Unknown parameter: '{key}'"
+                    )))
+                }
+            }
+        }
+
+        Self::new(args.into_iter()).map_err(ConfigError::with_reason)
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = Config::new(env::args()).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        help(&HelpFormat::Txt);
+        std::process::exit(2);
+    });
+
+    if config.help {
+        help(&config.help_format);
+        return Ok(());
+    }
+    if config.version {
+        version();
+        return Ok(());
+    }
+    if config.print_args {
+        println!("{}", shell_words(&config.serialize_to_args()));
+        return Ok(());
+    }
+    #[cfg(feature = "urlencoding")]
+    if config.print_query {
+        println!("{}", config.to_url_query_string());
+        return Ok(());
+    }
+
+    run(config)
+}
+
+#[cfg(not(tarpaulin_include))]
+fn help(format: &HelpFormat) {
+    let renderer: &dyn HelpRenderer = match format {
+        HelpFormat::Txt => &TxtRenderer,
+        HelpFormat::Man => &ManRenderer,
+        HelpFormat::Markdown => &MarkdownRenderer,
+        HelpFormat::Json => &JsonRenderer,
+    };
+    print!("{}", renderer.render(&Help::new()));
+}
+
+#[cfg(not(tarpaulin_include))]
+fn version() {
+    print!("{}", version_info());
+}
+
+/// Name and version, then `key: value` build details for bug reports.
+///
+/// ```text
+/// ports 0.5.0
+/// target: x86_64-linux
+/// features: serde, urlencoding
+/// ```
+fn version_info() -> String {
+    let features: Vec<&str> = [
+        ("serde", cfg!(feature = "serde")),
+        ("urlencoding", cfg!(feature = "urlencoding")),
+    ]
+    .into_iter()
+    .filter_map(|(feature, enabled)| enabled.then_some(feature))
+    .collect();
+    let features = if features.is_empty() {
+        String::from("none")
+    } else {
+        features.join(", ")
+    };
+
+    format!(
+        "{} {}\ntarget: {}-{}\nfeatures: {features}\n",
+        env!("CARGO_BIN_NAME"),
+        env!("CARGO_PKG_VERSION"),
+        env::consts::ARCH,
+        env::consts::OS,
+    )
+}
+
+/// Arguments, space-separated, single-quoted if the shell would split
+/// or expand them (e.g., `--verbose --column-separator ' | ' 80`).
+fn shell_words(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            let is_plain = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_=.,:/@%+".contains(c));
+            if is_plain {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run(mut config: Config) -> Result<(), Box<dyn Error>> {
+    if let Some(path) = &config.pids_file {
+        let pids = read_pids_file(path)?;
+        config.pid_filters.extend(pids);
+    }
+    let config = &config;
+
+    let mut out = match &config.output_file {
+        Some(path) => Writer::File(
+            fs::File::create(path)
+                .map_err(|e| format!("Cannot write '{}': {e}", path.display()))?,
+        ),
+        None => Writer::Stdout(io::stdout()),
+    };
+
+    if let Some(interval) = config.watch {
+        return watch_loop(config, interval, &mut out);
+    }
+    if config.kill {
+        kill(config, &mut out)?;
+        out.flush()?;
+        return Ok(());
+    }
+    run_once(config, &mut out)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Where the output goes: stdout, or `--output-file`.
+enum Writer {
+    Stdout(io::Stdout),
+    File(fs::File),
+}
+
+impl Writer {
+    /// Only stdout goes through the pager.
+    fn is_stdout(&self) -> bool {
+        matches!(self, Self::Stdout(_))
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => stdout.write(buf),
+            Self::File(file) => file.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::File(file) => file.flush(),
+        }
+    }
+}
+
+/// Redraw the ports every `interval`, until interrupted.
+///
+/// There is nothing to restore on `Ctrl-C`: the screen is cleared with
+/// plain escape codes (no alternate screen, no hidden cursor), so the
+/// default `SIGINT` behaviour leaves the terminal as it was.
+///
+/// With `--diff`, the screen is not cleared. Instead, opened (`+`) and
+/// closed (`-`) ports are printed as they come and go.
+#[cfg(not(tarpaulin_include))]
+fn watch_loop(config: &Config, interval: Duration, out: &mut Writer) -> Result<(), Box<dyn Error>> {
+    let mut previous = Vec::new();
+    loop {
+        if config.diff {
+            let (current, _) = collect_ports(config)?;
+            let (added, removed) = diff_ports(&previous, &current);
+            for port in removed {
+                writeln!(out, "{}", diff_line('-', port))?;
+            }
+            for port in added {
+                writeln!(out, "{}", diff_line('+', port))?;
+            }
+            previous = current;
+        } else {
+            if out.is_stdout() {
+                // Clear the screen, and move the cursor to the top left.
+                write!(out, "\x1b[2J\x1b[H")?;
+            }
+            run_once(config, out)?;
+        }
+        out.flush()?;
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run_once(config: &Config, out: &mut Writer) -> Result<(), Box<dyn Error>> {
+    let (listening_ports, filtered_out) = collect_ports(config)?;
+    print_ports(listening_ports, config, out)?;
+    if config.mode >= Mode::Verbose && filtered_out > 0 {
+        // On stderr, so it doesn't get in the way of `--format` & co.
+        eprintln!("{}", filtered_out_footer(filtered_out));
+    }
+    Ok(())
+}
+
+/// Show the ports, then send `SIGTERM` to their processes.
+///
+/// Unless `--force`, the user has to confirm first.
+#[cfg(not(tarpaulin_include))]
+fn kill(config: &Config, out: &mut Writer) -> Result<(), Box<dyn Error>> {
+    let (listening_ports, _) = collect_ports(config)?;
+    let pids = kill_targets(&listening_ports);
+    if pids.is_empty() {
+        eprintln!("No processes to kill.");
+        return Ok(());
+    }
+
+    print_ports(listening_ports, config, out)?;
+    out.flush()?;
+
+    if !config.force {
+        eprint!("{}", kill_prompt(&pids));
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+        if !is_confirmed(&answer) {
+            eprintln!("Aborted.");
+            return Ok(());
+        }
+    }
+    kill_processes(&pids)?;
+    Ok(())
+}
+
+/// Unique PIDs of the ports' processes.
+///
+/// PID 0 is never a target: `kill 0` signals the whole process group,
+/// including `ports` and its shell.
+fn kill_targets(listening_ports: &[ListeningPort]) -> Vec<u32> {
+    unique_pids(listening_ports)
+        .iter()
+        .filter_map(|pid| pid.parse::<u32>().ok())
+        .filter(|pid| *pid != 0)
+        .collect()
+}
+
+fn kill_prompt(pids: &[u32]) -> String {
+    let pids: Vec<String> = pids.iter().map(u32::to_string).collect();
+    let label = if pids.len() == 1 { "PID" } else { "PIDs" };
+    format!("Send SIGTERM to {label} {}? [y/N] ", pids.join(", "))
+}
+
+/// `y` or `yes`, in any case. Anything else (including nothing) is no.
+fn is_confirmed(answer: &str) -> bool {
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+/// Output to parse, if not the one of a plain `lsof -i -n -P`.
+#[cfg(not(tarpaulin_include))]
+fn lsof_output(config: &Config) -> Result<Option<String>, Box<dyn Error>> {
+    Ok(if config.stdin {
+        Some(read_stdin_capture(io::stdin().lock())?)
+    } else if let Some(path) = &config.lsof_file {
+        Some(read_capture(path)?)
+    } else if config.resolve_hostnames || config.resolve_service_names {
+        let resolve_hostnames = if config.resolve_hostnames {
+            ResolveHostnames::Yes
+        } else {
+            ResolveHostnames::No
+        };
+        let resolve_service_names = if config.resolve_service_names {
+            ResolveServiceNames::Yes
+        } else {
+            ResolveServiceNames::No
+        };
+        Some(Lsof::listening_ports_raw_resolved(
+            resolve_hostnames,
+            resolve_service_names,
+        )?)
+    } else {
+        None
+    })
+}
+
+/// List, filter, enrich and sort the ports, as configured.
+///
+/// Also returns the number of ports filtered out by the port filters.
+#[cfg(not(tarpaulin_include))]
+fn collect_ports(config: &Config) -> Result<(Vec<ListeningPort>, usize), Box<dyn Error>> {
+    let include_udp = if config.include_udp {
+        IncludeUdp::Yes
+    } else {
+        IncludeUdp::No
+    };
+    let lsof_output = lsof_output(config)?;
+    let mut listening_ports = match (lsof_output, config.strict_parse) {
+        (Some(output), true) => Lsof::parse_strict(&output, include_udp)?,
+        (Some(output), false) => Lsof::parse(&output, include_udp)?,
+        (None, true) => Lsof::listening_ports_strict(include_udp)?,
+        (None, false) => Lsof::listening_ports(include_udp)?,
+    };
+
+    if config.unix {
+        listening_ports.extend(Lsof::unix_sockets()?);
+    }
+
+    // To filter `*:http` on `80`.
+    let services = if config.resolve_service_names {
+        ServiceNames::from_system()
+    } else {
+        ServiceNames::default()
+    };
+    let filtered_out = if config.filters.is_empty() {
+        0
+    } else {
+        filter_ports(&mut listening_ports, &config.filters, &services)
+    };
+    if !config.excluded_ports.is_empty() {
+        exclude_ports(&mut listening_ports, &config.excluded_ports, &services);
+    }
+    if !config.pid_filters.is_empty() {
+        filter_pids(&mut listening_ports, &config.pid_filters);
+    }
+    if !config.user_filters.is_empty() {
+        filter_users(&mut listening_ports, &config.user_filters);
+    }
+    if !config.excluded_users.is_empty() {
+        exclude_users(&mut listening_ports, &config.excluded_users);
+    }
+    if !config.excluded_commands.is_empty() {
+        exclude_commands(&mut listening_ports, &config.excluded_commands);
+    }
+    if !config.protocol_filters.is_empty() {
+        filter_protocols(&mut listening_ports, &config.protocol_filters);
+    }
+    filter_address_families(&mut listening_ports, config.ipv4, config.ipv6);
+    if config.loopback_only {
+        filter_loopback(&mut listening_ports);
+    }
+    if config.wildcard_only {
+        filter_wildcard(&mut listening_ports);
+    }
+
+    let cpu_filter = config.min_cpu.is_some() || config.max_cpu.is_some();
+    let mem_filter = config.min_mem.is_some() || config.max_mem.is_some();
+    // Summaries, `--quiet` and `--pid-only` only need `lsof` info
+    // (unless filtering on `ps` info).
+    let lsof_only = config.summary || config.summary_by_command || config.quiet || config.pid_only;
+    let needs_ps = !lsof_only || cpu_filter || mem_filter || config.since.is_some();
+    if config.mode >= Mode::Verbose && needs_ps && !listening_ports.is_empty() {
+        // Enable more info through `ps`.
+        let pids: Vec<&String> = listening_ports.iter().map(|port| &port.pid).collect();
+        let processes_info = match &config.ps_file {
+            Some(path) => Ps::parse(&read_capture(path)?, &pids)?,
+            None => Ps::processes_info(&pids)?,
+        };
+
+        for port in &mut listening_ports {
+            port.enrich_with_process_info(&processes_info);
+        }
+    }
+    if cpu_filter {
+        let (min, max) = (config.min_cpu, config.max_cpu);
+        filter_usage(&mut listening_ports, ProcessInfo::cpu_float, min, max);
+    }
+    if mem_filter {
+        let (min, max) = (config.min_mem, config.max_mem);
+        filter_usage(&mut listening_ports, ProcessInfo::mem_float, min, max);
+    }
+    if let Some(since) = config.since {
+        filter_since(&mut listening_ports, since, &Local::now());
+    }
+
+    order_ports(
+        &mut listening_ports,
+        config.sort_key.as_ref(),
+        config.reverse,
+    );
+    if let Some(top) = config.top {
+        keep_top(&mut listening_ports, top);
+    }
+
+    Ok((listening_ports, filtered_out))
+}
+
+#[cfg(not(tarpaulin_include))]
+fn print_ports(
+    mut listening_ports: Vec<ListeningPort>,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), Box<dyn Error>> {
+    if listening_ports.is_empty() && config.warn_no_results {
+        eprintln!("No listening ports found matching the given filters.");
+    }
+
+    if config.count {
+        write!(out, "{}", count_output(&listening_ports))?;
+        return Ok(());
+    }
+
+    if config.summary || config.summary_by_command {
+        let (header, summary) = if config.summary {
+            ("USER", summarize_by_user(&listening_ports))
+        } else {
+            ("COMMAND", summarize_by_command(&listening_ports))
+        };
+        let counts: Vec<String> = summary.iter().map(|(_, count)| count.to_string()).collect();
+        let rows: Vec<Vec<&String>> = summary
+            .iter()
+            .zip(&counts)
+            .map(|((group, _), count)| vec![group, count])
+            .collect();
+        print_table(
+            &[header, "COUNT"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &rows,
+            &TableExtras::default(),
+            config,
+            out,
+        )?;
+        return Ok(());
+    }
+
+    if config.print0_pids {
+        // Like `find -print0`, for `xargs -0`.
+        let pids: Vec<&str> = unique_pids(&listening_ports);
+        write!(out, "{}", pids.join("\0"))?;
+        return Ok(());
+    }
+
+    if config.pid_only {
+        write!(out, "{}", pid_only_output(&listening_ports))?;
+        return Ok(());
+    }
+
+    if config.quiet {
+        // Verbosity is irrelevant here, it only affects filtering.
+        write!(out, "{}", quiet_output(&listening_ports))?;
+        return Ok(());
+    }
+
+    if let Some(formatter) = formatter(config) {
+        write!(out, "{}", formatter.format(&listening_ports))?;
+        return Ok(());
+    }
+
+    if listening_ports.is_empty() {
+        return Ok(());
+    }
+
+    if config.service_names {
+        let services = ServiceNames::from_system();
+        for port in &mut listening_ports {
+            if let Some(name) = services.rename(&port.name) {
+                port.name = name;
+            }
+        }
+    }
+
+    if config.aggregate_ports {
+        listening_ports = aggregate_ports(listening_ports)
+            .into_iter()
+            .map(AggregatedPort::into_listening_port)
+            .collect();
+    }
+
+    if let Some(group_by) = config.group_by {
+        let groups = match group_by {
+            GroupBy::User => group_ports_by_user(listening_ports),
+            GroupBy::Command => group_ports_by_command(listening_ports),
+        };
+        // Rows of the same group are now next to each other, which is
+        // what `group_headers()` expects.
+        listening_ports = groups.into_values().flatten().collect();
+    }
+
+    let aliases = match &config.port_alias {
+        Some(path) => Some(AliasMap::from_file(Path::new(path))?),
+        None => None,
+    };
+
+    match config.mode {
+        Mode::Regular => regular(listening_ports, aliases.as_ref(), config, out),
+        Mode::Verbose => verbose(listening_ports, aliases.as_ref(), config, out),
+        Mode::VeryVerbose => very_verbose(listening_ports, aliases.as_ref(), config, out),
+    }
+}
+
+/// Returns the number of ports removed.
+///
+/// Service names (e.g., `*:http`) are looked up in `services`, so they
+/// match their port number (`80`).
+fn filter_ports(
+    listening_ports: &mut Vec<ListeningPort>,
+    allowed: &[String],
+    services: &ServiceNames,
+) -> usize {
+    let before = listening_ports.len();
+    listening_ports.retain(|x| {
+        resolved_port_number(x, services).is_some_and(|port| allowed.contains(&port.to_string()))
+    });
+    before - listening_ports.len()
+}
+
+/// Port number, even if `lsof` printed a service name instead.
+fn resolved_port_number(port: &ListeningPort, services: &ServiceNames) -> Option<u16> {
+    port.port_number().or_else(|| {
+        let (_host, service) = port.name.rsplit_once(':')?;
+        services.port(service)
+    })
+}
+
+fn filtered_out_footer(filtered_out: usize) -> String {
+    match filtered_out {
+        1 => String::from("1 port filtered out"),
+        n => format!("{n} ports filtered out"),
+    }
+}
+
+fn exclude_ports(
+    listening_ports: &mut Vec<ListeningPort>,
+    excluded: &[String],
+    services: &ServiceNames,
+) {
+    listening_ports.retain(|x| {
+        resolved_port_number(x, services).is_none_or(|port| !excluded.contains(&port.to_string()))
+    });
+}
+
+fn summarize_by_user(listening_ports: &[ListeningPort]) -> Vec<(String, usize)> {
+    summarize(listening_ports, GroupBy::User)
+}
+
+/// Note: `lsof` truncates command names (e.g., `docker-proxy` becomes
+/// `docker-pr`), so unless the ports are enriched with process info,
+/// different commands may be counted as one.
+fn summarize_by_command(listening_ports: &[ListeningPort]) -> Vec<(String, usize)> {
+    summarize(listening_ports, GroupBy::Command)
+}
+
+/// Number of ports per group, most ports first.
+///
+/// Groups with as many ports are sorted by name.
+fn summarize(listening_ports: &[ListeningPort], group_by: GroupBy) -> Vec<(String, usize)> {
+    let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+    for port in listening_ports {
+        *counts.entry(group_by.key(port)).or_default() += 1;
+    }
+
+    let mut summary: Vec<(String, usize)> = counts
+        .into_iter()
+        .map(|(group, count)| (String::from(group), count))
+        .collect();
+    // Stable, so equal counts stay sorted by name.
+    summary.sort_by(|(_, a), (_, b)| b.cmp(a));
+    summary
+}
+
+/// Number of ports, for scripts (e.g., `[ $(ports --count) -gt 0 ]`).
+fn count_output(listening_ports: &[ListeningPort]) -> String {
+    format!("{}\n", listening_ports.len())
+}
+
+/// `HOST:PORT` of each port, for scripts (e.g., `for port in $(ports
+/// --quiet)`).
+fn quiet_output(listening_ports: &[ListeningPort]) -> String {
+    let mut output = String::new();
+    for port in listening_ports {
+        output.push_str(&port.name);
+        output.push('\n');
+    }
+    output
+}
+
+/// Ports that were opened and closed between two polls.
+///
+/// Ports are the same if they have the same PID and `HOST:PORT`, so a
+/// process restarting on the same port is both added and removed.
+fn diff_ports<'a>(
+    old: &'a [ListeningPort],
+    new: &'a [ListeningPort],
+) -> (Vec<&'a ListeningPort>, Vec<&'a ListeningPort>) {
+    let same = |a: &ListeningPort, b: &ListeningPort| a.pid == b.pid && a.name == b.name;
+
+    let added = new
+        .iter()
+        .filter(|port| !old.iter().any(|x| same(x, port)))
+        .collect();
+    let removed = old
+        .iter()
+        .filter(|port| !new.iter().any(|x| same(x, port)))
+        .collect();
+
+    (added, removed)
+}
+
+/// `+ nginx 1234 *:80`
+fn diff_line(sign: char, port: &ListeningPort) -> String {
+    format!("{sign} {} {} {}", port.command, port.pid, port.name)
+}
+
+/// All the sockets of a process.
+#[derive(Debug, Eq, PartialEq)]
+struct AggregatedPort {
+    port: ListeningPort,
+    types: Vec<String>,
+    nodes: Vec<String>,
+    names: Vec<String>,
+}
+
+impl AggregatedPort {
+    fn new(port: ListeningPort) -> Self {
+        Self {
+            types: vec![port.type_.clone()],
+            nodes: vec![port.node.clone()],
+            names: vec![port.name.clone()],
+            port,
+        }
+    }
+
+    fn add(&mut self, port: &ListeningPort) {
+        for (values, value) in [
+            (&mut self.types, &port.type_),
+            (&mut self.nodes, &port.node),
+            (&mut self.names, &port.name),
+        ] {
+            if !values.contains(value) {
+                values.push(value.clone());
+            }
+        }
+    }
+
+    /// Single port, with comma-joined values (e.g., `*:5432, [::1]:5432`).
+    fn into_listening_port(self) -> ListeningPort {
+        let mut port = self.port;
+        port.type_ = self.types.join(", ");
+        port.node = self.nodes.join(", ");
+        port.name = self.names.join(", ");
+        port
+    }
+}
+
+fn group_ports_by_user(
+    listening_ports: Vec<ListeningPort>,
+) -> BTreeMap<String, Vec<ListeningPort>> {
+    group_ports(listening_ports, GroupBy::User)
+}
+
+fn group_ports_by_command(
+    listening_ports: Vec<ListeningPort>,
+) -> BTreeMap<String, Vec<ListeningPort>> {
+    group_ports(listening_ports, GroupBy::Command)
+}
+
+/// Ports by group, sorted by name. Ports keep their order in groups.
+fn group_ports(
+    listening_ports: Vec<ListeningPort>,
+    group_by: GroupBy,
+) -> BTreeMap<String, Vec<ListeningPort>> {
+    let mut groups: BTreeMap<String, Vec<ListeningPort>> = BTreeMap::new();
+    for port in listening_ports {
+        groups
+            .entry(String::from(group_by.key(&port)))
+            .or_default()
+            .push(port);
+    }
+    groups
+}
+
+/// Group of each run of consecutive ports, with its number of rows.
+fn group_headers(listening_ports: &[ListeningPort], group_by: GroupBy) -> Vec<(String, usize)> {
+    let mut groups: Vec<(String, usize)> = Vec::new();
+    for port in listening_ports {
+        let key = group_by.key(port);
+        match groups.last_mut() {
+            Some((group, rows)) if group == key => *rows += 1,
+            _ => groups.push((String::from(key), 1)),
+        }
+    }
+    groups
+}
+
+/// Group ports by process (`command` + `pid`), in order of appearance.
+fn aggregate_ports(listening_ports: Vec<ListeningPort>) -> Vec<AggregatedPort> {
+    let mut aggregated: Vec<AggregatedPort> = Vec::new();
+
+    for port in listening_ports {
+        if let Some(process) = aggregated
+            .iter_mut()
+            .find(|x| x.port.pid == port.pid && x.port.command == port.command)
+        {
+            process.add(&port);
+        } else {
+            aggregated.push(AggregatedPort::new(port));
+        }
+    }
+
+    aggregated
+}
+
+/// Sort (if a key is given), then reverse (if asked to).
+///
+/// Reversing without a key reverses the `lsof` order.
+fn order_ports(listening_ports: &mut [ListeningPort], sort_key: Option<&SortKey>, reverse: bool) {
+    if let Some(key) = sort_key {
+        sort_ports(listening_ports, key);
+    }
+    if reverse {
+        listening_ports.reverse();
+    }
+}
+
+/// First `top` ports, in their current order.
+fn keep_top(listening_ports: &mut Vec<ListeningPort>, top: usize) {
+    listening_ports.truncate(top);
+}
+
+/// Stable sort, so equal values keep the `lsof` order.
+fn sort_ports(listening_ports: &mut [ListeningPort], key: &SortKey) {
+    match key {
+        SortKey::Port => listening_ports.sort_by_key(|port| {
+            // Non-numeric ports last.
+            let number = port.port_number();
+            (number.is_none(), number)
+        }),
+        SortKey::Pid => listening_ports.sort_by_key(|port| port.pid.parse::<u32>().ok()),
+        SortKey::Command => listening_ports.sort_by(|a, b| a.command.cmp(&b.command)),
+        SortKey::User => listening_ports.sort_by(|a, b| a.user.cmp(&b.user)),
+        SortKey::Cpu => listening_ports.sort_by(|a, b| {
+            let cpu = |port: &ListeningPort| port.pinfo.as_ref().and_then(ProcessInfo::cpu_float);
+            cpu(a).partial_cmp(&cpu(b)).unwrap_or(Ordering::Equal)
+        }),
+        SortKey::Mem => listening_ports.sort_by(|a, b| {
+            let mem = |port: &ListeningPort| port.pinfo.as_ref().and_then(ProcessInfo::mem_float);
+            mem(a).partial_cmp(&mem(b)).unwrap_or(Ordering::Equal)
+        }),
+    }
+}
+
+/// PIDs of the ports, without duplicates, in order of appearance.
+fn unique_pids(listening_ports: &[ListeningPort]) -> Vec<&str> {
+    let mut pids: Vec<&str> = Vec::new();
+    for port in listening_ports {
+        if !pids.contains(&port.pid.as_str()) {
+            pids.push(&port.pid);
+        }
+    }
+    pids
+}
+
+/// Unique PIDs, one per line (e.g., `kill $(ports 8080 --pid-only)`).
+fn pid_only_output(listening_ports: &[ListeningPort]) -> String {
+    let mut output = String::new();
+    for pid in unique_pids(listening_ports) {
+        output.push_str(pid);
+        output.push('\n');
+    }
+    output
+}
+
+fn filter_pids(listening_ports: &mut Vec<ListeningPort>, allowed: &[String]) {
+    listening_ports.retain(|x| allowed.contains(&x.pid));
+}
+
+/// User names in `allowed` are expected trimmed and lowercase.
+fn filter_users(listening_ports: &mut Vec<ListeningPort>, allowed: &[String]) {
+    listening_ports.retain(|x| allowed.contains(&x.user.trim().to_lowercase()));
+}
+
+/// Users in `excluded` are expected lowercase, like `filter_users()`.
+fn exclude_users(listening_ports: &mut Vec<ListeningPort>, excluded: &[String]) {
+    listening_ports.retain(|x| !excluded.contains(&x.user.trim().to_lowercase()));
+}
+
+/// Hide commands containing any of the `patterns` (expected lowercase).
+fn exclude_commands(listening_ports: &mut Vec<ListeningPort>, patterns: &[String]) {
+    listening_ports.retain(|x| {
+        let command = x.command.to_lowercase();
+        !patterns
+            .iter()
+            .any(|pattern| command.contains(pattern.as_str()))
+    });
+}
+
+/// Protocols in `allowed` are expected lowercase (e.g., `tcp6`).
+fn filter_protocols(listening_ports: &mut Vec<ListeningPort>, allowed: &[String]) {
+    listening_ports.retain(|x| allowed.iter().any(|protocol| is_of_protocol(x, protocol)));
+}
+
+/// `lsof` only prints `TCP` or `UDP` as `NODE`. The address family of
+/// `tcp4` or `tcp6` is in `TYPE` (`IPv4` or `IPv6`).
+fn is_of_protocol(port: &ListeningPort, protocol: &str) -> bool {
+    let (node, family) = if let Some(node) = protocol.strip_suffix('4') {
+        (node, Some("IPv4"))
+    } else if let Some(node) = protocol.strip_suffix('6') {
+        (node, Some("IPv6"))
+    } else {
+        (protocol, None)
+    };
+    port.node.eq_ignore_ascii_case(node) && family.is_none_or(|family| port.type_ == family)
+}
+
+/// Neither family selected means no filtering, like both.
+fn filter_address_families(listening_ports: &mut Vec<ListeningPort>, ipv4: bool, ipv6: bool) {
+    if ipv4 == ipv6 {
+        return;
+    }
+    let family = if ipv4 { "IPv4" } else { "IPv6" };
+    listening_ports.retain(|x| x.type_ == family);
+}
+
+/// Percentage of CPU or memory (`usage`), from 0 to 100.
+fn parse_usage_threshold(threshold: &str, usage: &str) -> Result<f32, String> {
+    match threshold.parse::<f32>() {
+        Ok(value) if (0.0..=100.0).contains(&value) => Ok(value),
+        _ => Err(format!(
+            "Invalid {usage} threshold: '{threshold}' (expected 0 to 100)"
+        )),
+    }
+}
+
+/// Keep ports whose process `usage` (e.g., CPU) is between `min` and
+/// `max` percent (inclusive). Ports without usage info are removed.
+fn filter_usage(
+    listening_ports: &mut Vec<ListeningPort>,
+    usage: fn(&ProcessInfo) -> Option<f32>,
+    min: Option<f32>,
+    max: Option<f32>,
+) {
+    listening_ports.retain(|x| {
+        let Some(value) = x.pinfo.as_ref().and_then(usage) else {
+            return false;
+        };
+        min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+    });
+}
+
+/// Number followed by a unit: `s`, `m`, `h` or `d` (e.g., `30m`).
+fn parse_since(since: &str) -> Result<Duration, String> {
+    let error = || format!("Invalid duration: '{since}' (expected e.g., 30m, 2h or 1d)");
+    let Some(unit) = since.chars().last() else {
+        return Err(error());
+    };
+    let seconds = match unit {
+        's' => 1,
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        _ => return Err(error()),
+    };
+    match since[..since.len() - 1].parse::<u64>() {
+        Ok(value) => value
+            .checked_mul(seconds)
+            .map(Duration::from_secs)
+            .ok_or_else(error),
+        Err(_) => Err(error()),
+    }
+}
+
+/// Keep ports whose process started less than `since` ago, relative to
+/// `now`. Ports without a (parsable) start time are removed.
+fn filter_since<Tz: TimeZone>(
+    listening_ports: &mut Vec<ListeningPort>,
+    since: Duration,
+    now: &DateTime<Tz>,
+) {
+    let Some(cutoff) = SystemTime::from(now.clone()).checked_sub(since) else {
+        return;
+    };
+    listening_ports.retain(|x| {
+        x.pinfo
+            .as_ref()
+            .and_then(|pinfo| parse_ps_start_time_relative_to(&pinfo.start, now))
+            .is_some_and(|start| start >= cutoff)
+    });
+}
+
+/// Number of ports, at least one.
+fn parse_top(top: &str) -> Result<usize, String> {
+    match top.parse::<usize>() {
+        Ok(top) if top >= 1 => Ok(top),
+        _ => Err(format!(
+            "Invalid top: '{top}' (expected a number, at least 1)"
+        )),
+    }
+}
+
+fn filter_loopback(listening_ports: &mut Vec<ListeningPort>) {
+    listening_ports.retain(ListeningPort::is_loopback);
+}
+
+fn filter_wildcard(listening_ports: &mut Vec<ListeningPort>) {
+    listening_ports.retain(ListeningPort::is_wildcard);
+}
+
+/// Whole seconds, at least one.
+fn parse_watch_interval(seconds: &str) -> Result<Duration, String> {
+    match seconds.parse::<u64>() {
+        Ok(seconds) if seconds >= 1 => Ok(Duration::from_secs(seconds)),
+        _ => Err(format!(
+            "Invalid watch interval: '{seconds}' (expected seconds, at least 1)"
+        )),
+    }
+}
+
+/// Captured `lsof` or `ps` output, read in place of the command's.
+#[cfg(not(tarpaulin_include))]
+fn read_capture(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Cannot read '{}': {e}", path.display()))
+}
+
+/// Piped `lsof` output (e.g., `lsof -i -n -P | ports --stdin`).
+fn read_stdin_capture(mut input: impl io::Read) -> Result<String, String> {
+    let mut output = String::new();
+    input
+        .read_to_string(&mut output)
+        .map_err(|e| format!("Cannot read stdin: {e}"))?;
+    Ok(output)
+}
+
+/// One PID per line, like `cgroup.procs`.
+fn read_pids_file(path: &Path) -> Result<Vec<String>, String> {
+    let pids: Vec<String> = fs::read_to_string(path)
+        .map_err(|e| format!("Cannot read '{}': {e}", path.display()))?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect();
+    if pids.is_empty() {
+        // Filtering on nothing would mean 'keep everything'.
+        return Err(format!("No PIDs in '{}'", path.display()));
+    }
+    Ok(pids)
+}
+
+fn formatter(config: &Config) -> Option<Box<dyn Formatter>> {
+    let formatter: Box<dyn Formatter> = match config.format.as_ref()? {
+        OutputFormat::AnsibleVars => Box::new(AnsibleVarsFormatter),
+        OutputFormat::Env => Box::new(EnvFormatter),
+        OutputFormat::GraphQl => Box::new(
+            config
+                .graphql_field_name
+                .as_ref()
+                .map_or_else(GraphQLFormatter::new, |name| {
+                    GraphQLFormatter::with_field_name(name)
+                }),
+        ),
+        OutputFormat::K8sLabels => Box::new(K8sLabelsFormatter),
+        OutputFormat::NginxUpstream => Box::new(NginxUpstreamFormatter),
+        OutputFormat::OpenMetrics => Box::new(OpenMetricsFormatter),
+        OutputFormat::RustDebug => Box::new(DebugFormatter),
+        OutputFormat::RustPretty => Box::new(PrettyDebugFormatter),
+        OutputFormat::SystemdSocket => Box::new(SystemdSocketFormatter),
+        OutputFormat::Template(template) => Box::new(TemplateFormatter {
+            template: template.clone(),
+            terminator: if config.null_terminate { '\0' } else { '\n' },
+        }),
+    };
+    Some(formatter)
+}
+
+// Yes, bad, I know. But I want the same signature for all modes.
+#[allow(clippy::needless_pass_by_value, clippy::unnecessary_wraps)]
+#[cfg(not(tarpaulin_include))]
+fn regular(
+    listening_ports: Vec<ListeningPort>,
+    aliases: Option<&AliasMap>,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), Box<dyn Error>> {
+    let mut columns = vec![
+        Column::Command,
+        Column::Pid,
+        Column::User,
+        Column::Type,
+        Column::Node,
+        Column::Name,
+    ];
+    if aliases.is_some() {
+        columns.insert(6, Column::Alias);
+    }
+    if config.numeric_port {
+        columns.insert(6, Column::Port);
+    }
+
+    print_columns(&listening_ports, &columns, aliases, config, out)
+}
+
+#[allow(clippy::needless_pass_by_value)] // Same signature for all modes.
+#[cfg(not(tarpaulin_include))]
+fn verbose(
+    listening_ports: Vec<ListeningPort>,
+    aliases: Option<&AliasMap>,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), Box<dyn Error>> {
+    let mut columns = vec![
+        Column::Command,
+        Column::Pid,
+        Column::User,
+        Column::Type,
+        Column::Node,
+        Column::Name,
+        Column::FullCommand,
+    ];
+    if aliases.is_some() {
+        columns.insert(6, Column::Alias);
+    }
+    if config.numeric_port {
+        columns.insert(6, Column::Port);
+    }
+    if config.show_ruser {
+        columns.insert(3, Column::Ruser);
+    }
+
+    print_columns(&listening_ports, &columns, aliases, config, out)
+}
+
+#[allow(clippy::needless_pass_by_value)] // Same signature for all modes.
+#[cfg(not(tarpaulin_include))]
+fn very_verbose(
+    listening_ports: Vec<ListeningPort>,
+    aliases: Option<&AliasMap>,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), Box<dyn Error>> {
+    let mut columns = vec![
+        Column::Command,
+        Column::Pid,
+        Column::User,
+        Column::Type,
+        Column::Node,
+        Column::Name,
+        Column::Ppid,
+        Column::Cpu,
+        Column::Mem,
+        Column::Start,
+        Column::Time,
+        Column::FullCommand,
+    ];
+    if aliases.is_some() {
+        columns.insert(6, Column::Alias);
+    }
+    if config.numeric_port {
+        columns.insert(6, Column::Port);
+    }
+    if config.show_ruser {
+        columns.insert(3, Column::Ruser);
+    }
+
+    print_columns(&listening_ports, &columns, aliases, config, out)
+}
+
+/// Print the mode's `columns`, unless others were selected.
+#[cfg(not(tarpaulin_include))]
+fn print_columns(
+    listening_ports: &[ListeningPort],
+    columns: &[Column],
+    aliases: Option<&AliasMap>,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), Box<dyn Error>> {
+    let columns = if config.columns.is_empty() {
+        columns
+    } else {
+        &config.columns
+    };
+
+    let port_numbers: Vec<String> = if columns.contains(&Column::Port) {
+        listening_ports
+            .iter()
+            .map(|port| {
+                port.port_number()
+                    .map(|n| n.to_string())
+                    .unwrap_or_default()
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let empty = String::new();
+    let rows = table_rows(listening_ports, columns, aliases, &port_numbers, &empty);
+
+    let headers: Vec<&str> = columns.iter().map(|column| column.header()).collect();
+    let alignments: Vec<fmt::Alignment> = columns.iter().map(|column| column.alignment()).collect();
+
+    let groups = config
+        .group_by
+        .map(|group_by| group_headers(listening_ports, group_by))
+        .unwrap_or_default();
+
+    let is_terminal = out.is_stdout() && io::stdout().is_terminal();
+    // Colors would end up in the TSV values.
+    if config.color.enabled(is_terminal) && !config.tab_separated {
+        let (headers, rows) = paint_columns(&headers, &alignments, &rows, columns, config);
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+        let rows: Vec<Vec<&String>> = rows.iter().map(|row| row.iter().collect()).collect();
+        let extras = TableExtras {
+            highlighted: listening_ports
+                .iter()
+                .map(|port| is_highlighted(port, &config.highlight_ports))
+                .collect(),
+            groups,
+        };
+        print_table(&headers, &alignments, &rows, &extras, config, out)?;
+    } else {
+        let extras = TableExtras {
+            groups,
+            ..TableExtras::default()
+        };
+        print_table(&headers, &alignments, &rows, &extras, config, out)?;
+    }
+
+    Ok(())
+}
+
+/// Headers and rows, with the values of styled columns painted.
+///
+/// Unless `--no-align`, cells of styled columns are padded to the
+/// column width first (see [`Style::paint_padded()`]).
+fn paint_columns(
+    headers: &[&str],
+    alignments: &[fmt::Alignment],
+    rows: &[Vec<&String>],
+    columns: &[Column],
+    config: &Config,
+) -> (Vec<String>, Vec<Vec<String>>) {
+    let mut headers: Vec<String> = headers.iter().map(|header| String::from(*header)).collect();
+    let mut rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().copied().cloned().collect())
+        .collect();
+
+    for (i, column) in columns.iter().enumerate() {
+        let Some(style) = column.style() else {
+            continue;
+        };
+        let cells = std::iter::once(&mut headers[i]).chain(rows.iter_mut().map(|row| &mut row[i]));
+        if config.no_align {
+            for cell in cells {
+                *cell = style.paint(cell);
+            }
+        } else {
+            let cells: Vec<&mut String> = cells.collect();
+            let width = cells.iter().map(|cell| cell.chars().count()).max();
+            let width = width.unwrap_or_default();
+            for cell in cells {
+                *cell = style.paint_padded(cell, width, alignments[i]);
+            }
+        }
+    }
+
+    (headers, rows)
+}
+
+/// Values of `columns`, one row per port.
+///
+/// `port_numbers` are those of `listening_ports`, in the same order.
+fn table_rows<'a>(
+    listening_ports: &'a [ListeningPort],
+    columns: &[Column],
+    aliases: Option<&'a AliasMap>,
+    port_numbers: &'a [String],
+    empty: &'a String,
+) -> Vec<Vec<&'a String>> {
+    listening_ports
+        .iter()
+        .enumerate()
+        .map(|(i, port)| {
+            let pinfo = port.pinfo.as_ref();
+            columns
+                .iter()
+                .map(|column| match column {
+                    Column::Command => &port.command,
+                    Column::Pid => &port.pid,
+                    Column::User => &port.user,
+                    Column::Ruser => pinfo.map_or(empty, |p| &p.ruser),
+                    Column::Type => &port.type_,
+                    Column::Node => &port.node,
+                    Column::Name => &port.name,
+                    Column::Port => port_numbers.get(i).unwrap_or(empty),
+                    Column::Alias => aliases
+                        .and_then(|aliases| aliases.alias_of(port))
+                        .unwrap_or(empty),
+                    Column::Ppid => pinfo.map_or(empty, |p| &p.ppid),
+                    Column::Cpu => pinfo.map_or(empty, |p| &p.pc_cpu),
+                    Column::Mem => pinfo.map_or(empty, |p| &p.pc_mem),
+                    Column::Vsz => pinfo.map_or(empty, |p| &p.vsz),
+                    Column::Rss => pinfo.map_or(empty, |p| &p.rss),
+                    Column::Start => pinfo.map_or(empty, |p| &p.start),
+                    Column::Time => pinfo.map_or(empty, |p| &p.time),
+                    Column::FullCommand => pinfo.map_or(empty, |p| &p.command),
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Additions to a rendered table, by row.
+#[derive(Debug, Default)]
+struct TableExtras {
+    /// Rows to paint (`--highlight`).
+    highlighted: Vec<bool>,
+    /// Sub-headers, with the number of rows under each (`--group-by`).
+    groups: Vec<(String, usize)>,
+}
+
+fn print_table(
+    headers: &[&str],
+    alignments: &[fmt::Alignment],
+    data: &[Vec<&String>],
+    extras: &TableExtras,
+    config: &Config,
+    out: &mut Writer,
+) -> Result<(), io::Error> {
+    let table = render_table(headers, alignments, data, config);
+    let table = highlight_rows(table, &extras.highlighted, config);
+    let table = insert_group_headers(table, &extras.groups, config);
+
+    if out.is_stdout() && config.watch.is_none() && !config.kill {
+        table.output_paged();
+    } else {
+        // No pager for files, and it would block the refresh (or
+        // hide the `--kill` prompt).
+        write!(out, "{table}")?;
+    }
+    Ok(())
+}
+
+fn render_table(
+    headers: &[&str],
+    alignments: &[fmt::Alignment],
+    data: &[Vec<&String>],
+    config: &Config,
+) -> String {
+    let table = if config.tab_separated {
+        unaligned_table(headers, data, "\t")
+    } else if config.no_align {
+        let separator = config.column_separator.as_deref().unwrap_or(" ");
+        unaligned_table(headers, data, separator)
+    } else if let Some(separator) = &config.column_separator {
+        delimited_table(headers, alignments, data, separator)
+    } else {
+        Table::new()
+            .headers(headers)
+            .alignments(alignments)
+            .data(data)
+            .to_string()
+    };
+
+    if config.no_header {
+        // Headers still count in column widths, so alignment is
+        // the same with or without them.
+        return table
+            .split_once('\n')
+            .map(|(_, data)| String::from(data))
+            .unwrap_or_default();
+    }
+    table
+}
+
+/// Whether any of the port's names (aggregated or not) has a
+/// highlighted port number.
+fn is_highlighted(port: &ListeningPort, highlight_ports: &[String]) -> bool {
+    port.name
+        .split(", ")
+        .filter_map(Address::parse)
+        .any(|address| highlight_ports.contains(&address.port.to_string()))
+}
+
+/// Paint the lines of the `highlighted` rows of a rendered table.
+///
+/// Rows are painted as a whole, after rendering, so the highlight
+/// doesn't change the column widths.
+fn highlight_rows(table: String, highlighted: &[bool], config: &Config) -> String {
+    if !highlighted.contains(&true) {
+        return table;
+    }
+    let header_lines = usize::from(!config.no_header);
+
+    let mut highlighted_table = String::with_capacity(table.len());
+    for (i, line) in table.lines().enumerate() {
+        let row = i.checked_sub(header_lines);
+        if row.is_some_and(|row| highlighted.get(row) == Some(&true)) {
+            highlighted_table.push_str(&Style::Reverse.paint_line(line));
+        } else {
+            highlighted_table.push_str(line);
+        }
+        highlighted_table.push('\n');
+    }
+    highlighted_table
+}
+
+/// Put a `GROUP:` line before the rows of each group.
+///
+/// Groups without rows are skipped.
+fn insert_group_headers(table: String, groups: &[(String, usize)], config: &Config) -> String {
+    if groups.is_empty() {
+        return table;
+    }
+
+    let mut lines = table.lines();
+    let mut grouped_table = String::with_capacity(table.len());
+    if !config.no_header {
+        if let Some(header) = lines.next() {
+            grouped_table.push_str(header);
+            grouped_table.push('\n');
+        }
+    }
+    for (group, rows) in groups.iter().filter(|(_, rows)| *rows > 0) {
+        let group = if group.is_empty() { "?" } else { group };
+        grouped_table.push_str(group);
+        grouped_table.push_str(":\n");
+        for line in lines.by_ref().take(*rows) {
+            grouped_table.push_str(line);
+            grouped_table.push('\n');
+        }
+    }
+    grouped_table
+}
+
+/// Like `verynicetable`, but with a custom column separator.
+fn delimited_table(
+    headers: &[&str],
+    alignments: &[fmt::Alignment],
+    data: &[Vec<&String>],
+    separator: &str,
+) -> String {
+    let rows: Vec<Vec<&str>> = std::iter::once(headers.to_vec())
+        .chain(
+            data.iter()
+                .map(|row| row.iter().map(|cell| cell.as_str()).collect()),
+        )
+        .collect();
+
+    let mut widths = vec![0; headers.len()];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in &rows {
+        let line: Vec<String> = row
+            .iter()
+            .zip(&widths)
+            .zip(alignments)
+            .map(|((cell, &width), alignment)| match alignment {
+                fmt::Alignment::Right => format!("{cell:>width$}"),
+                fmt::Alignment::Center => format!("{cell:^width$}"),
+                fmt::Alignment::Left => format!("{cell:<width$}"),
+            })
+            .collect();
+        table.push_str(line.join(separator).trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Cells as-is, without computing column widths.
+fn unaligned_table(headers: &[&str], data: &[Vec<&String>], separator: &str) -> String {
+    let mut table = headers.join(separator);
+    table.push('\n');
+    for row in data {
+        let line: Vec<&str> = row.iter().map(|cell| cell.as_str()).collect();
+        table.push_str(&line.join(separator));
+        table.push('\n');
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_no_args() {
+        let args = vec![String::new()].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                help: false,
+                help_format: HelpFormat::Txt,
+                version: false,
+                print_args: false,
+                #[cfg(feature = "urlencoding")]
+                print_query: false,
+                mode: Mode::Regular,
+                filters: Vec::new(),
+                pid_filters: Vec::new(),
+                pids_file: None,
+                format: None,
+                graphql_field_name: None,
+                include_udp: false,
+                show_ruser: false,
+                column_separator: None,
+                no_align: false,
+                warn_no_results: false,
+                strict_parse: false,
+                aggregate_ports: false,
+                port_alias: None,
+                print0_pids: false,
+                sort_key: None,
+                reverse: false,
+                user_filters: Vec::new(),
+                protocol_filters: Vec::new(),
+                ipv4: false,
+                ipv6: false,
+                excluded_ports: Vec::new(),
+                service_names: false,
+                watch: None,
+                diff: false,
+                count: false,
+                no_header: false,
+                columns: Vec::new(),
+                numeric_port: false,
+                output_file: None,
+                top: None,
+                excluded_users: Vec::new(),
+                excluded_commands: Vec::new(),
+                loopback_only: false,
+                wildcard_only: false,
+                lsof_file: None,
+                ps_file: None,
+                stdin: false,
+                color: ColorMode::Auto,
+                highlight_ports: Vec::new(),
+                group_by: None,
+                summary: false,
+                summary_by_command: false,
+                min_cpu: None,
+                max_cpu: None,
+                min_mem: None,
+                max_mem: None,
+                since: None,
+                null_terminate: false,
+                tab_separated: false,
+                quiet: false,
+                pid_only: false,
+                kill: false,
+                force: false,
+                resolve_hostnames: false,
+                resolve_service_names: false,
+                unix: false,
+            }
+        );
+    }
+
+    #[test]
+    fn config_with_bin_path() {
+        let args = vec![String::from("/usr/local/bin/ports")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                help: false,
+                help_format: HelpFormat::Txt,
+                version: false,
+                print_args: false,
+                #[cfg(feature = "urlencoding")]
+                print_query: false,
+                mode: Mode::Regular,
+                filters: Vec::new(),
+                pid_filters: Vec::new(),
+                pids_file: None,
+                format: None,
+                graphql_field_name: None,
+                include_udp: false,
+                show_ruser: false,
+                column_separator: None,
+                no_align: false,
+                warn_no_results: false,
+                strict_parse: false,
+                aggregate_ports: false,
+                port_alias: None,
+                print0_pids: false,
+                sort_key: None,
+                reverse: false,
+                user_filters: Vec::new(),
+                protocol_filters: Vec::new(),
+                ipv4: false,
+                ipv6: false,
+                excluded_ports: Vec::new(),
+                service_names: false,
+                watch: None,
+                diff: false,
+                count: false,
+                no_header: false,
+                columns: Vec::new(),
+                numeric_port: false,
+                output_file: None,
+                top: None,
+                excluded_users: Vec::new(),
+                excluded_commands: Vec::new(),
+                loopback_only: false,
+                wildcard_only: false,
+                lsof_file: None,
+                ps_file: None,
+                stdin: false,
+                color: ColorMode::Auto,
+                highlight_ports: Vec::new(),
+                group_by: None,
+                summary: false,
+                summary_by_command: false,
+                min_cpu: None,
+                max_cpu: None,
+                min_mem: None,
+                max_mem: None,
+                since: None,
+                null_terminate: false,
+                tab_separated: false,
+                quiet: false,
+                pid_only: false,
+                kill: false,
+                force: false,
+                resolve_hostnames: false,
+                resolve_service_names: false,
+                unix: false,
+            }
+        );
+    }
+
+    #[test]
+    fn config_serialize_to_args() {
+        let config = Config {
+            mode: Mode::Verbose,
+            filters: vec![String::from("80"), String::from("443")],
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.serialize_to_args(),
+            vec![
+                String::from("--verbose"),
+                String::from("80"),
+                String::from("443")
+            ]
+        );
+    }
+
+    #[test]
+    fn config_serialize_to_args_default() {
+        assert!(Config::default().serialize_to_args().is_empty());
+    }
+
+    #[test]
+    fn config_serialize_to_args_round_trip() {
+        let args = [
+            "-vvv",
+            "8000-8002",
+            "--format",
+            "graphql",
+            "--graphql-field-name",
+            "ports",
+            "--column-separator",
+            " | ",
+            "--port-alias",
+            "aliases.toml",
+            "--include-udp",
+            "--show-ruser",
+            "--no-align",
+            "--warn-no-results",
+            "--strict-parse",
+            "--aggregate-ports",
+            "--print0-pids",
+            "--sort",
+            "cpu",
+            "--reverse",
+            "--pid",
+            "723",
+            "--pid",
+            "1174",
+            "--include-pids-file",
+            "pids.txt",
+            "--top",
+            "5",
+            "--min-cpu",
+            "0.5",
+            "--max-cpu",
+            "50",
+            "--min-mem",
+            "1",
+            "--max-mem",
+            "2.5",
+            "--since",
+            "5400s",
+            "--user",
+            "root",
+            "--user",
+            "www-data",
+            "--exclude-user",
+            "postgres",
+            "--exclude-command",
+            "rapportd",
+            "-p",
+            "tcp6",
+            "--ipv4",
+            "--ipv6",
+            "--exclude-port",
+            "8001",
+            "--watch=3",
+            "--diff",
+            "--count",
+            "--no-header",
+            "--columns",
+            "name,pid,full-command",
+            "--numeric-port",
+            "--color",
+            "never",
+            "--highlight",
+            "8080",
+            "--group-by",
+            "user",
+            "--loopback-only",
+            "--lsof-file",
+            "lsof.txt",
+            "--ps-file",
+            "ps.txt",
+            "--output-file",
+            "ports.txt",
+        ];
+        let args = std::iter::once(String::new()).chain(args.into_iter().map(String::from));
+        let config = Config::new(args).unwrap();
+
+        let args = std::iter::once(String::new()).chain(config.serialize_to_args());
+
+        assert_eq!(Config::new(args), Ok(config));
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_to_url_query_string() {
+        let config = Config {
+            mode: Mode::Verbose,
+            filters: vec![String::from("80"), String::from("443")],
+            format: Some(OutputFormat::Env),
+            column_separator: Some(String::from(" | ")),
+            include_udp: true,
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.to_url_query_string(),
+            "mode=verbose&format=env&column-separator=%20%7C%20&include-udp=true&filters=80%2C443"
+        );
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_to_url_query_string_default() {
+        assert_eq!(Config::default().to_url_query_string(), "");
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_from_url_query_string_round_trip() {
+        let config = Config {
+            mode: Mode::VeryVerbose,
+            filters: vec![String::from("80"), String::from("443")],
+            format: Some(OutputFormat::GraphQl),
+            graphql_field_name: Some(String::from("ports & more")),
+            user_filters: vec![String::from("root"), String::from("www-data")],
+            protocol_filters: vec![String::from("tcp"), String::from("udp6")],
+            watch: Some(Duration::from_secs(10)),
+            include_udp: true,
+            show_ruser: true,
+            no_align: true,
+            warn_no_results: true,
+            ..Config::default()
+        };
+
+        let query = config.to_url_query_string();
+
+        assert_eq!(Config::from_url_query_string(&query), Ok(config));
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_print_query() {
+        let args = vec![String::new(), String::from("--print-query")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.print_query);
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_from_query() {
+        let args = [
+            "",
+            "--from-query",
+            "mode=verbose&include-udp=true&filters=80,443",
+        ]
+        .into_iter()
+        .map(String::from);
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config,
+            Config {
+                mode: Mode::Verbose,
+                include_udp: true,
+                filters: vec![String::from("80"), String::from("443")],
+                ..Config::default()
+            }
+        );
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_from_query_cannot_be_combined() {
+        for args in [
+            ["", "--verbose", "--from-query", "include-udp=true"],
+            ["", "--from-query", "include-udp=true", "80"],
+        ] {
+            let args = args.into_iter().map(String::from);
+
+            assert_eq!(
+                Config::new(args),
+                Err(String::from(
+                    "'--from-query' cannot be combined with other options"
+                ))
+            );
+        }
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_from_query_error() {
+        let args = ["", "--from-query", "colour=always"]
+            .into_iter()
+            .map(String::from);
+
+        assert_eq!(
+            Config::new(args),
+            Err(String::from("Unknown parameter: 'colour'"))
+        );
+    }
+
+    #[cfg(feature = "urlencoding")]
+    #[test]
+    fn config_from_url_query_string_errors() {
+        assert_eq!(
+            Config::from_url_query_string("colour=always"),
+            Err(ConfigError::with_reason("Unknown parameter: 'colour'"))
+        );
+        // Too dangerous to be shared.
+        assert_eq!(
+            Config::from_url_query_string("kill=true"),
+            Err(ConfigError::with_reason("Unknown parameter: 'kill'"))
+        );
+        assert_eq!(
+            Config::from_url_query_string("mode=help"),
+            Err(ConfigError::with_reason("Unknown mode: 'help'"))
+        );
+        assert_eq!(
+            Config::from_url_query_string("filters=80,--help"),
+            Err(ConfigError::with_reason("Invalid filter: '--help'"))
+        );
+        assert_eq!(
+            Config::from_url_query_string("no-align=yes"),
+            Err(ConfigError::with_reason(
+                "Invalid value for 'no-align': 'yes'"
+            ))
+        );
+        assert_eq!(
+            Config::from_url_query_string("format=xml"),
+            Err(ConfigError::with_reason("Unknown format: 'xml'"))
+        );
+    }
+
+    #[test]
+    fn config_help_full() {
+        let args = vec![String::new(), String::from("--help")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.help);
+    }
+
+    #[test]
+    fn config_help_short() {
+        let args = vec![String::new(), String::from("-h")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.help);
+    }
+
+    #[test]
+    fn config_help_format() {
+        for (format, expected) in [
+            ("txt", HelpFormat::Txt),
+            ("man", HelpFormat::Man),
+            ("markdown", HelpFormat::Markdown),
+            ("json", HelpFormat::Json),
+        ] {
+            let args = vec![
+                String::new(),
+                String::from("--help-format"),
+                String::from(format),
+            ]
+            .into_iter();
+            let config = Config::new(args).unwrap();
+
+            assert!(config.help);
+            assert_eq!(config.help_format, expected);
+        }
+    }
+
+    #[test]
+    fn config_help_format_unknown() {
+        let args = vec![
+            String::new(),
+            String::from("--help-format"),
+            String::from("html"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown help format: 'html'")));
+    }
+
+    #[test]
+    fn config_version_full() {
+        let args = vec![String::new(), String::from("--version")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.version);
+    }
+
+    #[test]
+    fn config_version_short() {
+        let args = vec![String::new(), String::from("-v")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.version);
+    }
+
+    #[test]
+    fn config_regular() {
+        let args = vec![String::new()].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Regular);
+    }
+
+    #[test]
+    fn config_verbose_full() {
+        let args = vec![String::new(), String::from("--verbose")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_verbose_short() {
+        let args = vec![String::new(), String::from("-vv")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_verbose_over_verbose_is_no_op() {
+        let args = vec![
+            String::new(),
+            String::from("--verbose"),
+            String::from("--verbose"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_very_verbose_full() {
+        let args = vec![String::new(), String::from("--very-verbose")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_very_verbose_short() {
+        let args = vec![String::new(), String::from("-vvv")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_very_verbose_gt_verbose() {
+        let args = vec![
+            String::new(),
+            String::from("--verbose"),
+            String::from("--very-verbose"),
+            String::from("--verbose"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_very_verbose_over_very_verbose_is_no_op() {
+        let args = vec![
+            String::new(),
+            String::from("--very-verbose"),
+            String::from("--very-verbose"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_filters() {
+        let args = vec![String::new(), String::from("1337"), String::from("42069")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.filters,
+            &[String::from("1337"), String::from("42069")]
+        );
+    }
+
+    #[test]
+    fn config_filters_invalid_too_low() {
+        let args = vec![String::new(), String::from("-1")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'-1'"));
+    }
+
+    #[test]
+    fn config_filters_invalid_too_high() {
+        let args = vec![String::new(), String::from("65536")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'65536'"));
+    }
+
+    #[test]
+    fn config_filters_invalid_not_a_number() {
+        let args = vec![String::new(), String::from("123nan")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'123nan'"));
+    }
+
+    #[test]
+    fn config_range_filters_regular() {
+        let args = vec![String::new(), String::from("1000-1005")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.filters,
+            &[
+                String::from("1000"),
+                String::from("1001"),
+                String::from("1002"),
+                String::from("1003"),
+                String::from("1004"),
+                String::from("1005"),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_range_filters_end_first() {
+        let args = vec![String::new(), String::from("1005-1000")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.filters,
+            &[
+                String::from("1000"),
+                String::from("1001"),
+                String::from("1002"),
+                String::from("1003"),
+                String::from("1004"),
+                String::from("1005"),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_range_filters_multiple_ranges() {
+        let args = vec![
+            String::new(),
+            String::from("1000-1005"),
+            String::from("40000-40003"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.filters,
+            &[
+                String::from("1000"),
+                String::from("1001"),
+                String::from("1002"),
+                String::from("1003"),
+                String::from("1004"),
+                String::from("1005"),
+                String::from("40000"),
+                String::from("40001"),
+                String::from("40002"),
+                String::from("40003"),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_range_filters_with_simple_filter() {
+        let args = vec![
+            String::new(),
+            String::from("8000"),
+            String::from("1005-1000"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.filters,
+            &[
+                String::from("8000"),
+                String::from("1000"),
+                String::from("1001"),
+                String::from("1002"),
+                String::from("1003"),
+                String::from("1004"),
+                String::from("1005"),
+            ]
+        );
+    }
+
+    #[test]
+    fn config_range_filters_range_equals() {
+        let args = vec![String::new(), String::from("1000-1000")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.filters, &[String::from("1000"),]);
+    }
+
+    #[test]
+    fn config_range_filters_invalid_too_low() {
+        let args = vec![String::new(), String::from("-1-10")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'-1-10'"));
+    }
+
+    #[test]
+    fn config_range_filters_invalid_too_high() {
+        let args = vec![String::new(), String::from("65530-65536")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'65530-65536'"));
+    }
+
+    #[test]
+    fn config_exclude_port() {
+        let args = vec![
+            String::new(),
+            String::from("--exclude-port"),
+            String::from("22"),
+            String::from("--exclude-port"),
+            String::from("8002-8000"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.excluded_ports,
+            vec![
+                String::from("22"),
+                String::from("8000"),
+                String::from("8001"),
+                String::from("8002")
+            ]
+        );
+        assert!(config.filters.is_empty());
+    }
+
+    #[test]
+    fn config_exclude_port_invalid() {
+        let args = vec![
+            String::new(),
+            String::from("--exclude-port"),
+            String::from("ssh"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Invalid port: 'ssh'")));
+    }
+
+    #[test]
+    fn config_watch_default_interval() {
+        let args = vec![String::new(), String::from("--watch"), String::from("80")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.watch, Some(Duration::from_secs(2)));
+        assert_eq!(config.filters, vec![String::from("80")]);
+    }
+
+    #[test]
+    fn config_watch_interval() {
+        let args = vec![String::new(), String::from("--watch=5")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.watch, Some(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn config_watch_invalid_interval() {
+        let args = vec![String::new(), String::from("--watch=0")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "Invalid watch interval: '0' (expected seconds, at least 1)"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_no_header() {
+        let args = vec![String::new(), String::from("--no-header")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.no_header);
+    }
+
+    #[test]
+    fn config_columns() {
+        let args = vec![
+            String::new(),
+            String::from("--columns"),
+            String::from("pid, name,command"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.columns,
+            vec![Column::Pid, Column::Name, Column::Command]
+        );
+        assert_eq!(config.mode, Mode::Regular);
+    }
+
+    #[test]
+    fn config_columns_process_info_increases_verbosity() {
+        let args = vec![
+            String::new(),
+            String::from("--columns"),
+            String::from("pid,cpu"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_columns_unknown() {
+        let args = vec![
+            String::new(),
+            String::from("--columns"),
+            String::from("pid,host"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown column: 'host'")));
+    }
+
+    #[test]
+    fn config_numeric_port() {
+        let args = vec![String::new(), String::from("--numeric-port")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.numeric_port);
+    }
+
+    #[test]
+    fn config_count() {
+        let args = vec![
+            String::new(),
+            String::from("8000-9000"),
+            String::from("--count"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.count);
+        assert_eq!(config.filters.len(), 1001);
+    }
+
+    #[test]
+    fn config_diff() {
+        let args = vec![
+            String::new(),
+            String::from("--watch"),
+            String::from("--diff"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.diff);
+    }
+
+    #[test]
+    fn config_diff_without_watch() {
+        let args = vec![String::new(), String::from("--diff")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("'--diff' requires '--watch'")));
+    }
+
+    #[test]
+    fn config_top() {
+        let args = vec![String::new(), String::from("--top"), String::from("5")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.top, Some(5));
+    }
+
+    #[test]
+    fn config_cpu_thresholds() {
+        let args = vec![
+            String::new(),
+            String::from("--min-cpu"),
+            String::from("0"),
+            String::from("--max-cpu"),
+            String::from("100"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.min_cpu, Some(0.0));
+        assert_eq!(config.max_cpu, Some(100.0));
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_cpu_thresholds_keep_very_verbose() {
+        let args = vec![
+            String::new(),
+            String::from("-vvv"),
+            String::from("--min-cpu"),
+            String::from("10"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_cpu_thresholds_min_greater_than_max() {
+        let args = vec![
+            String::new(),
+            String::from("--min-cpu"),
+            String::from("50"),
+            String::from("--max-cpu"),
+            String::from("10.5"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--min-cpu' (50) is greater than '--max-cpu' (10.5)"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_mem_thresholds() {
+        let args = vec![
+            String::new(),
+            String::from("--min-mem"),
+            String::from("0.5"),
+            String::from("--max-mem"),
+            String::from("100"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.min_mem, Some(0.5));
+        assert_eq!(config.max_mem, Some(100.0));
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_mem_threshold_invalid() {
+        let args = vec![
+            String::new(),
+            String::from("--max-mem"),
+            String::from("101"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "Invalid memory threshold: '101' (expected 0 to 100)"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_mem_thresholds_min_greater_than_max() {
+        let args = vec![
+            String::new(),
+            String::from("--min-mem"),
+            String::from("3"),
+            String::from("--max-mem"),
+            String::from("2"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--min-mem' (3) is greater than '--max-mem' (2)"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_since() {
+        let args = vec![String::new(), String::from("--since"), String::from("1h")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.since, Some(Duration::from_hours(1)));
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn parse_since_units() {
+        assert_eq!(parse_since("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse_since("30m"), Ok(Duration::from_mins(30)));
+        assert_eq!(parse_since("2h"), Ok(Duration::from_hours(2)));
+        assert_eq!(parse_since("1d"), Ok(Duration::from_hours(24)));
+    }
+
+    #[test]
+    fn parse_since_invalid() {
+        for since in ["", "h", "1", "1w", "-1h", "1.5h", "h1"] {
+            assert_eq!(
+                parse_since(since),
+                Err(format!(
+                    "Invalid duration: '{since}' (expected e.g., 30m, 2h or 1d)"
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn parse_usage_threshold_bounds() {
+        assert_eq!(parse_usage_threshold("0", "CPU"), Ok(0.0));
+        assert_eq!(parse_usage_threshold("100", "CPU"), Ok(100.0));
+        assert_eq!(parse_usage_threshold("12.5", "CPU"), Ok(12.5));
+        for threshold in ["-0.1", "100.1", "abc", "NaN", ""] {
+            assert_eq!(
+                parse_usage_threshold(threshold, "CPU"),
+                Err(format!(
+                    "Invalid CPU threshold: '{threshold}' (expected 0 to 100)"
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn parse_top_invalid() {
+        for top in ["0", "-1", "1.5", "five", ""] {
+            assert_eq!(
+                parse_top(top),
+                Err(format!(
+                    "Invalid top: '{top}' (expected a number, at least 1)"
+                )),
+            );
+        }
+    }
+
+    #[test]
+    fn config_loopback_only_with_wildcard_only() {
+        let args = vec![
+            String::new(),
+            String::from("--loopback-only"),
+            String::from("--wildcard-only"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--loopback-only' and '--wildcard-only' cannot be combined"
+            ))
+        );
+    }
+
+    #[test]
+    fn version_info_regular() {
+        let version = version_info();
+        let mut lines = version.lines();
+
+        let (bin, number) = lines.next().unwrap().split_once(' ').unwrap();
+        assert_eq!(bin, "ports");
+        assert!(number.split('.').all(|part| part.parse::<u32>().is_ok()));
+        assert!(lines.next().unwrap().starts_with("target: "));
+        assert!(lines.next().unwrap().starts_with("features: "));
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn config_color() {
+        for (mode, expected) in [
+            ("always", ColorMode::Always),
+            ("auto", ColorMode::Auto),
+            ("never", ColorMode::Never),
+        ] {
+            let args = vec![String::new(), String::from("--color"), String::from(mode)].into_iter();
+            let config = Config::new(args).unwrap();
+
+            assert_eq!(config.color, expected);
+        }
+    }
+
+    #[test]
+    fn config_highlight() {
+        let args = vec![
+            String::new(),
+            String::from("--highlight"),
+            String::from("8080"),
+            String::from("--highlight"),
+            String::from("8000-8001"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.highlight_ports,
+            vec![
+                String::from("8080"),
+                String::from("8000"),
+                String::from("8001")
+            ]
+        );
+    }
+
+    #[test]
+    fn config_highlight_invalid() {
+        let args = vec![
+            String::new(),
+            String::from("--highlight"),
+            String::from("x"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Invalid port: 'x'")));
+    }
+
+    #[test]
+    fn config_group_by() {
+        for (group_by, expected) in [("user", GroupBy::User), ("command", GroupBy::Command)] {
+            let args = vec![
+                String::new(),
+                String::from("--group-by"),
+                String::from(group_by),
+            ]
+            .into_iter();
+            let config = Config::new(args).unwrap();
+
+            assert_eq!(config.group_by, Some(expected));
+        }
+    }
+
+    #[test]
+    fn config_group_by_unknown() {
+        let args = vec![
+            String::new(),
+            String::from("--group-by"),
+            String::from("pid"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown group: 'pid'")));
+    }
+
+    #[test]
+    fn config_summary_with_summary_by_command() {
+        let args = vec![
+            String::new(),
+            String::from("--summary"),
+            String::from("--summary-by-command"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--summary' and '--summary-by-command' cannot be combined"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_color_unknown() {
+        let args = vec![String::new(), String::from("--color"), String::from("yes")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown color mode: 'yes'")));
+    }
+
+    #[test]
+    fn config_color_never_not_even_on_terminal() {
+        let args = vec![
+            String::new(),
+            String::from("--color"),
+            String::from("never"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(!config.color.enabled(true));
+
+        // Not painted, so `render_table()` gets plain cells.
+        let command = String::from("sshd");
+        let pid = String::from("1174");
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &config,
+        );
+        assert!(!table.contains('\x1b'));
+    }
+
+    #[test]
+    fn parse_watch_interval_regular() {
+        assert_eq!(parse_watch_interval("1"), Ok(Duration::from_secs(1)));
+        assert_eq!(parse_watch_interval("30"), Ok(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn parse_watch_interval_invalid() {
+        for seconds in ["0", "-1", "1.5", "2s", ""] {
+            assert!(parse_watch_interval(seconds).is_err(), "{seconds}");
+        }
+    }
+
+    #[test]
+    fn config_include_udp() {
+        let args = vec![String::new(), String::from("--include-udp")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.include_udp);
+    }
+
+    #[test]
+    fn config_udp() {
+        let args = vec![String::new(), String::from("--udp")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.include_udp);
+    }
+
+    #[test]
+    fn config_unix() {
+        let args = vec![String::new(), String::from("--unix")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.unix);
+    }
+
+    #[test]
+    fn config_unix_with_capture() {
+        for input in ["--stdin", "--lsof-file"] {
+            let mut args = vec![String::new(), String::from("--unix")];
+            args.push(String::from(input));
+            if input == "--lsof-file" {
+                args.push(String::from("lsof.txt"));
+            }
+            let config = Config::new(args.into_iter());
+
+            assert_eq!(
+                config,
+                Err(format!("'--unix' and '{input}' cannot be combined"))
+            );
+        }
+    }
+
+    #[test]
+    fn config_include_pids_file() {
+        let path =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pids.txt");
+        let args = vec![
+            String::new(),
+            String::from("--include-pids-file"),
+            path.to_string_lossy().to_string(),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.pids_file, Some(path));
+        assert!(config.pid_filters.is_empty());
+    }
+
+    #[test]
+    fn config_pid() {
+        let args = vec![
+            String::new(),
+            String::from("--pid"),
+            String::from("723"),
+            String::from("--pid"),
+            String::from("1174"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.pid_filters,
+            vec![String::from("723"), String::from("1174")]
+        );
+    }
+
+    #[test]
+    fn config_pid_invalid() {
+        for pid in ["-1", "abc", "4294967296", ""] {
+            let args = vec![String::new(), String::from("--pid"), String::from(pid)].into_iter();
+            let config = Config::new(args);
+
+            assert_eq!(config, Err(format!("Invalid PID: '{pid}'")));
+        }
+    }
+
+    #[test]
+    fn config_user() {
+        let args = vec![
+            String::new(),
+            String::from("--user"),
+            String::from(" Root "),
+            String::from("--user"),
+            String::from("www-data"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.user_filters,
+            vec![String::from("root"), String::from("www-data")]
+        );
+    }
+
+    #[test]
+    fn config_exclude_user() {
+        let args = vec![
+            String::new(),
+            String::from("--exclude-user"),
+            String::from("Root"),
+            String::from("--exclude-user"),
+            String::from("nobody"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.excluded_users,
+            vec![String::from("root"), String::from("nobody")]
+        );
+    }
+
+    #[test]
+    fn config_exclude_command() {
+        let args = vec![
+            String::new(),
+            String::from("--exclude-command"),
+            String::from("ControlCe"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.excluded_commands, vec![String::from("controlce")]);
+    }
+
+    #[test]
+    fn config_exclude_command_empty() {
+        let args = vec![
+            String::new(),
+            String::from("--exclude-command"),
+            String::new(),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Empty command pattern")));
+    }
+
+    #[test]
+    fn config_user_empty() {
+        let args = vec![String::new(), String::from("--user"), String::from(" ")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Empty user name")));
+    }
+
+    #[test]
+    fn config_protocol() {
+        let args = vec![
+            String::new(),
+            String::from("--protocol"),
+            String::from("TCP6"),
+            String::from("-p"),
+            String::from("tcp"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.protocol_filters,
+            vec![String::from("tcp6"), String::from("tcp")]
+        );
+        assert!(!config.include_udp);
+    }
+
+    #[test]
+    fn config_protocol_udp_includes_udp() {
+        let args = vec![String::new(), String::from("-p"), String::from("udp6")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.include_udp);
+    }
+
+    #[test]
+    fn config_protocol_unknown() {
+        let args = vec![String::new(), String::from("-p"), String::from("sctp")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown protocol: 'sctp'")));
+    }
+
+    #[test]
+    fn config_protocol_empty() {
+        let args = vec![String::new(), String::from("--protocol"), String::new()].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown protocol: ''")));
+    }
+
+    #[test]
+    fn config_ipv4_ipv6() {
+        let args = vec![
+            String::new(),
+            String::from("--ipv4"),
+            String::from("--ipv6"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.ipv4);
+        assert!(config.ipv6);
+    }
+
+    #[test]
+    fn config_warn_no_results() {
+        let args = vec![String::new(), String::from("--warn-no-results")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.warn_no_results);
+    }
+
+    #[test]
+    fn config_aggregate_ports() {
+        let args = vec![String::new(), String::from("--aggregate-ports")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.aggregate_ports);
+    }
+
+    #[test]
+    fn config_service_names() {
+        let args = vec![String::new(), String::from("--service-names")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.service_names);
+    }
+
+    #[test]
+    fn config_service_names_with_port_alias() {
+        let args = vec![
+            String::new(),
+            String::from("--service-names"),
+            String::from("--port-alias"),
+            String::from("aliases.toml"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--service-names' and '--port-alias' cannot be combined"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_lsof_file_and_ps_file() {
+        let args = vec![
+            String::new(),
+            String::from("--lsof-file"),
+            String::from("lsof.txt"),
+            String::from("--ps-file"),
+            String::from("ps.txt"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.lsof_file, Some(PathBuf::from("lsof.txt")));
+        assert_eq!(config.ps_file, Some(PathBuf::from("ps.txt")));
+    }
+
+    #[test]
+    fn config_stdin_with_lsof_file() {
+        let args = vec![
+            String::new(),
+            String::from("--stdin"),
+            String::from("--lsof-file"),
+            String::from("lsof.txt"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--stdin' and '--lsof-file' cannot be combined"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_stdin_with_watch() {
+        let args = vec![
+            String::new(),
+            String::from("--stdin"),
+            String::from("--watch"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from("'--stdin' and '--watch' cannot be combined"))
+        );
+    }
+
+    #[test]
+    fn read_stdin_capture_regular() {
+        let stdin = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+";
+
+        let output = read_stdin_capture(stdin.as_bytes()).unwrap();
+        let listening_ports = Lsof::parse(&output, IncludeUdp::No).unwrap();
+
+        assert_eq!(listening_ports.len(), 1);
+        assert_eq!(listening_ports[0].pid, "1174");
+    }
+
+    #[test]
+    fn read_stdin_capture_missing_header() {
+        let stdin = "sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)\n";
+
+        let output = read_stdin_capture(stdin.as_bytes()).unwrap();
+        let res = Lsof::parse(&output, IncludeUdp::No).unwrap_err();
+
+        assert_eq!(
+            res.to_string(),
+            "The lsof output is missing expected properties."
+        );
+    }
+
+    #[test]
+    fn read_stdin_capture_empty() {
+        let output = read_stdin_capture(io::empty()).unwrap();
+        let res = Lsof::parse(&output, IncludeUdp::No).unwrap_err();
+
+        assert_eq!(res.to_string(), "The lsof output is missing the header.");
+    }
+
+    #[test]
+    fn config_output_file() {
+        let args = vec![
+            String::new(),
+            String::from("--output-file"),
+            String::from("ports.txt"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.output_file, Some(PathBuf::from("ports.txt")));
+    }
+
+    #[test]
+    fn config_port_alias() {
+        let args = vec![
+            String::new(),
+            String::from("--port-alias"),
+            String::from("aliases.toml"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.port_alias, Some(String::from("aliases.toml")));
+    }
+
+    #[test]
+    fn config_print0_pids() {
+        let args = vec![String::new(), String::from("--print0-pids")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.print0_pids);
+    }
+
+    #[test]
+    fn config_sort() {
+        let args = vec![String::new(), String::from("--sort"), String::from("port")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.sort_key, Some(SortKey::Port));
+        assert_eq!(config.mode, Mode::Regular);
+    }
+
+    #[test]
+    fn config_sort_process_info_enables_verbose() {
+        for key in ["cpu", "mem"] {
+            let args = vec![String::new(), String::from("--sort"), String::from(key)].into_iter();
+            let config = Config::new(args).unwrap();
+
+            assert_eq!(config.mode, Mode::Verbose);
+        }
+    }
+
+    #[test]
+    fn config_sort_process_info_does_not_decrease_verbosity() {
+        let args = vec![
+            String::new(),
+            String::from("-vvv"),
+            String::from("--sort"),
+            String::from("cpu"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::VeryVerbose);
+    }
+
+    #[test]
+    fn config_sort_unknown() {
+        let args = vec![String::new(), String::from("--sort"), String::from("time")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown sort key: 'time'")));
+    }
+
+    #[test]
+    fn config_reverse() {
+        let args = vec![String::new(), String::from("--reverse")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.reverse);
+    }
+
+    #[test]
+    fn config_strict_parse() {
+        let args = vec![String::new(), String::from("--strict-parse")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.strict_parse);
+    }
+
+    #[test]
+    fn config_print_args() {
+        let args = vec![
+            String::new(),
+            String::from("--print-args"),
+            String::from("80"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.print_args);
+        assert_eq!(config.filters, vec![String::from("80")]);
+    }
+
+    #[test]
+    fn shell_words_regular() {
+        let args = [
+            String::from("--verbose"),
+            String::from("--column-separator"),
+            String::from(" | "),
+            String::from("--since"),
+            String::from("1h30m"),
+            String::from("8000-8002"),
+        ];
+
+        assert_eq!(
+            shell_words(&args),
+            "--verbose --column-separator ' | ' --since 1h30m 8000-8002"
+        );
+    }
+
+    #[test]
+    fn shell_words_quotes_special_characters() {
+        let args = [String::new(), String::from("it's"), String::from("*:80")];
+
+        assert_eq!(shell_words(&args), r"'' 'it'\''s' '*:80'");
+    }
+
+    #[test]
+    fn config_show_ruser() {
+        let args = vec![String::new(), String::from("--show-ruser")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.show_ruser);
+    }
+
+    #[test]
+    fn config_column_separator() {
+        let args = vec![
+            String::new(),
+            String::from("--column-separator"),
+            String::from(" | "),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.column_separator, Some(String::from(" | ")));
+    }
+
+    #[test]
+    fn config_tab_separated_conflicts() {
+        for (option, value) in [("--column-separator", "|"), ("--group-by", "user")] {
+            let args = vec![
+                String::new(),
+                String::from("--tab-separated"),
+                String::from(option),
+                String::from(value),
+            ]
+            .into_iter();
+            let config = Config::new(args);
+
+            assert_eq!(
+                config,
+                Err(format!(
+                    "'--tab-separated' and '{option}' cannot be combined"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn config_resolve_hostnames() {
+        let args = vec![String::new(), String::from("--resolve-hostnames")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.resolve_hostnames);
+    }
+
+    #[test]
+    fn config_no_dns_overrides_resolve_hostnames() {
+        let args = vec![
+            String::new(),
+            String::from("--resolve-hostnames"),
+            String::from("--no-dns"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(!config.resolve_hostnames);
+    }
+
+    #[test]
+    fn config_resolve_service_names() {
+        let args = vec![String::new(), String::from("--resolve-service-names")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.resolve_service_names);
+    }
+
+    #[test]
+    fn config_resolve_service_names_with_capture() {
+        for input in ["--stdin", "--lsof-file"] {
+            let mut args = vec![String::new(), String::from("--resolve-service-names")];
+            args.push(String::from(input));
+            if input == "--lsof-file" {
+                args.push(String::from("lsof.txt"));
+            }
+            let config = Config::new(args.into_iter());
+
+            assert_eq!(
+                config,
+                Err(format!(
+                    "'--resolve-service-names' and '{input}' cannot be combined"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn config_resolve_service_names_with_port_alias() {
+        let args = vec![
+            String::new(),
+            String::from("--resolve-service-names"),
+            String::from("--port-alias"),
+            String::from("aliases.toml"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from(
+                "'--resolve-service-names' and '--port-alias' cannot be combined"
+            ))
+        );
+    }
+
+    #[test]
+    fn config_resolve_hostnames_with_capture() {
+        for input in ["--stdin", "--lsof-file"] {
+            let mut args = vec![String::new(), String::from("--resolve-hostnames")];
+            args.push(String::from(input));
+            if input == "--lsof-file" {
+                args.push(String::from("lsof.txt"));
+            }
+            let config = Config::new(args.into_iter());
+
+            assert_eq!(
+                config,
+                Err(format!(
+                    "'--resolve-hostnames' and '{input}' cannot be combined"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn config_no_align() {
+        let args = vec![String::new(), String::from("--no-align")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert!(config.no_align);
+    }
+
+    #[test]
+    fn config_column_separator_missing_value() {
+        let args = vec![String::new(), String::from("--column-separator")].into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from("Missing value for '--column-separator'"))
+        );
+    }
+
+    #[test]
+    fn config_format_ansible_vars() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("ansible-vars"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::AnsibleVars));
+    }
+
+    #[test]
+    fn config_format_env() {
+        let args = vec![String::new(), String::from("--format"), String::from("env")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::Env));
+    }
+
+    #[test]
+    fn config_format_graphql() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("graphql"),
+            String::from("--graphql-field-name"),
+            String::from("ports"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::GraphQl));
+        assert_eq!(config.graphql_field_name, Some(String::from("ports")));
+    }
+
+    #[test]
+    fn config_format_k8s_labels() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("k8s-labels"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::K8sLabels));
+    }
+
+    #[test]
+    fn config_format_nginx_upstream() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("nginx-upstream"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::NginxUpstream));
+    }
+
+    #[test]
+    fn config_format_openmetrics() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("openmetrics"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::OpenMetrics));
+    }
+
+    #[test]
+    fn config_format_rust_debug() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("rust-debug"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::RustDebug));
+    }
+
+    #[test]
+    fn config_format_rust_pretty() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("rust-pretty"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::RustPretty));
+    }
+
+    #[test]
+    fn config_format_systemd_socket() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("systemd-socket"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.format, Some(OutputFormat::SystemdSocket));
+    }
+
+    #[test]
+    fn config_format_unknown() {
+        let args = vec![String::new(), String::from("--format"), String::from("xml")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'xml'"));
+    }
+
+    #[test]
+    fn config_format_template() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("{pid}: {name}"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(
+            config.format,
+            Some(OutputFormat::Template(
+                Template::parse("{pid}: {name}").unwrap()
+            ))
+        );
+        assert_eq!(config.mode, Mode::Regular);
+    }
+
+    #[test]
+    fn config_format_template_with_process_info_is_verbose() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("{pid} {pinfo.command}"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_format_template_unknown_placeholder() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("{pid}: {port}"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(config, Err(String::from("Unknown placeholder: '{port}'")));
+    }
+
+    #[test]
+    fn config_format_missing_value() {
+        let args = vec![String::new(), String::from("--format")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'--format'"));
+    }
+
+    #[test]
+    fn config_bad_argument() {
+        let args = vec![String::new(), String::from("--abcdef")].into_iter();
+        let error = Config::new(args).unwrap_err();
+
+        assert!(error.contains("'--abcdef'"));
+    }
+
+    fn new_template_port() -> ListeningPort {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.user = String::from("quentin");
+        pinfo.pid = String::from("1337");
+        pinfo.ppid = String::from("1");
+        pinfo.pc_cpu = String::from("1.5");
+        pinfo.pc_mem = String::from("0.3");
+        pinfo.vsz = String::from("31836");
+        pinfo.rss = String::from("24064");
+        pinfo.start = String::from("09:27");
+        pinfo.time = String::from("0:02");
+        pinfo.command = String::from("python3 -m http.server");
+        let mut port = ListeningPort::new();
+        port.command = String::from("python3");
+        port.pid = String::from("1337");
+        port.user = String::from("quentin");
+        port.type_ = String::from("IPv4");
+        port.node = String::from("TCP");
+        port.name = String::from("*:8000");
+        port.pinfo = Some(pinfo);
+        port
+    }
+
+    #[test]
+    fn template_each_placeholder() {
+        let port = new_template_port();
+
+        for (placeholder, value) in [
+            ("{command}", "python3"),
+            ("{pid}", "1337"),
+            ("{user}", "quentin"),
+            ("{type}", "IPv4"),
+            ("{node}", "TCP"),
+            ("{name}", "*:8000"),
+            ("{pinfo.user}", "quentin"),
+            ("{pinfo.pid}", "1337"),
+            ("{pinfo.ppid}", "1"),
+            ("{pinfo.pc_cpu}", "1.5"),
+            ("{pinfo.pc_mem}", "0.3"),
+            ("{pinfo.vsz}", "31836"),
+            ("{pinfo.rss}", "24064"),
+            ("{pinfo.start}", "09:27"),
+            ("{pinfo.time}", "0:02"),
+            ("{pinfo.command}", "python3 -m http.server"),
+        ] {
+            let template = Template::parse(placeholder).unwrap();
+            assert_eq!(template.render(&port), value, "{placeholder}");
+        }
+    }
+
+    #[test]
+    fn template_literals_and_escaped_braces() {
+        let template = Template::parse("{{{pid}}} on {name}!").unwrap();
+
+        assert_eq!(template.render(&new_template_port()), "{1337} on *:8000!");
+    }
+
+    #[test]
+    fn template_missing_process_info_is_empty() {
+        let mut port = new_template_port();
+        port.pinfo = None;
+        let template = Template::parse("{pid}:{pinfo.command}:{pinfo.pc_cpu}").unwrap();
+
+        assert_eq!(template.render(&port), "1337::");
+    }
+
+    #[test]
+    fn template_unknown_placeholder() {
+        assert_eq!(
+            Template::parse("{pid} {pinfo.tty}"),
+            Err(String::from("Unknown placeholder: '{pinfo.tty}'"))
+        );
+        assert_eq!(
+            Template::parse("{}"),
+            Err(String::from("Unknown placeholder: '{}'"))
+        );
+    }
+
+    #[test]
+    fn template_unbalanced_braces() {
+        assert_eq!(
+            Template::parse("{pid"),
+            Err(String::from("Unclosed '{' in format: '{pid'"))
+        );
+        assert_eq!(
+            Template::parse("pid}"),
+            Err(String::from("Unmatched '}' in format: 'pid}'"))
+        );
+    }
+
+    #[test]
+    fn template_needs_process_info() {
+        assert!(!Template::parse("{pid} {name}")
+            .unwrap()
+            .needs_process_info());
+        assert!(Template::parse("{pinfo.start}")
+            .unwrap()
+            .needs_process_info());
+    }
+
+    #[test]
+    fn formatter_template_one_line_per_port() {
+        let formatter = TemplateFormatter {
+            template: Template::parse("{pid} {name}").unwrap(),
+            terminator: '\n',
+        };
+        let mut other = new_template_port();
+        other.pid = String::from("42");
+        other.name = String::from("127.0.0.1:5432");
+
+        let output = formatter.format(&[new_template_port(), other]);
+
+        assert_eq!(output, "1337 *:8000\n42 127.0.0.1:5432\n");
+    }
+
+    #[test]
+    fn formatter_template_null_terminated() {
+        let args = vec![
+            String::new(),
+            String::from("--format"),
+            String::from("{pinfo.command}"),
+            String::from("--null"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+        let mut multiline = new_template_port();
+        if let Some(pinfo) = multiline.pinfo.as_mut() {
+            pinfo.command = String::from("sh -c 'a\nb'");
+        }
+
+        let output = formatter(&config)
+            .unwrap()
+            .format(&[new_template_port(), multiline]);
+
+        assert_eq!(output, "python3 -m http.server\0sh -c 'a\nb'\0");
+    }
+
+    #[test]
+    fn config_null_without_template() {
+        for args in [
+            vec![String::new(), String::from("--null")],
+            vec![
+                String::new(),
+                String::from("--null"),
+                String::from("--format"),
+                String::from("env"),
+            ],
+        ] {
+            let config = Config::new(args.into_iter());
+
+            assert_eq!(
+                config,
+                Err(String::from("'--null' requires a '--format' template"))
+            );
+        }
+    }
+
+    #[test]
+    fn formatter_ansible_vars() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:1337");
+
+        let config = Config {
+            format: Some(OutputFormat::AnsibleVars),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[port]);
+
+        assert!(output.starts_with("listening_ports:\n"));
+        assert!(output.contains("    port: 1337\n"));
+    }
+
+    #[test]
+    fn formatter_env() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:1337");
+
+        let config = Config {
+            format: Some(OutputFormat::Env),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[port]);
+
+        assert!(output.contains("PORT_1337_NAME='*:1337'\n"));
+    }
+
+    #[test]
+    fn formatter_graphql() {
+        let config = Config {
+            format: Some(OutputFormat::GraphQl),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[]);
+
+        assert_eq!(output, "{\"data\":{\"listeningPorts\":[]}}\n");
+    }
+
+    #[test]
+    fn formatter_graphql_custom_field_name() {
+        let config = Config {
+            format: Some(OutputFormat::GraphQl),
+            graphql_field_name: Some(String::from("ports")),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[]);
+
+        assert_eq!(output, "{\"data\":{\"ports\":[]}}\n");
+    }
+
+    #[test]
+    fn formatter_k8s_labels() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.name = String::from("*:1337");
+
+        let config = Config {
+            format: Some(OutputFormat::K8sLabels),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[port]);
+
+        assert_eq!(output, "app=nginx,port in (1337)\n");
+    }
+
+    #[test]
+    fn formatter_nginx_upstream() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.name = String::from("*:1337");
+
+        let config = Config {
+            format: Some(OutputFormat::NginxUpstream),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[port]);
+
+        assert_eq!(output, "upstream nginx {\n    server 127.0.0.1:1337;\n}\n");
+    }
+
+    #[test]
+    fn delimited_table_separator_between_columns() {
+        let (command, pid, name) = (
+            String::from("nginx"),
+            String::from("1234"),
+            String::from("*:80"),
+        );
+
+        let table = delimited_table(
+            &["COMMAND", "PID", "HOST:PORT"],
+            &[
+                fmt::Alignment::Left,
+                fmt::Alignment::Right,
+                fmt::Alignment::Right,
+            ],
+            &[vec![&command, &pid, &name]],
+            " | ",
+        );
+
+        for line in table.lines() {
+            assert_eq!(line.matches(" | ").count(), 2);
+        }
+    }
+
+    #[test]
+    fn delimited_table_aligns_columns() {
+        let (command, pid, name) = (
+            String::from("nginx"),
+            String::from("1"),
+            String::from("*:80"),
+        );
+
+        let table = delimited_table(
+            &["COMMAND", "PID", "HOST:PORT"],
+            &[
+                fmt::Alignment::Left,
+                fmt::Alignment::Right,
+                fmt::Alignment::Right,
+            ],
+            &[vec![&command, &pid, &name]],
+            "|",
+        );
+
+        assert_eq!(
+            table,
+            "\
+COMMAND|PID|HOST:PORT
+nginx  |  1|     *:80
+"
+        );
+    }
+
+    fn new_column_port() -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.pid = String::from("1234");
+        port.user = String::from("root");
+        port.type_ = String::from("IPv4");
+        port.node = String::from("TCP");
+        port.name = String::from("*:80");
+        let mut pinfo = ProcessInfo::new();
+        pinfo.ruser = String::from("www-data");
+        pinfo.ppid = String::from("1");
+        pinfo.pc_cpu = String::from("1.5");
+        pinfo.vsz = String::from("10640");
+        pinfo.rss = String::from("2048");
+        pinfo.command = String::from("nginx: master process");
+        port.pinfo = Some(pinfo);
+        port
+    }
+
+    #[test]
+    fn table_rows_vsz_and_rss() {
+        let listening_ports = [new_column_port()];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Vsz, Column::Rss],
+            None,
+            &[],
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["10640", "2048"]]);
+    }
+
+    #[test]
+    fn config_columns_vsz_and_rss() {
+        let args = vec![
+            String::new(),
+            String::from("--columns"),
+            String::from("pid,vsz,rss"),
+        ]
+        .into_iter();
+        let config = Config::new(args).unwrap();
+
+        assert_eq!(config.columns, vec![Column::Pid, Column::Vsz, Column::Rss]);
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn table_rows_ppid() {
+        let mut without_pinfo = new_column_port();
+        without_pinfo.pinfo = None;
+        let listening_ports = [new_column_port(), without_pinfo];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Pid, Column::Ppid],
+            None,
+            &[],
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["1234", "1"], vec!["1234", ""]]);
+        assert_eq!(Column::from_name("ppid"), Some(Column::Ppid));
+        assert!(Column::Ppid.needs_process_info());
+    }
+
+    #[test]
+    fn table_rows_selection() {
+        let listening_ports = [new_column_port()];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Pid, Column::Name],
+            None,
+            &[],
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["1234", "*:80"]]);
+    }
+
+    #[test]
+    fn table_rows_reorder() {
+        let listening_ports = [new_column_port()];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[
+                Column::Name,
+                Column::Cpu,
+                Column::Command,
+                Column::FullCommand,
+            ],
+            None,
+            &[],
+            &empty,
+        );
+
+        assert_eq!(
+            rows,
+            vec![vec!["*:80", "1.5", "nginx", "nginx: master process"]]
+        );
+    }
+
+    #[test]
+    fn table_rows_alias_and_ruser() {
+        let listening_ports = [new_column_port()];
+        let aliases = AliasMap::parse("80 = web").unwrap();
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Alias, Column::Ruser],
+            Some(&aliases),
+            &[],
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["web", "www-data"]]);
+    }
+
+    #[test]
+    fn table_rows_port() {
+        let listening_ports = [new_column_port()];
+        let port_numbers = [String::from("80")];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Name, Column::Port],
+            None,
+            &port_numbers,
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["*:80", "80"]]);
+    }
+
+    #[test]
+    fn table_rows_missing_values_are_empty() {
+        let mut port = new_column_port();
+        port.pinfo = None;
+        let listening_ports = [port];
+        let empty = String::new();
+
+        let rows = table_rows(
+            &listening_ports,
+            &[Column::Alias, Column::Ruser, Column::Port, Column::Mem],
+            None,
+            &[],
+            &empty,
+        );
+
+        assert_eq!(rows, vec![vec!["", "", "", ""]]);
+    }
+
+    #[test]
+    fn column_names_round_trip() {
+        for column in Column::ALL {
+            assert_eq!(Column::from_name(column.name()), Some(column));
+        }
+    }
+
+    #[test]
+    fn print_table_output_file() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let config = Config {
+            no_align: true,
+            ..Config::default()
+        };
+        let file = tempfile::NamedTempFile::new().unwrap();
+        let mut out = Writer::File(file.reopen().unwrap());
+
+        print_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &TableExtras::default(),
+            &config,
+            &mut out,
+        )
+        .unwrap();
+        out.flush().unwrap();
+
+        let content = fs::read_to_string(file.path()).unwrap();
+        assert_eq!(content, "COMMAND PID\nnginx 1\n");
+    }
+
+    #[test]
+    fn writer_is_stdout() {
+        assert!(Writer::Stdout(io::stdout()).is_stdout());
+
+        let file = tempfile::tempfile().unwrap();
+        assert!(!Writer::File(file).is_stdout());
+    }
+
+    #[test]
+    fn render_table_no_header() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let config = Config {
+            no_header: true,
+            no_align: true,
+            ..Config::default()
+        };
+
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &config,
+        );
+
+        assert_eq!(table.lines().next(), Some("nginx 1"));
+    }
+
+    #[test]
+    fn render_table_no_header_delimited() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let config = Config {
+            no_header: true,
+            column_separator: Some(String::from(" | ")),
+            ..Config::default()
+        };
+
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &config,
+        );
+
+        assert_eq!(table, "nginx   |   1\n");
+    }
+
+    #[test]
+    fn render_table_with_header() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let config = Config {
+            no_align: true,
+            ..Config::default()
+        };
+
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &config,
+        );
+
+        assert_eq!(table.lines().next(), Some("COMMAND PID"));
+    }
+
+    #[test]
+    fn render_table_tab_separated() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let (command_2, pid_2) = (String::from("sshd"), String::from("1174"));
+        let config = Config {
+            tab_separated: true,
+            ..Config::default()
+        };
+
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid], vec![&command_2, &pid_2]],
+            &config,
+        );
+
+        assert_eq!(table, "COMMAND\tPID\nnginx\t1\nsshd\t1174\n");
+    }
+
+    #[test]
+    fn render_table_tab_separated_no_header() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+        let config = Config {
+            tab_separated: true,
+            no_header: true,
+            ..Config::default()
+        };
+
+        let table = render_table(
+            &["COMMAND", "PID"],
+            &[fmt::Alignment::Left, fmt::Alignment::Right],
+            &[vec![&command, &pid]],
+            &config,
+        );
+
+        assert_eq!(table, "nginx\t1\n");
+    }
+
+    #[test]
+    fn paint_columns_keeps_alignment() {
+        let (command, pid, user) = (
+            String::from("nginx"),
+            String::from("1"),
+            String::from("www"),
+        );
+        let (command_2, pid_2) = (String::from("sshd"), String::from("1174"));
+        let config = Config {
+            column_separator: Some(String::from(" | ")),
+            ..Config::default()
+        };
+        let alignments = [
+            fmt::Alignment::Left,
+            fmt::Alignment::Right,
+            fmt::Alignment::Left,
+        ];
+
+        let (headers, rows) = paint_columns(
+            &["COMMAND", "PID", "USER"],
+            &alignments,
+            &[vec![&command, &pid, &user], vec![&command_2, &pid_2, &user]],
+            &[Column::Command, Column::Pid, Column::User],
+            &config,
+        );
+        let headers: Vec<&str> = headers.iter().map(String::as_str).collect();
+        let rows: Vec<Vec<&String>> = rows.iter().map(|row| row.iter().collect()).collect();
+        let table = render_table(&headers, &alignments, &rows, &config);
+
+        assert_eq!(
+            table,
+            "\
+\x1b[1mCOMMAND\x1b[0m |  \x1b[33mPID\x1b[0m | USER
+\x1b[1mnginx\x1b[0m   |    \x1b[33m1\x1b[0m | www
+\x1b[1msshd\x1b[0m    | \x1b[33m1174\x1b[0m | www
+"
+        );
+    }
+
+    #[test]
+    fn paint_columns_no_align() {
+        let (command, user) = (String::from("nginx"), String::from("www"));
+        let config = Config {
+            no_align: true,
+            ..Config::default()
+        };
+
+        let (headers, rows) = paint_columns(
+            &["COMMAND", "USER"],
+            &[fmt::Alignment::Left, fmt::Alignment::Left],
+            &[vec![&command, &user]],
+            &[Column::Command, Column::User],
+            &config,
+        );
+
+        assert_eq!(headers, vec!["\x1b[1mCOMMAND\x1b[0m", "USER"]);
+        assert_eq!(rows, vec![vec!["\x1b[1mnginx\x1b[0m", "www"]]);
+    }
+
+    #[test]
+    fn is_highlighted_regular() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("127.0.0.1:8080");
+
+        assert!(is_highlighted(&port, &[String::from("8080")]));
+        assert!(!is_highlighted(&port, &[String::from("80")]));
+        assert!(!is_highlighted(&port, &[]));
+    }
+
+    #[test]
+    fn is_highlighted_aggregated() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:5432, [::1]:8080");
+
+        assert!(is_highlighted(&port, &[String::from("8080")]));
+    }
+
+    #[test]
+    fn highlight_rows_regular() {
+        let table = String::from("COMMAND PID\nnginx 1\nsshd 2\n");
+
+        let table = highlight_rows(table, &[false, true], &Config::default());
+
+        assert_eq!(table, "COMMAND PID\nnginx 1\n\x1b[7msshd 2\x1b[0m\n");
+    }
+
+    #[test]
+    fn highlight_rows_no_header() {
+        let config = Config {
+            no_header: true,
+            ..Config::default()
+        };
+        let table = String::from("nginx 1\nsshd 2\n");
+
+        let table = highlight_rows(table, &[true, false], &config);
+
+        assert_eq!(table, "\x1b[7mnginx 1\x1b[0m\nsshd 2\n");
+    }
+
+    #[test]
+    fn highlight_rows_none() {
+        let table = String::from("COMMAND PID\nnginx 1\n");
+
+        assert_eq!(
+            highlight_rows(table.clone(), &[], &Config::default()),
+            table
+        );
+        assert_eq!(
+            highlight_rows(table.clone(), &[false], &Config::default()),
+            table
+        );
+    }
+
+    #[test]
+    fn group_ports_by_user_regular() {
+        let new_port = |user: &str, pid: &str| {
+            let mut port = ListeningPort::new();
+            port.user = String::from(user);
+            port.pid = String::from(pid);
+            port
+        };
+        let listening_ports = vec![
+            new_port("www-data", "1"),
+            new_port("root", "2"),
+            new_port("www-data", "3"),
+        ];
+
+        let groups = group_ports_by_user(listening_ports);
+
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["root", "www-data"]);
+        assert_eq!(pids(&groups["root"]), vec!["2"]);
+        assert_eq!(pids(&groups["www-data"]), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn group_ports_by_command_regular() {
+        let new_port = |command: &str, pid: &str| {
+            let mut port = ListeningPort::new();
+            port.command = String::from(command);
+            port.pid = String::from(pid);
+            port
+        };
+        let listening_ports = vec![
+            new_port("sshd", "1"),
+            new_port("nginx", "2"),
+            new_port("nginx", "3"),
+        ];
+
+        let groups = group_ports_by_command(listening_ports);
+
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["nginx", "sshd"]);
+        assert_eq!(pids(&groups["nginx"]), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn group_ports_by_command_uses_process_name() {
+        let mut port = ListeningPort::new();
+        port.command = String::from("docker-pr");
+        let mut pinfo = ProcessInfo::new();
+        pinfo.command = String::from("/usr/bin/docker-proxy -proto tcp");
+        port.pinfo = Some(pinfo);
+
+        let groups = group_ports_by_command(vec![port]);
+
+        assert_eq!(groups.keys().collect::<Vec<_>>(), vec!["docker-proxy"]);
+    }
+
+    #[test]
+    fn group_ports_empty() {
+        assert!(group_ports_by_user(Vec::new()).is_empty());
+    }
+
+    #[test]
+    fn group_headers_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.user = String::from("root");
+        let port_2 = port_1.clone();
+        let mut port_3 = ListeningPort::new();
+        port_3.user = String::from("www-data");
+
+        let groups = group_headers(&[port_1, port_2, port_3], GroupBy::User);
+
+        assert_eq!(
+            groups,
+            vec![(String::from("root"), 2), (String::from("www-data"), 1)]
+        );
+    }
+
+    #[test]
+    fn insert_group_headers_regular() {
+        let table = String::from("COMMAND USER\nsshd root\ncupsd root\nnginx www\n");
+        let groups = [(String::from("root"), 2), (String::from("www"), 1)];
+
+        let table = insert_group_headers(table, &groups, &Config::default());
+
+        assert_eq!(
+            table,
+            "COMMAND USER\nroot:\nsshd root\ncupsd root\nwww:\nnginx www\n"
+        );
+    }
+
+    #[test]
+    fn insert_group_headers_skips_empty_groups() {
+        let config = Config {
+            no_header: true,
+            ..Config::default()
+        };
+        let table = String::from("nginx www\n");
+        let groups = [(String::from("root"), 0), (String::from("www"), 1)];
+
+        let table = insert_group_headers(table, &groups, &config);
+
+        assert_eq!(table, "www:\nnginx www\n");
+    }
+
+    #[test]
+    fn unaligned_table_no_padding() {
+        let (command, pid, name) = (
+            String::from("nginx"),
+            String::from("1"),
+            String::from("*:80"),
+        );
+
+        let table = unaligned_table(
+            &["COMMAND", "PID", "HOST:PORT"],
+            &[vec![&command, &pid, &name]],
+            " ",
+        );
+
+        assert_eq!(
+            table,
+            "\
+COMMAND PID HOST:PORT
+nginx 1 *:80
+"
+        );
+    }
+
+    #[test]
+    fn unaligned_table_custom_separator() {
+        let (command, pid) = (String::from("nginx"), String::from("1"));
+
+        let table = unaligned_table(&["COMMAND", "PID"], &[vec![&command, &pid]], ",");
+
+        assert_eq!(table, "COMMAND,PID\nnginx,1\n");
+    }
+
+    #[test]
+    fn formatter_openmetrics() {
+        let config = Config {
+            format: Some(OutputFormat::OpenMetrics),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[]);
+
+        assert!(output.ends_with("# EOF\n"));
+    }
+
+    #[test]
+    fn formatter_rust_debug() {
+        let config = Config {
+            format: Some(OutputFormat::RustDebug),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[ListeningPort::new()]);
+
+        assert!(output.starts_with("ListeningPort {"));
+    }
+
+    #[test]
+    fn formatter_rust_pretty() {
+        let config = Config {
+            format: Some(OutputFormat::RustPretty),
+            ..Config::default()
+        };
+
+        let output = formatter(&config).unwrap().format(&[ListeningPort::new()]);
+
+        assert!(output.starts_with("ListeningPort {\n"));
+    }
+
+    #[test]
+    fn formatter_systemd_socket() {
+        let config = Config {
+            format: Some(OutputFormat::SystemdSocket),
+            ..Config::default()
+        };
+
+        let mut port = ListeningPort::new();
+        port.command = String::from("nginx");
+        port.node = String::from("TCP");
+        port.name = String::from("*:80");
+        let output = formatter(&config).unwrap().format(&[port]);
+
+        assert!(output.starts_with("# nginx.socket\n"));
+    }
+
+    #[test]
+    fn formatter_none() {
+        assert!(formatter(&Config::default()).is_none());
+    }
+
+    #[test]
+    fn filter_ports_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:1337");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("127.0.0.1:1337");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("[::1]:1337");
+        let mut port_4 = ListeningPort::new();
+        port_4.name = String::from("[::]:42069");
+        let mut port_5 = ListeningPort::new();
+        port_5.name = String::from("42069");
+
+        let mut port_6 = ListeningPort::new();
+        port_6.name = String::new();
+        let mut port_7 = ListeningPort::new();
+        port_7.name = String::from("abc");
+        let mut port_8 = ListeningPort::new();
+        port_8.name = String::from("def:");
+
+        let mut listening_ports = vec![
+            port_1.clone(),
+            port_2.clone(),
+            port_3.clone(),
+            port_4.clone(),
+            port_5.clone(),
+            port_6.clone(),
+            port_7.clone(),
+            port_8.clone(),
+        ];
+
+        let filtered_out = filter_ports(
+            &mut listening_ports,
+            &[String::from("1337"), String::from("42069")],
+            &ServiceNames::default(),
+        );
+
+        assert_eq!(filtered_out, 3);
+        assert!(listening_ports.contains(&port_1));
+        assert!(listening_ports.contains(&port_2));
+        assert!(listening_ports.contains(&port_3));
+        assert!(listening_ports.contains(&port_4));
+        assert!(listening_ports.contains(&port_5));
+
+        assert!(!listening_ports.contains(&port_6));
+        assert!(!listening_ports.contains(&port_7));
+        assert!(!listening_ports.contains(&port_8));
+    }
+
+    #[test]
+    fn filter_ports_service_names() {
+        let services = ServiceNames::parse("http 80/tcp\nhttps 443/tcp\n");
+
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:http");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("[::1]:http");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("127.0.0.1:80");
+
+        let mut port_4 = ListeningPort::new();
+        port_4.name = String::from("*:https");
+        let mut port_5 = ListeningPort::new();
+        port_5.name = String::from("*:unknown");
+
+        let mut listening_ports = vec![
+            port_1.clone(),
+            port_2.clone(),
+            port_3.clone(),
+            port_4,
+            port_5,
+        ];
+
+        let filtered_out = filter_ports(&mut listening_ports, &[String::from("80")], &services);
+
+        assert_eq!(filtered_out, 2);
+        assert_eq!(listening_ports, vec![port_1, port_2, port_3]);
+    }
+
+    #[test]
+    fn filter_ports_service_names_not_looked_up() {
+        let mut port = ListeningPort::new();
+        port.name = String::from("*:http");
+
+        let mut listening_ports = vec![port];
+
+        filter_ports(
+            &mut listening_ports,
+            &[String::from("80")],
+            &ServiceNames::default(),
+        );
+
+        assert!(listening_ports.is_empty());
+    }
+
+    #[test]
+    fn exclude_ports_service_names() {
+        let services = ServiceNames::parse("http 80/tcp\n");
+
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:http");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("*:8080");
+
+        let mut listening_ports = vec![port_1, port_2.clone()];
+
+        exclude_ports(&mut listening_ports, &[String::from("80")], &services);
+
+        assert_eq!(listening_ports, vec![port_2]);
+    }
+
+    #[test]
+    fn filter_ports_empty() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:1337");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("127.0.0.1:1337");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("[::1]:1337");
+
+        let mut listening_ports = vec![port_1, port_2, port_3];
+
+        let filtered_out = filter_ports(&mut listening_ports, &[], &ServiceNames::default());
+
+        assert_eq!(filtered_out, 3);
+        // This is correct. We happen to treat 'no-filters' as
+        // 'keep-everything', but this is not `filter_ports()`' problem.
+        assert!(listening_ports.is_empty());
+    }
+
+    #[test]
+    fn filtered_out_footer_plural() {
+        assert_eq!(filtered_out_footer(1), "1 port filtered out");
+        assert_eq!(filtered_out_footer(3), "3 ports filtered out");
+    }
+
+    #[test]
+    fn exclude_ports_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:22");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("[::1]:5432");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("127.0.0.1:8080");
+
+        let mut listening_ports = vec![port_1, port_2, port_3.clone()];
+
+        exclude_ports(
+            &mut listening_ports,
+            &[String::from("22"), String::from("5432")],
+            &ServiceNames::default(),
+        );
+
+        assert_eq!(listening_ports, vec![port_3]);
+    }
+
+    #[test]
+    fn exclude_ports_takes_precedence_over_filter_ports() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:8000");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("*:8001");
+
+        let mut listening_ports = vec![port_1.clone(), port_2];
+
+        filter_ports(
+            &mut listening_ports,
+            &[String::from("8000"), String::from("8001")],
+            &ServiceNames::default(),
+        );
+        exclude_ports(
+            &mut listening_ports,
+            &[String::from("8001")],
+            &ServiceNames::default(),
+        );
+
+        assert_eq!(listening_ports, vec![port_1]);
+    }
+
+    #[test]
+    fn summarize_by_user_fixture() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lsof.txt");
+        let fixture = fs::read_to_string(fixture).unwrap();
+        let listening_ports = Lsof::parse(&fixture, IncludeUdp::No).unwrap();
+
+        let summary = summarize_by_user(&listening_ports);
+
+        assert_eq!(
+            summary,
+            vec![
+                (String::from("root"), 64),
+                (String::from("quentin"), 4),
+                (String::from("systemd-resolve"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarize_by_user_ties_by_name() {
+        let new_port = |user: &str| {
+            let mut port = ListeningPort::new();
+            port.user = String::from(user);
+            port
+        };
+        let listening_ports = [
+            new_port("www-data"),
+            new_port("postgres"),
+            new_port("root"),
+            new_port("root"),
+        ];
+
+        assert_eq!(
+            summarize_by_user(&listening_ports),
+            vec![
+                (String::from("root"), 2),
+                (String::from("postgres"), 1),
+                (String::from("www-data"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarize_by_command_ties_and_order() {
+        let new_port = |command: &str| {
+            let mut port = ListeningPort::new();
+            port.command = String::from(command);
+            port
+        };
+        let listening_ports = [
+            new_port("sshd"),
+            new_port("nginx"),
+            new_port("docker-pr"),
+            new_port("nginx"),
+            new_port("docker-pr"),
+            new_port("cupsd"),
+            new_port("docker-pr"),
+        ];
+
+        assert_eq!(
+            summarize_by_command(&listening_ports),
+            vec![
+                (String::from("docker-pr"), 3),
+                (String::from("nginx"), 2),
+                (String::from("cupsd"), 1),
+                (String::from("sshd"), 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn summarize_by_user_empty() {
+        assert!(summarize_by_user(&[]).is_empty());
+    }
+
+    #[test]
+    fn count_output_zero() {
+        assert_eq!(count_output(&[]), "0\n");
+    }
+
+    #[test]
+    fn count_output_one() {
+        assert_eq!(count_output(&[ListeningPort::new()]), "1\n");
+    }
+
+    #[test]
+    fn count_output_many() {
+        let listening_ports = vec![ListeningPort::new(); 42];
+
+        assert_eq!(count_output(&listening_ports), "42\n");
+    }
+
+    #[test]
+    fn quiet_output_one_per_line() {
+        let mut port_1 = ListeningPort::new();
+        port_1.command = String::from("nginx");
+        port_1.name = String::from("*:80");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("127.0.0.1:5432");
+
+        assert_eq!(quiet_output(&[port_1, port_2]), "*:80\n127.0.0.1:5432\n");
+        assert_eq!(quiet_output(&[]), "");
+    }
+
+    #[test]
+    fn config_quiet_with_verbose() {
+        let args = vec![String::new(), String::from("--quiet"), String::from("-vv")].into_iter();
+        let config = Config::new(args).unwrap();
+
+        // Verbose is ignored by the output, not an error.
+        assert!(config.quiet);
+        assert_eq!(config.mode, Mode::Verbose);
+    }
+
+    #[test]
+    fn config_quiet_with_format() {
+        let args = vec![
+            String::new(),
+            String::from("--quiet"),
+            String::from("--format"),
+            String::from("env"),
+        ]
+        .into_iter();
+        let config = Config::new(args);
+
+        assert_eq!(
+            config,
+            Err(String::from("'--quiet' and '--format' cannot be combined"))
+        );
+    }
+
+    fn new_diff_port(command: &str, pid: &str, name: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.command = String::from(command);
+        port.pid = String::from(pid);
+        port.name = String::from(name);
+        port
+    }
+
+    #[test]
+    fn diff_ports_regular() {
+        let old = [
+            new_diff_port("nginx", "1234", "*:80"),
+            new_diff_port("sshd", "42", "*:22"),
+        ];
+        let new = [
+            new_diff_port("nginx", "1234", "*:80"),
+            new_diff_port("python3", "5678", "127.0.0.1:8000"),
+        ];
+
+        let (added, removed) = diff_ports(&old, &new);
+
+        assert_eq!(added, vec![&new[1]]);
+        assert_eq!(removed, vec![&old[1]]);
+    }
+
+    #[test]
+    fn diff_ports_no_changes() {
+        let ports = [new_diff_port("nginx", "1234", "*:80")];
+
+        let (added, removed) = diff_ports(&ports, &ports);
+
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn diff_ports_first_poll() {
+        let new = [new_diff_port("nginx", "1234", "*:80")];
+
+        let (added, removed) = diff_ports(&[], &new);
+
+        assert_eq!(added, vec![&new[0]]);
+        assert!(removed.is_empty());
+    }
+
+    #[test]
+    fn diff_ports_restart_on_same_port() {
+        let old = [new_diff_port("nginx", "1234", "*:80")];
+        let new = [new_diff_port("nginx", "1300", "*:80")];
+
+        let (added, removed) = diff_ports(&old, &new);
+
+        assert_eq!(added, vec![&new[0]]);
+        assert_eq!(removed, vec![&old[0]]);
+    }
+
+    #[test]
+    fn diff_line_regular() {
+        let port = new_diff_port("nginx", "1234", "*:80");
+
+        assert_eq!(diff_line('+', &port), "+ nginx 1234 *:80");
+        assert_eq!(diff_line('-', &port), "- nginx 1234 *:80");
+    }
+
+    #[test]
+    fn aggregate_ports_regular() {
+        let new_port = |command: &str, pid: &str, type_: &str, name: &str| {
+            let mut port = ListeningPort::new();
+            port.command = String::from(command);
+            port.pid = String::from(pid);
+            port.type_ = String::from(type_);
+            port.node = String::from("TCP");
+            port.name = String::from(name);
+            port
+        };
+
+        let listening_ports = vec![
+            new_port("postgres", "42", "IPv4", "*:5432"),
+            new_port("sshd", "1174", "IPv4", "*:22"),
+            new_port("postgres", "42", "IPv4", "127.0.0.1:5432"),
+            new_port("postgres", "42", "IPv6", "[::1]:5432"),
+        ];
+
+        let aggregated: Vec<ListeningPort> = aggregate_ports(listening_ports)
+            .into_iter()
+            .map(AggregatedPort::into_listening_port)
+            .collect();
+
+        assert_eq!(
+            aggregated,
+            vec![
+                new_port(
+                    "postgres",
+                    "42",
+                    "IPv4, IPv6",
+                    "*:5432, 127.0.0.1:5432, [::1]:5432"
+                ),
+                new_port("sshd", "1174", "IPv4", "*:22"),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregate_ports_same_command_different_pid() {
+        let mut port_1 = ListeningPort::new();
+        port_1.command = String::from("nginx");
+        port_1.pid = String::from("1");
+        let mut port_2 = ListeningPort::new();
+        port_2.command = String::from("nginx");
+        port_2.pid = String::from("2");
+
+        let aggregated = aggregate_ports(vec![port_1, port_2]);
+
+        assert_eq!(aggregated.len(), 2);
+    }
+
+    fn new_sortable_port(id: &str, name: &str, cpu: &str, mem: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        // Same value in all text fields, to sort on any of them.
+        port.command = String::from(id);
+        port.pid = String::from(id);
+        port.user = String::from(id);
+        port.name = String::from(name);
+        let mut pinfo = ProcessInfo::new();
+        pinfo.pc_cpu = String::from(cpu);
+        pinfo.pc_mem = String::from(mem);
+        port.pinfo = Some(pinfo);
+        port
+    }
+
+    fn pids(listening_ports: &[ListeningPort]) -> Vec<&str> {
+        listening_ports
+            .iter()
+            .map(|port| port.pid.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn sort_ports_port() {
+        let mut listening_ports = vec![
+            new_sortable_port("1", "*:http", "", ""),
+            new_sortable_port("2", "*:8080", "", ""),
+            new_sortable_port("3", "[::1]:443", "", ""),
+            new_sortable_port("4", "*:80", "", ""),
+        ];
+
+        sort_ports(&mut listening_ports, &SortKey::Port);
+
+        assert_eq!(pids(&listening_ports), vec!["4", "3", "2", "1"]);
+    }
+
+    #[test]
+    fn sort_ports_pid_is_numeric() {
+        let mut listening_ports = vec![
+            new_sortable_port("100", "*:80", "", ""),
+            new_sortable_port("20", "*:80", "", ""),
+            new_sortable_port("3", "*:80", "", ""),
+        ];
+
+        sort_ports(&mut listening_ports, &SortKey::Pid);
+
+        assert_eq!(pids(&listening_ports), vec!["3", "20", "100"]);
+    }
+
+    #[test]
+    fn sort_ports_command() {
+        let mut listening_ports = vec![
+            new_sortable_port("sshd", "*:22", "", ""),
+            new_sortable_port("nginx", "*:80", "", ""),
+        ];
+
+        sort_ports(&mut listening_ports, &SortKey::Command);
+
+        assert_eq!(pids(&listening_ports), vec!["nginx", "sshd"]);
+    }
+
+    #[test]
+    fn sort_ports_user() {
+        let mut listening_ports = vec![
+            new_sortable_port("root", "*:22", "", ""),
+            new_sortable_port("dev", "*:80", "", ""),
+        ];
+
+        sort_ports(&mut listening_ports, &SortKey::User);
+
+        assert_eq!(pids(&listening_ports), vec!["dev", "root"]);
+    }
+
+    #[test]
+    fn sort_ports_cpu() {
+        let mut listening_ports = vec![
+            new_sortable_port("1", "*:80", "10.5", ""),
+            new_sortable_port("2", "*:80", "2.0", ""),
+            new_sortable_port("3", "*:80", "", ""),
+        ];
+
+        sort_ports(&mut listening_ports, &SortKey::Cpu);
+
+        // Missing values first.
+        assert_eq!(pids(&listening_ports), vec!["3", "2", "1"]);
+    }
+
+    #[test]
+    fn sort_ports_mem() {
+        let mut listening_ports = vec![
+            new_sortable_port("1", "*:80", "", "0.3"),
+            new_sortable_port("2", "*:80", "", "0.1"),
+        ];
+        listening_ports.push(ListeningPort::new()); // No process info.
+
+        sort_ports(&mut listening_ports, &SortKey::Mem);
+
+        assert_eq!(pids(&listening_ports), vec!["", "2", "1"]);
+    }
+
+    #[test]
+    fn sort_ports_is_stable() {
+        let mut listening_ports = vec![
+            new_sortable_port("1", "*:80", "1.0", "1.0"),
+            new_sortable_port("2", "*:80", "1.0", "1.0"),
+            new_sortable_port("3", "*:80", "1.0", "1.0"),
+        ];
+        // Same port, CPU and memory.
+        for key in [SortKey::Port, SortKey::Cpu, SortKey::Mem] {
+            sort_ports(&mut listening_ports, &key);
+
+            assert_eq!(pids(&listening_ports), vec!["1", "2", "3"]);
+        }
+    }
+
+    #[test]
+    fn order_ports_sort_without_reverse() {
+        let mut listening_ports = vec![
+            new_sortable_port("2", "*:80", "", ""),
+            new_sortable_port("1", "*:22", "", ""),
+        ];
+
+        order_ports(&mut listening_ports, Some(&SortKey::Pid), false);
+
+        assert_eq!(pids(&listening_ports), vec!["1", "2"]);
+    }
+
+    #[test]
+    fn order_ports_reverse_without_sort() {
+        let mut listening_ports = vec![
+            new_sortable_port("2", "*:80", "", ""),
+            new_sortable_port("1", "*:22", "", ""),
+            new_sortable_port("3", "*:443", "", ""),
+        ];
+
+        order_ports(&mut listening_ports, None, true);
+
+        assert_eq!(pids(&listening_ports), vec!["3", "1", "2"]);
+    }
+
+    #[test]
+    fn order_ports_reverse_every_sort_key() {
+        for key in [
+            SortKey::Port,
+            SortKey::Pid,
+            SortKey::Command,
+            SortKey::User,
+            SortKey::Cpu,
+            SortKey::Mem,
+        ] {
+            // Ascending order is 1, 2, 3 for every key.
+            let mut listening_ports = vec![
+                new_sortable_port("2", "*:80", "2.0", "2.0"),
+                new_sortable_port("3", "*:443", "3.0", "3.0"),
+                new_sortable_port("1", "*:22", "1.0", "1.0"),
+            ];
+
+            order_ports(&mut listening_ports, Some(&key), true);
+
+            assert_eq!(pids(&listening_ports), vec!["3", "2", "1"], "{key:?}");
+        }
+    }
+
+    #[test]
+    fn keep_top_truncates_to_n() {
+        let mut listening_ports = vec![
+            new_sortable_port("3", "*:443", "3.0", "3.0"),
+            new_sortable_port("2", "*:80", "2.0", "2.0"),
+            new_sortable_port("1", "*:22", "1.0", "1.0"),
+        ];
 
-    let empty = String::new();
-    let listening_ports: Vec<Vec<&String>> = listening_ports
-        .iter()
-        .map(|port| {
-            vec![
-                &port.command,
-                &port.pid,
-                &port.user,
-                &port.type_,
-                &port.node,
-                &port.name,
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.pc_cpu),
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.pc_mem),
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.start),
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.time),
-                port.pinfo.as_ref().map_or_else(|| &empty, |p| &p.command),
-            ]
-        })
-        .collect();
+        keep_top(&mut listening_ports, 2);
 
-    Table::new()
-        .headers(&[
-            "COMMAND",
-            "PID",
-            "USER",
-            "TYPE",
-            "NODE",
-            "HOST:PORT",
-            "%CPU",
-            "%MEM",
-            "START",
-            "TIME",
-            "COMMAND",
-        ])
-        .alignments(&[
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Left,
-            fmt::Alignment::Right,
-            fmt::Alignment::Right,
-            fmt::Alignment::Right,
-            fmt::Alignment::Right,
-            fmt::Alignment::Right,
-            fmt::Alignment::Left,
-        ])
-        .data(&listening_ports)
-        .output_paged();
+        assert_eq!(pids(&listening_ports), vec!["3", "2"]);
+    }
 
-    Ok(())
-}
+    #[test]
+    fn keep_top_after_reverse() {
+        let mut listening_ports = vec![
+            new_sortable_port("2", "*:80", "2.0", "2.0"),
+            new_sortable_port("3", "*:443", "3.0", "3.0"),
+            new_sortable_port("1", "*:22", "1.0", "1.0"),
+        ];
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        order_ports(&mut listening_ports, Some(&SortKey::Cpu), true);
+        keep_top(&mut listening_ports, 1);
+
+        assert_eq!(pids(&listening_ports), vec!["3"]);
+    }
 
     #[test]
-    fn config_no_args() {
-        let args = vec![String::new()].into_iter();
-        let config = Config::new(args).unwrap();
+    fn keep_top_more_than_total() {
+        let mut listening_ports = vec![
+            new_sortable_port("2", "*:80", "", ""),
+            new_sortable_port("1", "*:22", "", ""),
+        ];
 
-        assert_eq!(
-            config,
-            Config {
-                help: false,
-                version: false,
-                mode: Mode::Regular,
-                filters: Vec::new(),
-            }
-        );
+        keep_top(&mut listening_ports, 5);
+
+        assert_eq!(pids(&listening_ports), vec!["2", "1"]);
     }
 
     #[test]
-    fn config_with_bin_path() {
-        let args = vec![String::from("/usr/local/bin/ports")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn pid_only_output_unique() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("723");
+        port_1.name = String::from("*:5353");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("1174");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("723");
+        port_3.name = String::from("[::]:5353");
+
+        let output = pid_only_output(&[port_1, port_2, port_3]);
+
+        assert_eq!(output, "723\n1174\n");
+        assert_eq!(pid_only_output(&[]), "");
+    }
+
+    #[test]
+    fn kill_targets_unique_pids() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("723");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("1174");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("723");
+
+        assert_eq!(kill_targets(&[port_1, port_2, port_3]), vec![723, 1174]);
+    }
+
+    #[test]
+    fn kill_targets_skips_invalid_pids() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("0");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("abc");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("42");
+
+        assert_eq!(kill_targets(&[port_1, port_2, port_3]), vec![42]);
+    }
 
+    #[test]
+    fn kill_prompt_lists_pids() {
+        assert_eq!(kill_prompt(&[42]), "Send SIGTERM to PID 42? [y/N] ");
         assert_eq!(
-            config,
-            Config {
-                help: false,
-                version: false,
-                mode: Mode::Regular,
-                filters: Vec::new(),
-            }
+            kill_prompt(&[723, 1174]),
+            "Send SIGTERM to PIDs 723, 1174? [y/N] "
         );
     }
 
     #[test]
-    fn config_help_full() {
-        let args = vec![String::new(), String::from("--help")].into_iter();
+    fn is_confirmed_answers() {
+        for answer in ["y\n", "Y", "yes\n", " YES "] {
+            assert!(is_confirmed(answer), "{answer:?}");
+        }
+        for answer in ["", "\n", "n\n", "no", "yep", "nope"] {
+            assert!(!is_confirmed(answer), "{answer:?}");
+        }
+    }
+
+    #[test]
+    fn config_kill() {
+        let args = vec![
+            String::new(),
+            String::from("8080"),
+            String::from("--kill"),
+            String::from("--force"),
+        ]
+        .into_iter();
         let config = Config::new(args).unwrap();
 
-        assert!(config.help);
+        assert!(config.kill);
+        assert!(config.force);
     }
 
     #[test]
-    fn config_help_short() {
-        let args = vec![String::new(), String::from("-h")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn config_kill_errors() {
+        for (args, error) in [
+            (vec!["--force"], "'--force' requires '--kill'"),
+            (
+                vec!["--kill", "--watch"],
+                "'--kill' and '--watch' cannot be combined",
+            ),
+            (
+                vec!["--kill", "--stdin"],
+                "'--kill' and '--stdin' cannot be combined",
+            ),
+            (
+                vec!["--kill", "--lsof-file", "lsof.txt"],
+                "'--kill' and '--lsof-file' cannot be combined",
+            ),
+        ] {
+            let args = std::iter::once(String::new()).chain(args.into_iter().map(String::from));
+            let config = Config::new(args);
 
-        assert!(config.help);
+            assert_eq!(config, Err(String::from(error)));
+        }
     }
 
     #[test]
-    fn config_version_full() {
-        let args = vec![String::new(), String::from("--version")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn config_kill_requires_filter() {
+        for args in [
+            vec!["--kill"],
+            vec!["--kill", "--force"],
+            vec!["--kill", "--user", "root"],
+        ] {
+            let args = std::iter::once(String::new()).chain(args.into_iter().map(String::from));
+            let config = Config::new(args);
 
-        assert!(config.version);
+            assert_eq!(
+                config,
+                Err(String::from("'--kill' requires a port or a '--pid' filter"))
+            );
+        }
     }
 
     #[test]
-    fn config_version_short() {
-        let args = vec![String::new(), String::from("-v")].into_iter();
+    fn config_kill_with_pid_filter() {
+        let args = vec![
+            String::new(),
+            String::from("--pid"),
+            String::from("723"),
+            String::from("--kill"),
+        ]
+        .into_iter();
         let config = Config::new(args).unwrap();
 
-        assert!(config.version);
+        assert!(config.kill);
     }
 
     #[test]
-    fn config_regular() {
-        let args = vec![String::new()].into_iter();
+    fn config_kill_with_pids_file() {
+        let args = vec![
+            String::new(),
+            String::from("--include-pids-file"),
+            String::from("pids.txt"),
+            String::from("--kill"),
+        ]
+        .into_iter();
         let config = Config::new(args).unwrap();
 
-        assert_eq!(config.mode, Mode::Regular);
+        assert!(config.kill);
+    }
+
+    #[test]
+    fn config_kill_is_never_serialized() {
+        let config = Config {
+            kill: true,
+            force: true,
+            ..Config::default()
+        };
+
+        assert!(config.serialize_to_args().is_empty());
+    }
+
+    #[test]
+    fn unique_pids_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("723");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("1174");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("723");
+
+        let listening_ports = [port_1, port_2, port_3];
+
+        let pids = unique_pids(&listening_ports);
+
+        assert_eq!(pids, vec!["723", "1174"]);
+        // No trailing NUL.
+        assert_eq!(pids.join("\0"), "723\x001174");
+    }
+
+    #[test]
+    fn filter_pids_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("723");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("1174");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("7230");
+
+        let mut listening_ports = vec![port_1.clone(), port_2, port_3];
+
+        filter_pids(&mut listening_ports, &[String::from("723")]);
+
+        assert_eq!(listening_ports, vec![port_1]);
+    }
+
+    #[test]
+    fn filter_pids_multiple() {
+        let mut port_1 = ListeningPort::new();
+        port_1.pid = String::from("723");
+        let mut port_2 = ListeningPort::new();
+        port_2.pid = String::from("1174");
+        let mut port_3 = ListeningPort::new();
+        port_3.pid = String::from("2673");
+
+        let mut listening_ports = vec![port_1.clone(), port_2.clone(), port_3];
+
+        filter_pids(
+            &mut listening_ports,
+            &[String::from("1174"), String::from("723")],
+        );
+
+        assert_eq!(listening_ports, vec![port_1, port_2]);
+    }
+
+    #[test]
+    fn filter_users_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.user = String::from("root");
+        let mut port_2 = ListeningPort::new();
+        port_2.user = String::from("www-data");
+        let mut port_3 = ListeningPort::new();
+        port_3.user = String::from("rootless");
+
+        let mut listening_ports = vec![port_1.clone(), port_2, port_3];
+
+        filter_users(&mut listening_ports, &[String::from("root")]);
+
+        assert_eq!(listening_ports, vec![port_1]);
+    }
+
+    #[test]
+    fn filter_users_no_match() {
+        let mut port = ListeningPort::new();
+        port.user = String::from("root");
+
+        let mut listening_ports = vec![port];
+
+        filter_users(&mut listening_ports, &[String::from("nobody")]);
+
+        assert!(listening_ports.is_empty());
+    }
+
+    #[test]
+    fn filter_users_multiple() {
+        let mut port_1 = ListeningPort::new();
+        port_1.user = String::from("root");
+        let mut port_2 = ListeningPort::new();
+        port_2.user = String::from("www-data");
+        let mut port_3 = ListeningPort::new();
+        port_3.user = String::from("postgres");
+
+        let mut listening_ports = vec![port_1.clone(), port_2.clone(), port_3];
+
+        filter_users(
+            &mut listening_ports,
+            &[String::from("www-data"), String::from("root")],
+        );
+
+        assert_eq!(listening_ports, vec![port_1, port_2]);
+    }
+
+    #[test]
+    fn filter_users_case_insensitive() {
+        let mut port = ListeningPort::new();
+        port.user = String::from("Admin");
+
+        let mut listening_ports = vec![port.clone()];
+
+        filter_users(&mut listening_ports, &[String::from("admin")]);
+
+        assert_eq!(listening_ports, vec![port]);
+    }
+
+    #[test]
+    fn exclude_users_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.user = String::from("Root");
+        let mut port_2 = ListeningPort::new();
+        port_2.user = String::from("www-data");
+
+        let mut listening_ports = vec![port_1, port_2.clone()];
+
+        exclude_users(&mut listening_ports, &[String::from("root")]);
+
+        assert_eq!(listening_ports, vec![port_2]);
+    }
+
+    #[test]
+    fn exclude_users_takes_precedence_over_filter_ports() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:80");
+        port_1.user = String::from("root");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("*:8000");
+        port_2.user = String::from("alice");
+
+        let mut listening_ports = vec![port_1, port_2.clone()];
+
+        filter_ports(
+            &mut listening_ports,
+            &[String::from("80"), String::from("8000")],
+            &ServiceNames::default(),
+        );
+        exclude_users(&mut listening_ports, &[String::from("root")]);
+
+        assert_eq!(listening_ports, vec![port_2]);
+    }
+
+    fn new_command_port(command: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.command = String::from(command);
+        port
+    }
+
+    #[test]
+    fn exclude_commands_single() {
+        let mut listening_ports = vec![new_command_port("rapportd"), new_command_port("nginx")];
+
+        exclude_commands(&mut listening_ports, &[String::from("rapport")]);
+
+        assert_eq!(listening_ports, vec![new_command_port("nginx")]);
+    }
+
+    #[test]
+    fn exclude_commands_multiple() {
+        let mut listening_ports = vec![
+            new_command_port("rapportd"),
+            new_command_port("ControlCe"),
+            new_command_port("nginx"),
+        ];
+
+        exclude_commands(
+            &mut listening_ports,
+            &[String::from("rapportd"), String::from("controlce")],
+        );
+
+        assert_eq!(listening_ports, vec![new_command_port("nginx")]);
+    }
+
+    #[test]
+    fn exclude_commands_composes_with_filters() {
+        let mut port_1 = new_command_port("nginx");
+        port_1.name = String::from("*:80");
+        port_1.user = String::from("root");
+        let mut port_2 = new_command_port("rapportd");
+        port_2.name = String::from("*:80");
+        port_2.user = String::from("root");
+        let mut port_3 = new_command_port("nginx");
+        port_3.name = String::from("*:8080");
+        port_3.user = String::from("root");
+
+        let mut listening_ports = vec![port_1.clone(), port_2, port_3];
+
+        filter_ports(
+            &mut listening_ports,
+            &[String::from("80")],
+            &ServiceNames::default(),
+        );
+        filter_users(&mut listening_ports, &[String::from("root")]);
+        exclude_commands(&mut listening_ports, &[String::from("rapportd")]);
+
+        assert_eq!(listening_ports, vec![port_1]);
+    }
+
+    fn new_cpu_port(pid: &str, cpu: &str) -> ListeningPort {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.pc_cpu = String::from(cpu);
+        let mut port = ListeningPort::new();
+        port.pid = String::from(pid);
+        port.pinfo = Some(pinfo);
+        port
+    }
+
+    #[test]
+    fn filter_usage_cpu_boundaries() {
+        let mut listening_ports = vec![
+            new_cpu_port("1", "0.9"),
+            new_cpu_port("2", "1.0"),
+            new_cpu_port("3", "5.0"),
+            new_cpu_port("4", "5.1"),
+        ];
+
+        filter_usage(
+            &mut listening_ports,
+            ProcessInfo::cpu_float,
+            Some(1.0),
+            Some(5.0),
+        );
+
+        assert_eq!(pids(&listening_ports), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn filter_usage_cpu_min_only() {
+        let mut listening_ports = vec![new_cpu_port("1", "0.0"), new_cpu_port("2", "100.0")];
+
+        filter_usage(
+            &mut listening_ports,
+            ProcessInfo::cpu_float,
+            Some(50.0),
+            None,
+        );
+
+        assert_eq!(pids(&listening_ports), vec!["2"]);
+    }
+
+    #[test]
+    fn filter_usage_cpu_without_process_info() {
+        let mut port = ListeningPort::new();
+        port.pid = String::from("1");
+        let mut listening_ports = vec![port, new_cpu_port("2", ""), new_cpu_port("3", "0.0")];
+
+        filter_usage(
+            &mut listening_ports,
+            ProcessInfo::cpu_float,
+            None,
+            Some(100.0),
+        );
+
+        assert_eq!(pids(&listening_ports), vec!["3"]);
+    }
+
+    #[test]
+    fn filter_usage_mem() {
+        let new_mem_port = |pid: &str, mem: &str| {
+            let mut port = new_cpu_port(pid, "50.0");
+            if let Some(pinfo) = port.pinfo.as_mut() {
+                pinfo.pc_mem = String::from(mem);
+            }
+            port
+        };
+        let mut listening_ports = vec![
+            new_mem_port("1", "0.1"),
+            new_mem_port("2", "2.0"),
+            new_mem_port("3", "2.1"),
+        ];
+
+        filter_usage(
+            &mut listening_ports,
+            ProcessInfo::mem_float,
+            None,
+            Some(2.0),
+        );
+
+        assert_eq!(pids(&listening_ports), vec!["1", "2"]);
     }
 
     #[test]
-    fn config_verbose_full() {
-        let args = vec![String::new(), String::from("--verbose")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_usage_mem_without_process_info() {
+        let mut port = ListeningPort::new();
+        port.pid = String::from("1");
+        let mut listening_ports = vec![port];
 
-        assert_eq!(config.mode, Mode::Verbose);
+        filter_usage(
+            &mut listening_ports,
+            ProcessInfo::mem_float,
+            Some(0.0),
+            None,
+        );
+
+        assert!(listening_ports.is_empty());
     }
 
-    #[test]
-    fn config_verbose_short() {
-        let args = vec![String::new(), String::from("-vv")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn new_started_port(pid: &str, start: &str) -> ListeningPort {
+        let mut pinfo = ProcessInfo::new();
+        pinfo.start = String::from(start);
+        let mut port = ListeningPort::new();
+        port.pid = String::from(pid);
+        port.pinfo = Some(pinfo);
+        port
+    }
 
-        assert_eq!(config.mode, Mode::Verbose);
+    // Wednesday, July 17th 2024, 15:30.
+    fn since_now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 7, 17, 15, 30, 0).unwrap()
     }
 
     #[test]
-    fn config_verbose_over_verbose_is_no_op() {
-        let args = vec![
-            String::new(),
-            String::from("--verbose"),
-            String::from("--verbose"),
-        ]
-        .into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_since_time_of_day() {
+        let mut listening_ports = vec![
+            new_started_port("1", "14:29"),
+            new_started_port("2", "14:30"),
+            new_started_port("3", "15:10"),
+        ];
 
-        assert_eq!(config.mode, Mode::Verbose);
+        filter_since(&mut listening_ports, Duration::from_hours(1), &since_now());
+
+        assert_eq!(pids(&listening_ports), vec!["2", "3"]);
     }
 
     #[test]
-    fn config_very_verbose_full() {
-        let args = vec![String::new(), String::from("--very-verbose")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_since_time_of_day_yesterday() {
+        let now = Local.with_ymd_and_hms(2024, 7, 17, 9, 0, 0).unwrap();
+        let mut listening_ports = vec![
+            // Yesterday, 9h10 ago (not in 14h50).
+            new_started_port("1", "23:50"),
+            new_started_port("2", "08:30"),
+        ];
 
-        assert_eq!(config.mode, Mode::VeryVerbose);
+        filter_since(&mut listening_ports, Duration::from_hours(1), &now);
+
+        assert_eq!(pids(&listening_ports), vec!["2"]);
     }
 
     #[test]
-    fn config_very_verbose_short() {
-        let args = vec![String::new(), String::from("-vvv")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_since_month_and_day() {
+        let mut listening_ports = vec![
+            new_started_port("1", "Jul12"),
+            new_started_port("2", "Jul16"),
+            new_started_port("3", "09:27"),
+        ];
 
-        assert_eq!(config.mode, Mode::VeryVerbose);
+        filter_since(&mut listening_ports, Duration::from_hours(48), &since_now());
+
+        assert_eq!(pids(&listening_ports), vec!["2", "3"]);
     }
 
     #[test]
-    fn config_very_verbose_gt_verbose() {
-        let args = vec![
-            String::new(),
-            String::from("--verbose"),
-            String::from("--very-verbose"),
-            String::from("--verbose"),
-        ]
-        .into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_since_without_start_time() {
+        let mut no_pinfo = ListeningPort::new();
+        no_pinfo.pid = String::from("1");
+        let mut listening_ports = vec![no_pinfo, new_started_port("2", "garbage")];
 
-        assert_eq!(config.mode, Mode::VeryVerbose);
+        filter_since(&mut listening_ports, Duration::from_hours(1), &since_now());
+
+        assert!(listening_ports.is_empty());
     }
 
     #[test]
-    fn config_very_verbose_over_very_verbose_is_no_op() {
-        let args = vec![
-            String::new(),
-            String::from("--very-verbose"),
-            String::from("--very-verbose"),
-        ]
-        .into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_loopback_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:80");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("127.0.0.1:8000");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("0.0.0.0:5432");
+        let mut port_4 = ListeningPort::new();
+        port_4.name = String::from("[::1]:631");
 
-        assert_eq!(config.mode, Mode::VeryVerbose);
-    }
+        let mut listening_ports = vec![port_1, port_2.clone(), port_3, port_4.clone()];
 
-    #[test]
-    fn config_filters() {
-        let args = vec![String::new(), String::from("1337"), String::from("42069")].into_iter();
-        let config = Config::new(args).unwrap();
+        filter_loopback(&mut listening_ports);
 
-        assert_eq!(
-            config.filters,
-            &[String::from("1337"), String::from("42069")]
-        );
+        assert_eq!(listening_ports, vec![port_2, port_4]);
     }
 
     #[test]
-    fn config_filters_invalid_too_low() {
-        let args = vec![String::new(), String::from("-1")].into_iter();
-        let error = Config::new(args).unwrap_err();
+    fn filter_wildcard_regular() {
+        let mut port_1 = ListeningPort::new();
+        port_1.name = String::from("*:80");
+        let mut port_2 = ListeningPort::new();
+        port_2.name = String::from("127.0.0.1:8000");
+        let mut port_3 = ListeningPort::new();
+        port_3.name = String::from("0.0.0.0:5432");
+        let mut port_4 = ListeningPort::new();
+        port_4.name = String::from("[::1]:631");
 
-        assert!(error.contains("'-1'"));
-    }
+        let mut listening_ports = vec![port_1.clone(), port_2, port_3.clone(), port_4];
 
-    #[test]
-    fn config_filters_invalid_too_high() {
-        let args = vec![String::new(), String::from("65536")].into_iter();
-        let error = Config::new(args).unwrap_err();
+        filter_wildcard(&mut listening_ports);
 
-        assert!(error.contains("'65536'"));
+        assert_eq!(listening_ports, vec![port_1, port_3]);
     }
 
-    #[test]
-    fn config_filters_invalid_not_a_number() {
-        let args = vec![String::new(), String::from("123nan")].into_iter();
-        let error = Config::new(args).unwrap_err();
-
-        assert!(error.contains("'123nan'"));
+    fn new_protocol_port(node: &str, type_: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.node = String::from(node);
+        port.type_ = String::from(type_);
+        port
     }
 
     #[test]
-    fn config_range_filters_regular() {
-        let args = vec![String::new(), String::from("1000-1005")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_protocols_each_protocol() {
+        for (protocol, expected) in [
+            ("tcp", vec![("TCP", "IPv4"), ("TCP", "IPv6")]),
+            ("udp", vec![("UDP", "IPv4"), ("UDP", "IPv6")]),
+            ("tcp4", vec![("TCP", "IPv4")]),
+            ("udp4", vec![("UDP", "IPv4")]),
+            ("tcp6", vec![("TCP", "IPv6")]),
+            ("udp6", vec![("UDP", "IPv6")]),
+        ] {
+            let mut listening_ports = vec![
+                new_protocol_port("TCP", "IPv4"),
+                new_protocol_port("TCP", "IPv6"),
+                new_protocol_port("UDP", "IPv4"),
+                new_protocol_port("UDP", "IPv6"),
+            ];
 
-        assert_eq!(
-            config.filters,
-            &[
-                String::from("1000"),
-                String::from("1001"),
-                String::from("1002"),
-                String::from("1003"),
-                String::from("1004"),
-                String::from("1005"),
-            ]
-        );
+            filter_protocols(&mut listening_ports, &[String::from(protocol)]);
+
+            let expected: Vec<ListeningPort> = expected
+                .into_iter()
+                .map(|(node, type_)| new_protocol_port(node, type_))
+                .collect();
+            assert_eq!(listening_ports, expected, "{protocol}");
+        }
     }
 
     #[test]
-    fn config_range_filters_end_first() {
-        let args = vec![String::new(), String::from("1005-1000")].into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_protocols_multiple() {
+        let mut listening_ports = vec![
+            new_protocol_port("TCP", "IPv4"),
+            new_protocol_port("UDP", "IPv4"),
+            new_protocol_port("TCP", "IPv6"),
+        ];
 
-        assert_eq!(
-            config.filters,
-            &[
-                String::from("1000"),
-                String::from("1001"),
-                String::from("1002"),
-                String::from("1003"),
-                String::from("1004"),
-                String::from("1005"),
-            ]
+        filter_protocols(
+            &mut listening_ports,
+            &[String::from("tcp6"), String::from("udp")],
         );
-    }
-
-    #[test]
-    fn config_range_filters_multiple_ranges() {
-        let args = vec![
-            String::new(),
-            String::from("1000-1005"),
-            String::from("40000-40003"),
-        ]
-        .into_iter();
-        let config = Config::new(args).unwrap();
 
         assert_eq!(
-            config.filters,
-            &[
-                String::from("1000"),
-                String::from("1001"),
-                String::from("1002"),
-                String::from("1003"),
-                String::from("1004"),
-                String::from("1005"),
-                String::from("40000"),
-                String::from("40001"),
-                String::from("40002"),
-                String::from("40003"),
+            listening_ports,
+            vec![
+                new_protocol_port("UDP", "IPv4"),
+                new_protocol_port("TCP", "IPv6")
             ]
         );
     }
 
     #[test]
-    fn config_range_filters_with_simple_filter() {
-        let args = vec![
-            String::new(),
-            String::from("8000"),
-            String::from("1005-1000"),
-        ]
-        .into_iter();
-        let config = Config::new(args).unwrap();
+    fn filter_protocols_fixture() {
+        let fixture =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/lsof.txt");
+        let fixture = fs::read_to_string(fixture).unwrap();
+        let all_ports = Lsof::parse(&fixture, IncludeUdp::Yes).unwrap();
 
-        assert_eq!(
-            config.filters,
-            &[
-                String::from("8000"),
-                String::from("1000"),
-                String::from("1001"),
-                String::from("1002"),
-                String::from("1003"),
-                String::from("1004"),
-                String::from("1005"),
-            ]
-        );
-    }
+        let mut tcp6 = all_ports.clone();
+        filter_protocols(&mut tcp6, &[String::from("tcp6")]);
+        assert!(tcp6.iter().any(|x| x.pid == "816" && x.name == "[::1]:631"));
+        assert!(tcp6.iter().all(|x| x.node == "TCP" && x.type_ == "IPv6"));
 
-    #[test]
-    fn config_range_filters_range_equals() {
-        let args = vec![String::new(), String::from("1000-1000")].into_iter();
-        let config = Config::new(args).unwrap();
+        let mut udp6 = all_ports.clone();
+        filter_protocols(&mut udp6, &[String::from("udp6")]);
+        assert!(udp6.iter().any(|x| x.pid == "723" && x.name == "*:5353"));
+        assert!(udp6.iter().all(|x| x.node == "UDP" && x.type_ == "IPv6"));
 
-        assert_eq!(config.filters, &[String::from("1000"),]);
+        let mut tcp4 = all_ports;
+        filter_protocols(&mut tcp4, &[String::from("tcp4")]);
+        assert!(tcp4
+            .iter()
+            .any(|x| x.pid == "816" && x.name == "127.0.0.1:631"));
+        assert!(tcp4.iter().all(|x| x.node == "TCP" && x.type_ == "IPv4"));
     }
 
-    #[test]
-    fn config_range_filters_invalid_too_low() {
-        let args = vec![String::new(), String::from("-1-10")].into_iter();
-        let error = Config::new(args).unwrap_err();
-
-        assert!(error.contains("'-1-10'"));
+    fn new_family_port(type_: &str) -> ListeningPort {
+        let mut port = ListeningPort::new();
+        port.type_ = String::from(type_);
+        port
     }
 
     #[test]
-    fn config_range_filters_invalid_too_high() {
-        let args = vec![String::new(), String::from("65530-65536")].into_iter();
-        let error = Config::new(args).unwrap_err();
+    fn filter_address_families_ipv4() {
+        let mut listening_ports = vec![new_family_port("IPv4"), new_family_port("IPv6")];
 
-        assert!(error.contains("'65530-65536'"));
+        filter_address_families(&mut listening_ports, true, false);
+
+        assert_eq!(listening_ports, vec![new_family_port("IPv4")]);
     }
 
     #[test]
-    fn config_bad_argument() {
-        let args = vec![String::new(), String::from("--abcdef")].into_iter();
-        let error = Config::new(args).unwrap_err();
+    fn filter_address_families_ipv6() {
+        let mut listening_ports = vec![new_family_port("IPv4"), new_family_port("IPv6")];
 
-        assert!(error.contains("'--abcdef'"));
+        filter_address_families(&mut listening_ports, false, true);
+
+        assert_eq!(listening_ports, vec![new_family_port("IPv6")]);
     }
 
     #[test]
-    fn filter_ports_regular() {
-        let mut port_1 = ListeningPort::new();
-        port_1.name = String::from("*:1337");
-        let mut port_2 = ListeningPort::new();
-        port_2.name = String::from("127.0.0.1:1337");
-        let mut port_3 = ListeningPort::new();
-        port_3.name = String::from("[::1]:1337");
-        let mut port_4 = ListeningPort::new();
-        port_4.name = String::from("[::]:42069");
-        let mut port_5 = ListeningPort::new();
-        port_5.name = String::from("42069");
+    fn filter_address_families_both() {
+        let mut listening_ports = vec![new_family_port("IPv4"), new_family_port("IPv6")];
 
-        let mut port_6 = ListeningPort::new();
-        port_6.name = String::new();
-        let mut port_7 = ListeningPort::new();
-        port_7.name = String::from("abc");
-        let mut port_8 = ListeningPort::new();
-        port_8.name = String::from("def:");
+        filter_address_families(&mut listening_ports, true, true);
+
+        assert_eq!(
+            listening_ports,
+            vec![new_family_port("IPv4"), new_family_port("IPv6")]
+        );
+    }
 
+    #[test]
+    fn filter_address_families_neither() {
         let mut listening_ports = vec![
-            port_1.clone(),
-            port_2.clone(),
-            port_3.clone(),
-            port_4.clone(),
-            port_5.clone(),
-            port_6.clone(),
-            port_7.clone(),
-            port_8.clone(),
+            new_family_port("IPv4"),
+            new_family_port("IPv6"),
+            new_family_port("unix"),
         ];
 
-        filter_ports(
-            &mut listening_ports,
-            &[String::from("1337"), String::from("42069")],
+        filter_address_families(&mut listening_ports, false, false);
+
+        assert_eq!(
+            listening_ports,
+            vec![
+                new_family_port("IPv4"),
+                new_family_port("IPv6"),
+                new_family_port("unix")
+            ]
         );
+    }
 
-        assert!(listening_ports.contains(&port_1));
-        assert!(listening_ports.contains(&port_2));
-        assert!(listening_ports.contains(&port_3));
-        assert!(listening_ports.contains(&port_4));
-        assert!(listening_ports.contains(&port_5));
+    #[test]
+    fn read_pids_file_regular() {
+        let path =
+            std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pids.txt");
 
-        assert!(!listening_ports.contains(&port_6));
-        assert!(!listening_ports.contains(&port_7));
-        assert!(!listening_ports.contains(&port_8));
+        let pids = read_pids_file(&path).unwrap();
+
+        assert_eq!(pids, vec![String::from("723"), String::from("1174")]);
     }
 
     #[test]
-    fn filter_ports_empty() {
-        let mut port_1 = ListeningPort::new();
-        port_1.name = String::from("*:1337");
-        let mut port_2 = ListeningPort::new();
-        port_2.name = String::from("127.0.0.1:1337");
-        let mut port_3 = ListeningPort::new();
-        port_3.name = String::from("[::1]:1337");
+    fn read_pids_file_not_found() {
+        let pids = read_pids_file(Path::new("/does/not/exist"));
 
-        let mut listening_ports = vec![port_1, port_2, port_3];
+        assert!(pids
+            .unwrap_err()
+            .starts_with("Cannot read '/does/not/exist': "));
+    }
+
+    #[test]
+    fn read_pids_file_empty() {
+        let file = tempfile::NamedTempFile::new().unwrap();
 
-        filter_ports(&mut listening_ports, &[]);
+        let pids = read_pids_file(file.path());
 
-        // This is correct. We happen to treat 'no-filters' as
-        // 'keep-everything', but this is not `filter_ports()`' problem.
-        assert!(listening_ports.is_empty());
+        assert_eq!(pids, Err(format!("No PIDs in '{}'", file.path().display())));
     }
 }
@@ -0,0 +1,178 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fs;
+
+use crate::cmd::lsof::Address;
+
+/// Names of well-known ports (e.g., `80` is `http`).
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ServiceNames {
+    names: HashMap<u16, String>,
+    ports: HashMap<String, u16>,
+}
+
+impl ServiceNames {
+    /// Read service names from `/etc/services`.
+    ///
+    /// Names are a nicety, so if the file cannot be read, there simply
+    /// are none.
+    #[cfg(not(tarpaulin_include))]
+    #[must_use]
+    pub fn from_system() -> Self {
+        fs::read_to_string("/etc/services")
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Parse the `services(5)` format.
+    ///
+    /// ```text
+    /// # Comment.
+    /// http        80/tcp    www    # WorldWideWeb HTTP
+    /// https       443/tcp
+    /// ```
+    ///
+    /// The first name given to a port wins, whatever the protocol.
+    /// Malformed lines are skipped.
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        let mut names = HashMap::new();
+        let mut ports = HashMap::new();
+
+        for line in content.lines() {
+            let line = line.split_once('#').map_or(line, |(line, _)| line);
+            let mut fields = line.split_whitespace();
+            let (Some(name), Some(port)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            let Some(Ok(port)) = port.split_once('/').map(|(port, _)| port.parse::<u16>()) else {
+                continue;
+            };
+
+            names.entry(port).or_insert_with(|| String::from(name));
+            ports.entry(String::from(name)).or_insert(port);
+        }
+
+        Self { names, ports }
+    }
+
+    /// Service name of the port number.
+    #[must_use]
+    pub fn lookup(&self, port: u16) -> Option<&str> {
+        self.names.get(&port).map(String::as_str)
+    }
+
+    /// Reverse of [`ServiceNames::lookup()`] (e.g., `http` is 80).
+    #[must_use]
+    pub fn port(&self, name: &str) -> Option<u16> {
+        self.ports.get(name).copied()
+    }
+
+    /// `HOST:PORT` with the port replaced by its service name.
+    ///
+    /// For example, `*:80` becomes `*:http`. Returns `None` if the port
+    /// has no name, or if `name` is not an [`Address`].
+    #[must_use]
+    pub fn rename(&self, name: &str) -> Option<String> {
+        let address = Address::parse(name)?;
+        let service = self.lookup(address.port)?;
+        Some(format!("{}:{service}", address.host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SERVICES: &str = "\
+# Network services, Internet style
+ssh             22/tcp                          # SSH Remote Login Protocol
+http            80/tcp          www             # WorldWideWeb HTTP
+http            80/udp
+https           443/tcp
+postgresql      5432/tcp        postgres
+";
+
+    #[test]
+    fn parse_regular() {
+        let services = ServiceNames::parse(SERVICES);
+
+        assert_eq!(services.lookup(22), Some("ssh"));
+        assert_eq!(services.lookup(80), Some("http"));
+        assert_eq!(services.lookup(443), Some("https"));
+        assert_eq!(services.lookup(5432), Some("postgresql"));
+        assert_eq!(services.lookup(8080), None);
+    }
+
+    #[test]
+    fn parse_first_name_wins() {
+        let services = ServiceNames::parse("http 80/tcp\nwww 80/udp\n");
+
+        assert_eq!(services.lookup(80), Some("http"));
+    }
+
+    #[test]
+    fn port_regular() {
+        let services = ServiceNames::parse(SERVICES);
+
+        assert_eq!(services.port("ssh"), Some(22));
+        assert_eq!(services.port("http"), Some(80));
+        assert_eq!(services.port("postgresql"), Some(5432));
+        assert_eq!(services.port("www"), None);
+        assert_eq!(services.port("8080"), None);
+    }
+
+    #[test]
+    fn port_first_port_wins() {
+        let services = ServiceNames::parse("http 80/tcp\nhttp 8080/tcp\n");
+
+        assert_eq!(services.port("http"), Some(80));
+    }
+
+    #[test]
+    fn parse_skips_malformed_lines() {
+        let services = ServiceNames::parse("http\nhttp www/tcp\nhttp 80\nhttps 443/tcp\n");
+
+        assert_eq!(services, ServiceNames::parse("https 443/tcp"));
+    }
+
+    #[test]
+    fn parse_empty() {
+        assert_eq!(ServiceNames::parse(""), ServiceNames::default());
+    }
+
+    #[test]
+    fn rename_regular() {
+        let services = ServiceNames::parse(SERVICES);
+
+        assert_eq!(services.rename("*:80"), Some(String::from("*:http")));
+        assert_eq!(
+            services.rename("[::1]:5432"),
+            Some(String::from("[::1]:postgresql"))
+        );
+    }
+
+    #[test]
+    fn rename_unknown() {
+        let services = ServiceNames::parse(SERVICES);
+
+        assert_eq!(services.rename("*:8080"), None);
+        assert_eq!(services.rename("*:abc"), None);
+        assert_eq!(services.rename("localhost:80"), None);
+    }
+}
@@ -0,0 +1,287 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::SystemTime;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+
+/// Parse the `START` (or `STARTED`) column of `ps` into a point in time.
+///
+/// `ps` shortens the start time the older the process gets, and the
+/// exact format differs between implementations:
+///
+/// - `09:27` or `5:47PM`: Started during the last 24 hours.
+/// - `Mon06PM`: Started during the last week (macOS).
+/// - `Jul12`: Started this year (Linux).
+/// - `12Jul24`: Started before that (macOS).
+/// - `2023`: Started in a previous year (Linux).
+/// - `Thu Jul 11 09:27:03 2024`: Full timestamp (`lstart`).
+///
+/// `ps` prints times in the local timezone, so they are interpreted as
+/// such. Returns `None` if the format is not recognized.
+#[must_use]
+pub fn parse_ps_start_time(s: &str) -> Option<SystemTime> {
+    parse_ps_start_time_relative_to(s, &Local::now())
+}
+
+/// Like [`parse_ps_start_time()`], but with an explicit "now".
+///
+/// Short formats are relative to the current date (`09:27` means today
+/// at 09:27), which makes them impossible to test against the real
+/// clock.
+#[must_use]
+pub fn parse_ps_start_time_relative_to<Tz: TimeZone>(
+    s: &str,
+    now: &DateTime<Tz>,
+) -> Option<SystemTime> {
+    let s = s.trim();
+    let today = now.date_naive();
+
+    let datetime = parse_time_of_day(s, now.naive_local())
+        .or_else(|| parse_weekday_and_hour(s, today))
+        .or_else(|| parse_month_and_day(s, today))
+        .or_else(|| parse_day_month_and_year(s))
+        .or_else(|| parse_year(s))
+        .or_else(|| parse_full_timestamp(s))?;
+
+    let datetime = now.timezone().from_local_datetime(&datetime).earliest()?;
+
+    Some(datetime.into())
+}
+
+/// `09:27` (Linux), or `5:47PM` (macOS), today, or yesterday if that's
+/// in the future.
+fn parse_time_of_day(s: &str, now: NaiveDateTime) -> Option<NaiveDateTime> {
+    let time = NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%I:%M%p"))
+        .ok()?;
+    let datetime = now.date().and_time(time);
+    if datetime > now {
+        return datetime.checked_sub_days(Days::new(1));
+    }
+    Some(datetime)
+}
+
+/// `Mon06PM` (macOS), the most recent Monday at 6PM.
+fn parse_weekday_and_hour(s: &str, today: NaiveDate) -> Option<NaiveDateTime> {
+    let weekday = s.get(..3)?.parse::<chrono::Weekday>().ok()?;
+    let hour = s.get(3..)?;
+
+    // Not `split_at()`, which panics inside a multi-byte character.
+    let split = hour.len().checked_sub(2)?;
+    let (hour, meridiem) = (hour.get(..split)?, hour.get(split..)?);
+    let hour = hour
+        .parse::<u32>()
+        .ok()
+        .filter(|hour| (1..=12).contains(hour))?;
+    let hour = match meridiem.to_ascii_uppercase().as_str() {
+        "AM" => hour % 12,
+        "PM" => hour % 12 + 12,
+        _ => return None,
+    };
+
+    let days_ago =
+        (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    let date = today.checked_sub_days(Days::new(u64::from(days_ago)))?;
+
+    date.and_hms_opt(hour, 0, 0)
+}
+
+/// `Jul12` (Linux), July 12th of this year, or of last year if that's
+/// in the future.
+fn parse_month_and_day(s: &str, today: NaiveDate) -> Option<NaiveDateTime> {
+    let mut date = NaiveDate::parse_from_str(&format!("{}{s}", today.year()), "%Y%b%d").ok()?;
+    if date > today {
+        date = date.with_year(date.year() - 1)?;
+    }
+    Some(date.and_time(NaiveTime::MIN))
+}
+
+/// `12Jul24` (macOS).
+fn parse_day_month_and_year(s: &str) -> Option<NaiveDateTime> {
+    NaiveDate::parse_from_str(s, "%d%b%y")
+        .ok()
+        .map(|date| date.and_time(NaiveTime::MIN))
+}
+
+/// `2023` (Linux), the beginning of the year.
+fn parse_year(s: &str) -> Option<NaiveDateTime> {
+    if s.len() != 4 || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(s.parse().ok()?, 1, 1).map(|date| date.and_time(NaiveTime::MIN))
+}
+
+/// `Thu Jul 11 09:27:03 2024` (`lstart`).
+fn parse_full_timestamp(s: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(s, "%a %b %e %H:%M:%S %Y").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Wednesday.
+    fn now() -> DateTime<Local> {
+        Local.with_ymd_and_hms(2024, 7, 17, 15, 30, 0).unwrap()
+    }
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, min: u32, sec: u32) -> SystemTime {
+        Local
+            .with_ymd_and_hms(year, month, day, hour, min, sec)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn parse_time_of_day_24h() {
+        let time = parse_ps_start_time_relative_to("09:27", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 17, 9, 27, 0)));
+    }
+
+    #[test]
+    fn parse_time_of_day_12h_pm() {
+        let time = parse_ps_start_time_relative_to("1:47PM", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 17, 13, 47, 0)));
+    }
+
+    #[test]
+    fn parse_time_of_day_12h_am() {
+        let time = parse_ps_start_time_relative_to("12:05AM", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 17, 0, 5, 0)));
+    }
+
+    #[test]
+    fn parse_time_of_day_later_than_now_is_yesterday() {
+        assert_eq!(
+            parse_ps_start_time_relative_to("23:50", &now()),
+            Some(local(2024, 7, 16, 23, 50, 0))
+        );
+        assert_eq!(
+            parse_ps_start_time_relative_to("5:47PM", &now()),
+            Some(local(2024, 7, 16, 17, 47, 0))
+        );
+    }
+
+    #[test]
+    fn parse_weekday_and_hour_earlier_this_week() {
+        let time = parse_ps_start_time_relative_to("Mon06PM", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 15, 18, 0, 0)));
+    }
+
+    #[test]
+    fn parse_weekday_and_hour_last_week() {
+        let time = parse_ps_start_time_relative_to("Thu11AM", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 11, 11, 0, 0)));
+    }
+
+    #[test]
+    fn parse_weekday_and_hour_today() {
+        let time = parse_ps_start_time_relative_to("Wed12PM", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 17, 12, 0, 0)));
+    }
+
+    #[test]
+    fn parse_weekday_and_hour_invalid_hour() {
+        assert_eq!(parse_ps_start_time_relative_to("Mon13PM", &now()), None);
+        assert_eq!(parse_ps_start_time_relative_to("Mon06XM", &now()), None);
+    }
+
+    #[test]
+    fn parse_month_and_day_this_year() {
+        let time = parse_ps_start_time_relative_to("Jul12", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 12, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_month_and_day_last_year() {
+        let time = parse_ps_start_time_relative_to("Dec24", &now());
+
+        assert_eq!(time, Some(local(2023, 12, 24, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_day_month_and_year() {
+        let time = parse_ps_start_time_relative_to("3Aug23", &now());
+
+        assert_eq!(time, Some(local(2023, 8, 3, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_year() {
+        let time = parse_ps_start_time_relative_to("2023", &now());
+
+        assert_eq!(time, Some(local(2023, 1, 1, 0, 0, 0)));
+    }
+
+    #[test]
+    fn parse_full_timestamp() {
+        let time = parse_ps_start_time_relative_to("Thu Jul 11 09:27:03 2024", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 11, 9, 27, 3)));
+    }
+
+    #[test]
+    fn parse_full_timestamp_single_digit_day() {
+        let time = parse_ps_start_time_relative_to("Wed Jul  3 09:27:03 2024", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 3, 9, 27, 3)));
+    }
+
+    #[test]
+    fn parse_surrounding_whitespace_is_ignored() {
+        let time = parse_ps_start_time_relative_to("  09:27\n", &now());
+
+        assert_eq!(time, Some(local(2024, 7, 17, 9, 27, 0)));
+    }
+
+    #[test]
+    fn parse_unknown_format() {
+        assert_eq!(parse_ps_start_time_relative_to("", &now()), None);
+        assert_eq!(parse_ps_start_time_relative_to("abc", &now()), None);
+        assert_eq!(parse_ps_start_time_relative_to("25:00", &now()), None);
+        assert_eq!(parse_ps_start_time_relative_to("20233", &now()), None);
+    }
+
+    #[test]
+    fn parse_weekday_and_hour_non_ascii() {
+        assert_eq!(parse_ps_start_time_relative_to("Monéx", &now()), None);
+        assert_eq!(parse_ps_start_time_relative_to("Mon1é", &now()), None);
+    }
+
+    #[test]
+    fn parse_ps_start_time_uses_current_date() {
+        let before = Local::now().date_naive();
+        let time = parse_ps_start_time("00:00");
+        let after = Local::now().date_naive();
+
+        // Midnight of today, unless the date changed in between.
+        let midnight = |date: NaiveDate| {
+            Local
+                .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                .earliest()
+                .map(SystemTime::from)
+        };
+        assert!(time == midnight(before) || time == midnight(after));
+    }
+}
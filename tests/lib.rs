@@ -0,0 +1,112 @@
+// ports — List listening ports.
+// Copyright (C) 2024  Quentin Richert
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Public library API, as seen from outside the crate.
+
+use ports::formatters::{Formatter, JsonFormatter};
+use ports::{IncludeUdp, ListeningPort, ListeningPortBuilder, Lsof, ProcessInfo};
+
+const LSOF_OUTPUT: &str = "\
+COMMAND PID USER FD TYPE DEVICE SIZE/OFF NODE NAME
+sshd 1174 root 3u IPv4 22312 0t0 TCP *:22 (LISTEN)
+python3 2061 dev 3u IPv4 30110 0t0 TCP 127.0.0.1:8000 (LISTEN)
+";
+
+#[test]
+fn parse_lsof_output() {
+    let listening_ports = Lsof::parse_strict(LSOF_OUTPUT, IncludeUdp::No).unwrap();
+
+    assert_eq!(
+        listening_ports,
+        vec![
+            ListeningPortBuilder::new()
+                .command("sshd")
+                .pid("1174")
+                .user("root")
+                .type_("IPv4")
+                .node("TCP")
+                .name("*:22")
+                .build(),
+            ListeningPortBuilder::new()
+                .command("python3")
+                .pid("2061")
+                .user("dev")
+                .type_("IPv4")
+                .node("TCP")
+                .name("127.0.0.1:8000")
+                .build(),
+        ]
+    );
+}
+
+#[test]
+fn enrich_with_process_info() {
+    let mut pinfo = ProcessInfo::new();
+    pinfo.pid = String::from("2061");
+    pinfo.command = String::from("/usr/bin/python3 -m http.server");
+
+    let mut port = ListeningPortBuilder::new()
+        .command("python3")
+        .pid("2061")
+        .build();
+    port.enrich_with_process_info(&[pinfo]);
+
+    assert_eq!(port.full_command(), Some("/usr/bin/python3 -m http.server"));
+    assert_eq!(port.process_name(), "python3");
+}
+
+#[test]
+fn format_ports() {
+    let port = ListeningPortBuilder::new()
+        .command("sshd")
+        .pid("1174")
+        .name("*:22")
+        .build();
+
+    let output = JsonFormatter.format(&[port, ListeningPort::new()]);
+
+    assert!(output.starts_with(r#"[{"command":"sshd","pid":"1174","#));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let mut pinfo = ProcessInfo::new();
+    pinfo.user = String::from("dev");
+    pinfo.pid = String::from("2061");
+    pinfo.pc_cpu = String::from("0.3");
+    pinfo.pc_mem = String::from("1.2");
+    pinfo.start = String::from("09:27");
+    pinfo.time = String::from("0:01.52");
+    pinfo.command = String::from("python3 -m http.server");
+    let port = ListeningPortBuilder::new()
+        .command("python3")
+        .pid("2061")
+        .user("dev")
+        .type_("IPv4")
+        .node("TCP")
+        .name("127.0.0.1:8000")
+        .pinfo(pinfo)
+        .build();
+
+    let json = serde_json::to_string(&port).unwrap();
+
+    assert!(json.contains(r#""type":"IPv4""#));
+    assert!(json.contains(r#""cpu_percent":"0.3""#));
+    assert!(json.contains(r#""mem_percent":"1.2""#));
+    assert!(!json.contains("_cannot_instantiate"));
+    assert_eq!(serde_json::from_str::<ListeningPort>(&json).unwrap(), port);
+}